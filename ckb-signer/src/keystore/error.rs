@@ -14,6 +14,9 @@ pub enum Error {
     #[error("Key mismatch, got {got:x}, expected: {expected:x}")]
     KeyMismatch { got: H160, expected: H160 },
 
+    #[error("Account {0:x} is watch-only (imported from extended public key), it has no private key to sign or export")]
+    WatchOnly(H160),
+
     #[error("Key already exists {0:x}")]
     KeyExists(H160),
 
@@ -4,7 +4,7 @@ pub(crate) mod signer;
 mod util;
 
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fmt;
 use std::fs;
@@ -41,6 +41,8 @@ pub struct KeyStore {
     storage: PassphraseKeyStore,
     files: HashMap<H160, PathBuf>,
     ckb_roots: HashMap<H160, CkbRoot>,
+    // Accounts imported from extended public key, they have no private key
+    watch_only: HashSet<H160>,
     unlocked_keys: HashMap<H160, TimedKey>,
 }
 
@@ -51,6 +53,7 @@ impl Clone for KeyStore {
             storage: self.storage.clone(),
             files: self.files.clone(),
             ckb_roots: self.ckb_roots.clone(),
+            watch_only: self.watch_only.clone(),
             unlocked_keys: HashMap::default(),
         }
     }
@@ -67,6 +70,7 @@ impl KeyStore {
             },
             files: HashMap::default(),
             ckb_roots: HashMap::default(),
+            watch_only: HashSet::default(),
             unlocked_keys: HashMap::default(),
         };
         key_store.refresh_dir()?;
@@ -98,6 +102,12 @@ impl KeyStore {
         }
         self.files.contains_key(hash160)
     }
+    pub fn is_watch_only(&mut self, hash160: &H160, refresh: bool) -> bool {
+        if refresh {
+            self.refresh_dir().ok();
+        }
+        self.watch_only.contains(hash160)
+    }
 
     pub fn update(
         &mut self,
@@ -177,6 +187,48 @@ impl KeyStore {
             Ok(key.hash160().clone())
         }
     }
+    /// Import a watch-only account from the extended public keys of path `m` and
+    /// `m/44'/309'/0'`. The account id is the blake160 hash of the master public key (same as
+    /// the full account), and the addresses are derived from the `m/44'/309'/0'` one.
+    pub fn import_xpub(
+        &mut self,
+        master_pubkey: &ExtendedPubKey,
+        extended_pubkey: &ExtendedPubKey,
+    ) -> Result<H160, Error> {
+        if master_pubkey.depth != 0 {
+            return Err(Error::Other(String::from(
+                "The master extended public key must be of path m",
+            )));
+        }
+        let ckb_root = CkbRoot::from_extended_pubkey(extended_pubkey)?;
+        let hash160 =
+            H160::from_slice(&blake2b_256(&master_pubkey.public_key.serialize()[..])[0..20])
+                .expect("Generate hash(H160) from pubkey failed");
+        if self.files.contains_key(&hash160) {
+            return Err(Error::KeyExists(hash160));
+        }
+        let mut buf = Uuid::encode_buffer();
+        let id_str = Uuid::new_v4()
+            .to_hyphenated()
+            .encode_lower(&mut buf)
+            .to_string();
+        let json_value = serde_json::json!({
+            "origin": KEYSTORE_ORIGIN,
+            "id": id_str,
+            "version": KEYSTORE_VERSION,
+            "hash160": format!("{:x}", hash160),
+            "watch_only": true,
+            "root_pubkey": hex_string(&master_pubkey.public_key.serialize()[..]),
+            "ckb_root": ckb_root.to_json(),
+        });
+        let filepath = self
+            .storage
+            .store_json(key_filename(&hash160), &json_value)?;
+        self.files.insert(hash160.clone(), filepath);
+        self.ckb_roots.insert(hash160.clone(), ckb_root);
+        self.watch_only.insert(hash160.clone());
+        Ok(hash160)
+    }
     pub fn upgrade(&self, hash160: &H160, password: &[u8]) -> Result<(), Error> {
         let filepath = self.get_filepath(hash160)?;
         let backup_path = filepath.with_file_name(format!("{:#x}.upgrade-backup", hash160));
@@ -300,6 +352,7 @@ impl KeyStore {
     pub fn refresh_dir(&mut self) -> Result<(), Error> {
         let mut files = HashMap::default();
         let mut ckb_roots = HashMap::default();
+        let mut watch_only = HashSet::default();
         for entry in fs::read_dir(&self.keys_dir)? {
             let entry = entry?;
             let path = entry.path();
@@ -324,6 +377,9 @@ impl KeyStore {
                                 let ckb_root_opt = util::get_value(&value, "ckb_root")
                                     .ok()
                                     .and_then(|value| CkbRoot::from_json(value).ok());
+                                if is_watch_only_json(&value) {
+                                    watch_only.insert(hash160.clone());
+                                }
                                 (hash160, ckb_root_opt)
                             })
                     })
@@ -337,6 +393,7 @@ impl KeyStore {
         }
         self.files = files;
         self.ckb_roots = ckb_roots;
+        self.watch_only = watch_only;
         Ok(())
     }
 
@@ -402,6 +459,9 @@ impl PassphraseKeyStore {
         let mut file = fs::File::open(filepath)?;
         let data = serde_json::from_reader(&mut file)
             .map_err(|err| Error::ParseJsonFailed(err.to_string()))?;
+        if is_watch_only_json(&data) {
+            return Err(Error::WatchOnly(hash160.clone()));
+        }
         let key = Key::from_json(&data, password)?;
         if key.hash160() != hash160 {
             return Err(Error::KeyMismatch {
@@ -419,8 +479,16 @@ impl PassphraseKeyStore {
         key: &Key,
         password: &[u8],
     ) -> Result<PathBuf, Error> {
-        let filepath = self.join_path(filename);
         let json_value = key.to_json(password, self.scrypt_type);
+        self.store_json(filename, &json_value)
+    }
+
+    fn store_json<P: AsRef<Path>>(
+        &self,
+        filename: P,
+        json_value: &serde_json::Value,
+    ) -> Result<PathBuf, Error> {
+        let filepath = self.join_path(filename);
 
         #[cfg(unix)]
         let mut file = {
@@ -438,7 +506,7 @@ impl PassphraseKeyStore {
         #[cfg(not(unix))]
         let mut file = fs::File::create(&filepath)?;
 
-        serde_json::to_writer(&mut file, &json_value).map_err(|err| Error::Io(err.to_string()))?;
+        serde_json::to_writer(&mut file, json_value).map_err(|err| Error::Io(err.to_string()))?;
         Ok(filepath)
     }
 
@@ -452,6 +520,30 @@ impl PassphraseKeyStore {
     }
 }
 
+fn is_watch_only_json(value: &serde_json::Value) -> bool {
+    value
+        .get("watch_only")
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn key_filename(hash160: &H160) -> String {
+    let utc_now = Utc::now();
+    let date = utc_now.date_naive();
+    let time = utc_now.time();
+    format!(
+        "UTC--{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z--{:x}",
+        date.year(),
+        date.month(),
+        date.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.nanosecond(),
+        hash160,
+    )
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum KeyTimeout {
     Infinite,
@@ -615,6 +707,30 @@ impl CkbRoot {
         })
     }
 
+    /// Build from the extended public key of path `m/44'/309'/0'` (e.g. exported by
+    /// `account bitcoin-xpub`)
+    pub fn from_extended_pubkey(extended_pubkey: &ExtendedPubKey) -> Result<CkbRoot, Error> {
+        if extended_pubkey.depth != 3
+            || extended_pubkey.child_number
+                != ChildNumber::from_hardened_idx(0).expect("child number")
+        {
+            return Err(Error::Other(format!(
+                "The extended public key must be derived from path {}",
+                CKB_ROOT_PATH
+            )));
+        }
+        Ok(CkbRoot {
+            path: CKB_ROOT_PATH,
+            extended_pubkey: *extended_pubkey,
+        })
+    }
+
+    /// blake160 hash of the public key
+    pub fn hash160(&self) -> H160 {
+        H160::from_slice(&blake2b_256(&self.extended_pubkey.public_key.serialize()[..])[0..20])
+            .expect("Generate hash(H160) from pubkey failed")
+    }
+
     pub fn derived_key_set(
         &self,
         external_max_len: u32,
@@ -734,20 +850,7 @@ impl Key {
     }
 
     pub fn filename(&self) -> String {
-        let utc_now = Utc::now();
-        let date = utc_now.date_naive();
-        let time = utc_now.time();
-        format!(
-            "UTC--{:04}-{:02}-{:02}T{:02}-{:02}-{:02}.{:09}Z--{:x}",
-            date.year(),
-            date.month(),
-            date.day(),
-            time.hour(),
-            time.minute(),
-            time.second(),
-            time.nanosecond(),
-            self.hash160(),
-        )
+        key_filename(self.hash160())
     }
 
     pub fn from_json(data: &serde_json::Value, password: &[u8]) -> Result<Key, Error> {
@@ -979,4 +1082,67 @@ mod tests {
         assert_eq!(key_set, key_set_by_index);
         assert_eq!(key_set, expected_key_set);
     }
+
+    #[test]
+    fn test_ckb_root_from_extended_pubkey() {
+        let master_privkey = MasterPrivKey::from_bytes([3u8; 64]).expect("master privkey");
+        let ckb_root = master_privkey.ckb_root();
+        let path = DerivationPath::from_str(CKB_ROOT_PATH).unwrap();
+        let xpub = master_privkey.extended_pubkey(&path).to_string();
+
+        let watch_only_root =
+            CkbRoot::from_extended_pubkey(&ExtendedPubKey::from_str(&xpub).unwrap()).unwrap();
+        assert_eq!(
+            watch_only_root.derived_key_set_by_index(0, 5, 0, 5),
+            ckb_root.derived_key_set_by_index(0, 5, 0, 5)
+        );
+        assert_eq!(
+            CkbRoot::from_json(&watch_only_root.to_json())
+                .unwrap()
+                .hash160(),
+            watch_only_root.hash160()
+        );
+
+        let other_path = DerivationPath::from_str("m/44'/309'/1'").unwrap();
+        let other_xpub = master_privkey.extended_pubkey(&other_path);
+        assert!(CkbRoot::from_extended_pubkey(&other_xpub).is_err());
+    }
+
+    #[test]
+    fn test_import_xpub() {
+        let base_dir = std::env::temp_dir().join(format!("ckb-signer-test-{}", Uuid::new_v4()));
+        let full_dir = base_dir.join("full");
+        let watch_only_dir = base_dir.join("watch-only");
+        fs::create_dir_all(&full_dir).unwrap();
+        fs::create_dir_all(&watch_only_dir).unwrap();
+
+        let password = b"123";
+        let mut full_store = KeyStore::from_dir(full_dir, ScryptType::Light).unwrap();
+        let lock_arg = full_store.new_account(password).unwrap();
+        let master_xpub = full_store
+            .extended_pubkey_with_password(&lock_arg, &DerivationPath::master(), password)
+            .unwrap();
+        let path = DerivationPath::from_str(CKB_ROOT_PATH).unwrap();
+        let xpub = full_store
+            .extended_pubkey_with_password(&lock_arg, &path, password)
+            .unwrap();
+
+        let mut watch_only_store = KeyStore::from_dir(watch_only_dir, ScryptType::Light).unwrap();
+        assert!(watch_only_store.import_xpub(&xpub, &xpub).is_err());
+        assert_eq!(
+            watch_only_store.import_xpub(&master_xpub, &xpub).unwrap(),
+            lock_arg
+        );
+        assert!(watch_only_store.is_watch_only(&lock_arg, true));
+        let full_key_set = full_store
+            .get_ckb_root(&lock_arg, true)
+            .unwrap()
+            .derived_key_set_by_index(0, 5, 0, 5);
+        let watch_only_key_set = watch_only_store
+            .get_ckb_root(&lock_arg, true)
+            .unwrap()
+            .derived_key_set_by_index(0, 5, 0, 5);
+        assert_eq!(full_key_set, watch_only_key_set);
+        fs::remove_dir_all(&base_dir).ok();
+    }
 }
@@ -13,9 +13,9 @@ use ckb_signer::KeyStore;
 
 use crate::plugin::PluginManager;
 use crate::subcommands::{
-    AccountSubCommand, CliSubCommand, DAOSubCommand, DeploySubCommand, MockTxSubCommand,
-    MoleculeSubCommand, PluginSubCommand, RpcSubCommand, SudtSubCommand, TxSubCommand,
-    UtilSubCommand, WalletSubCommand,
+    AccountSubCommand, AddressBookSubCommand, CliSubCommand, DAOSubCommand, DeploySubCommand,
+    MockTxSubCommand, MoleculeSubCommand, PluginSubCommand, RpcSubCommand, SudtSubCommand,
+    TxSubCommand, UtilSubCommand, WalletSubCommand,
 };
 use crate::utils::{
     completer::CkbCompleter,
@@ -33,6 +33,7 @@ pub struct InteractiveEnv {
     config: GlobalConfig,
     config_file: PathBuf,
     history_file: PathBuf,
+    ckb_cli_dir: PathBuf,
     parser: clap::App<'static>,
     plugin_mgr: PluginManager,
     key_store: KeyStore,
@@ -56,7 +57,7 @@ impl InteractiveEnv {
         let mut config_file = ckb_cli_dir.clone();
         config_file.push("config");
 
-        let mut env_file = ckb_cli_dir;
+        let mut env_file = ckb_cli_dir.clone();
         env_file.push("env_vars");
         if env_file.as_path().exists() {
             let file = fs::File::open(&env_file).map_err(|err| err.to_string())?;
@@ -74,6 +75,7 @@ impl InteractiveEnv {
             config,
             config_file,
             history_file,
+            ckb_cli_dir,
             parser,
             plugin_mgr,
             key_store,
@@ -313,6 +315,12 @@ impl InteractiveEnv {
                     output.print(format, color);
                     Ok(())
                 }
+                ("address-book", Some(sub_matches)) => {
+                    let output = AddressBookSubCommand::new(&mut self.rpc_client)
+                        .process(sub_matches, debug)?;
+                    output.print(format, color);
+                    Ok(())
+                }
                 ("mock-tx", Some(sub_matches)) => {
                     let genesis_info = self.genesis_info().ok();
                     let output = MockTxSubCommand::new(
@@ -355,6 +363,7 @@ impl InteractiveEnv {
                         &mut self.rpc_client,
                         &mut self.plugin_mgr,
                         Some(genesis_info),
+                        &self.ckb_cli_dir,
                     )
                     .process(sub_matches, debug)?;
                     output.print(format, color);
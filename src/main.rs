@@ -12,12 +12,13 @@ use clap::{App, AppSettings, Arg};
 use interactive::InteractiveEnv;
 use plugin::PluginManager;
 use subcommands::{
-    AccountSubCommand, ApiServerSubCommand, CliSubCommand, DAOSubCommand, DeploySubCommand,
-    MockTxSubCommand, MoleculeSubCommand, PluginSubCommand, PubSubCommand, RpcSubCommand,
-    SudtSubCommand, TxSubCommand, UtilSubCommand, WalletSubCommand,
+    AccountSubCommand, AddressBookSubCommand, ApiServerSubCommand, CliSubCommand, DAOSubCommand,
+    DeploySubCommand, MockTxSubCommand, MoleculeSubCommand, PluginSubCommand, PubSubCommand,
+    RpcSubCommand, SudtSubCommand, TxSubCommand, UtilSubCommand, WalletSubCommand,
 };
 use utils::other::get_genesis_info;
 use utils::{
+    address_book,
     arg_parser::{ArgParser, UrlParser},
     config::GlobalConfig,
     other::{check_alerts, get_key_store, get_network_type},
@@ -46,15 +47,8 @@ fn main() -> Result<(), io::Error> {
     //   revisit here when clap updated.
     let version_short = format!("{}\n", version.short());
     let version_long = format!("{}\n", version.long());
-    let matches = build_cli(version_short.as_str(), version_long.as_str()).get_matches();
 
     let mut env_map: HashMap<String, String> = env::vars().collect();
-    let ckb_url_opt = matches
-        .value_of("url")
-        .map(ToOwned::to_owned)
-        .or_else(|| env_map.remove("API_URL"));
-    let local_only = matches.is_present("local-only");
-
     let ckb_cli_dir = if let Some(dir_string) = env_map.remove("CKB_CLI_HOME") {
         let dir = PathBuf::from(dir_string.as_str());
         if dir.exists() && !dir.is_dir() {
@@ -72,6 +66,15 @@ fn main() -> Result<(), io::Error> {
     if !ckb_cli_dir.exists() {
         fs::create_dir_all(&ckb_cli_dir)?;
     }
+    // Address arguments may reference address book entries, so init it before parsing arguments
+    address_book::init(&ckb_cli_dir);
+
+    let matches = build_cli(version_short.as_str(), version_long.as_str()).get_matches();
+    let ckb_url_opt = matches
+        .value_of("url")
+        .map(ToOwned::to_owned)
+        .or_else(|| env_map.remove("API_URL"));
+    let local_only = matches.is_present("local-only");
 
     let mut config = GlobalConfig::new(ckb_url_opt.clone());
     let mut config_file = ckb_cli_dir.clone();
@@ -130,6 +133,9 @@ fn main() -> Result<(), io::Error> {
         ("account", Some(sub_matches)) => {
             AccountSubCommand::new(&mut plugin_mgr, &mut key_store).process(sub_matches, debug)
         }
+        ("address-book", Some(sub_matches)) => {
+            AddressBookSubCommand::new(&mut rpc_client).process(sub_matches, debug)
+        }
         ("mock-tx", Some(sub_matches)) => {
             MockTxSubCommand::new(&mut rpc_client, &mut plugin_mgr, None)
                 .process(sub_matches, debug)
@@ -141,14 +147,15 @@ fn main() -> Result<(), io::Error> {
             UtilSubCommand::new(&mut rpc_client, &mut plugin_mgr).process(sub_matches, debug)
         }
         ("server", Some(sub_matches)) => {
-            ApiServerSubCommand::new(&mut rpc_client, plugin_mgr, None).process(sub_matches, debug)
+            ApiServerSubCommand::new(&mut rpc_client, plugin_mgr, None, ckb_cli_dir)
+                .process(sub_matches, debug)
         }
         ("plugin", Some(sub_matches)) => {
             PluginSubCommand::new(&mut plugin_mgr).process(sub_matches, debug)
         }
         ("molecule", Some(sub_matches)) => MoleculeSubCommand::new().process(sub_matches, debug),
         ("wallet", Some(sub_matches)) => {
-            WalletSubCommand::new(&mut rpc_client, &mut plugin_mgr, None)
+            WalletSubCommand::new(&mut rpc_client, &mut plugin_mgr, None, &ckb_cli_dir)
                 .process(sub_matches, debug)
         }
         ("dao", Some(sub_matches)) => {
@@ -235,6 +242,7 @@ pub fn build_cli<'a>(version_short: &'a str, version_long: &'a str) -> App<'a> {
         .global_setting(AppSettings::DeriveDisplayOrder)
         .subcommand(RpcSubCommand::subcommand().subcommand(PubSubCommand::subcommand()))
         .subcommand(AccountSubCommand::subcommand("account"))
+        .subcommand(AddressBookSubCommand::subcommand("address-book"))
         .subcommand(MockTxSubCommand::subcommand("mock-tx"))
         .subcommand(TxSubCommand::subcommand("tx"))
         .subcommand(ApiServerSubCommand::subcommand("server"))
@@ -344,6 +352,7 @@ You may also use some public available nodes, check the list of public nodes: ht
         )
         .subcommand(RpcSubCommand::subcommand())
         .subcommand(AccountSubCommand::subcommand("account"))
+        .subcommand(AddressBookSubCommand::subcommand("address-book"))
         .subcommand(MockTxSubCommand::subcommand("mock-tx"))
         .subcommand(TxSubCommand::subcommand("tx"))
         .subcommand(UtilSubCommand::subcommand("util"))
@@ -6,7 +6,7 @@ use bitcoin::util::bip32::DerivationPath;
 use crossbeam_channel::bounded;
 
 use ckb_sdk::util::serialize_signature;
-use ckb_signer::{DerivedKeySet, Key, KeyStore, MasterPrivKey};
+use ckb_signer::{DerivedKeySet, Key, KeyStore, KeyStoreError, MasterPrivKey};
 use ckb_types::core::service::Request;
 use plugin_protocol::{JsonrpcError, KeyStoreRequest, PluginRequest, PluginResponse};
 
@@ -172,6 +172,9 @@ impl DefaultKeyStore {
                     password,
                     recoverable,
                 } => {
+                    if keystore.is_watch_only(&hash160, true) {
+                        return Err(KeyStoreError::WatchOnly(hash160).to_string());
+                    }
                     let password =
                         password.ok_or_else(|| String::from(ERROR_KEYSTORE_REQUIRE_PASSWORD))?;
                     let path = DerivationPath::from_str(&path).map_err(|err| err.to_string())?;
@@ -4,7 +4,7 @@ use std::io::Write;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 
-use bitcoin::util::bip32::DerivationPath;
+use bitcoin::util::bip32::{DerivationPath, ExtendedPubKey};
 
 use ckb_sdk::{Address, AddressPayload, NetworkType};
 use ckb_signer::{Key, KeyStore, MasterPrivKey};
@@ -20,6 +20,7 @@ use crate::utils::{
         ArgParser, ExtendedPrivkeyPathParser, FilePathParser, FixedHashParser, FromStrParser,
         HexParser, PrivkeyPathParser, PrivkeyWrapper,
     },
+    omnilock,
     other::{address_json, read_password},
 };
 
@@ -74,12 +75,14 @@ impl<'a> AccountSubCommand<'a> {
     * lock_hash: The lock script hash of secp256k1_blake160_sighash_all lock (See [1]).
     * has_ckb_pubkey_derivation_root_path: The CKB public key derivation root path (m/44'/309'/0') is stored so that password is not required to do public key derivation.
     * address: The Mainnet/Testnet addresses of secp256k1_blake160_sighash_all lock (See [1]).
+    * omnilock_address: The Mainnet/Testnet full format addresses of omnilock in secp256k1 mode (See [3]).
 
   When `source` is \"[plugin]: xxx_keysotre_plugin\" means the account is stored in keystore plugin (Ledger plugin like [2]). If the account metadata is imported by `ckb-cli account import-from-plugin` the output fields are just like \"Local File System\". If the account is not imported, the output fields are:
     * account-id: The account id used to import the account metadata from plugin.
 
 [1]: https://github.com/nervosnetwork/ckb-system-scripts/blob/master/c/secp256k1_blake160_sighash_all.c
-[2]: https://github.com/obsidiansystems/ckb-plugin-ledger"),
+[2]: https://github.com/obsidiansystems/ckb-plugin-ledger
+[3]: https://github.com/nervosnetwork/rfcs/blob/master/rfcs/0042-omnilock/0042-omnilock.md"),
                 App::new("new").about("Create a new account and print related information."),
                 App::new("import")
                     .about("Import an unencrypted private key from <privkey-path> and create a new account.")
@@ -122,6 +125,24 @@ impl<'a> AccountSubCommand<'a> {
                             .validator(|input| FilePathParser::new(true).validate(input))
                             .about("The keystore file path (json format)")
                     ),
+                App::new("import-xpub")
+                    .about("Import a watch-only account from BIP-32 extended public keys of path m and m/44'/309'/0' (see: `account bitcoin-xpub`), it can query balances of derived addresses but can not sign")
+                    .arg(
+                        Arg::with_name("master-xpub")
+                            .long("master-xpub")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FromStrParser::<ExtendedPubKey>::new().validate(input))
+                            .about("The extended public key of path m in Base58Check format (with xpub prefix), its blake160 hash is the account lock arg")
+                    )
+                    .arg(
+                        Arg::with_name("xpub")
+                            .long("xpub")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FromStrParser::<ExtendedPubKey>::new().validate(input))
+                            .about("The extended public key of path m/44'/309'/0' in Base58Check format (with xpub prefix), the addresses are derived from it")
+                    ),
                 App::new("update")
                     .about("Update password of an account")
                     .arg(lock_arg().required(true)),
@@ -233,7 +254,7 @@ impl<'a> CliSubCommand for AccountSubCommand<'a> {
                                 }
                             } else {
                                 let has_ckb_root = self.key_store.get_ckb_root(&lock_arg, false).is_some();
-                                serde_json::json!({
+                                let mut value = serde_json::json!({
                                     "#": idx,
                                     "source": source,
                                     "lock_arg": format!("{:#x}", lock_arg),
@@ -241,7 +262,12 @@ impl<'a> CliSubCommand for AccountSubCommand<'a> {
                                     "has_ckb_pubkey_derivation_root_path": has_ckb_root,
                                     "address": address_json(address_payload.clone(), true),
                                     "address(deprecated)": address_json(address_payload, false),
-                                })
+                                    "omnilock_address": omnilock::pubkey_hash_address_json(&lock_arg),
+                                });
+                                if self.key_store.is_watch_only(&lock_arg, false) {
+                                    value["watch_only"] = serde_json::json!(true);
+                                }
+                                value
                             }
                         } else {
                             serde_json::json!({
@@ -341,6 +367,21 @@ impl<'a> CliSubCommand for AccountSubCommand<'a> {
                 });
                 Ok(Output::new_output(resp))
             }
+            ("import-xpub", Some(m)) => {
+                let master_pubkey: ExtendedPubKey =
+                    FromStrParser::<ExtendedPubKey>::new().from_matches(m, "master-xpub")?;
+                let extended_pubkey: ExtendedPubKey =
+                    FromStrParser::<ExtendedPubKey>::new().from_matches(m, "xpub")?;
+                let lock_arg = self
+                    .key_store
+                    .import_xpub(&master_pubkey, &extended_pubkey)
+                    .map_err(|err| err.to_string())?;
+                let resp = serde_json::json!({
+                    "lock_arg": format!("{:#x}", lock_arg),
+                    "watch_only": true,
+                });
+                Ok(Output::new_output(resp))
+            }
             ("update", Some(m)) => {
                 let lock_arg: H160 =
                     FixedHashParser::<H160>::default().from_matches(m, "lock-arg")?;
@@ -0,0 +1,119 @@
+use ckb_sdk::{Address, NetworkType};
+use clap::{App, Arg, ArgMatches};
+
+use super::{CliSubCommand, Output};
+use crate::utils::{
+    address_book::AddressBook,
+    arg_parser::{AddressParser, ArgParser},
+    other::get_network_type,
+    rpc::HttpRpcClient,
+};
+
+const NETWORK_NAMES: &[&str] = &["ckb", "ckb_testnet", "ckb_staging", "ckb_dev"];
+
+pub struct AddressBookSubCommand<'a> {
+    rpc_client: &'a mut HttpRpcClient,
+}
+
+impl<'a> AddressBookSubCommand<'a> {
+    pub fn new(rpc_client: &'a mut HttpRpcClient) -> AddressBookSubCommand<'a> {
+        AddressBookSubCommand { rpc_client }
+    }
+
+    pub fn subcommand(name: &'static str) -> App<'static> {
+        let arg_name = Arg::with_name("name")
+            .long("name")
+            .required(true)
+            .takes_value(true)
+            .about("The address book entry name (use it as `@name` in address arguments)");
+        let arg_network = Arg::with_name("network")
+            .long("network")
+            .takes_value(true)
+            .possible_values(NETWORK_NAMES);
+        App::new(name)
+            .about("Address book management, the addresses can be referenced by `@name` wherever an address is accepted")
+            .subcommands(vec![
+                App::new("add")
+                    .about("Add an address to address book")
+                    .arg(arg_name.clone())
+                    .arg(
+                        Arg::with_name("address")
+                            .long("address")
+                            .required(true)
+                            .takes_value(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .about("The address"),
+                    )
+                    .arg(arg_network.clone().about(
+                        "The network the entry is pinned to (default: the network of current CKB node)",
+                    )),
+                App::new("list")
+                    .about("List all addresses in address book")
+                    .arg(arg_network.about("Only list the entries of this network")),
+                App::new("remove")
+                    .about("Remove an address from address book")
+                    .arg(arg_name.clone()),
+                App::new("rename")
+                    .about("Rename an address book entry")
+                    .arg(arg_name)
+                    .arg(
+                        Arg::with_name("new-name")
+                            .long("new-name")
+                            .required(true)
+                            .takes_value(true)
+                            .about("The new entry name"),
+                    ),
+            ])
+    }
+}
+
+impl<'a> CliSubCommand for AddressBookSubCommand<'a> {
+    fn process(&mut self, matches: &ArgMatches, _debug: bool) -> Result<Output, String> {
+        match matches.subcommand() {
+            ("add", Some(m)) => {
+                let name = m.value_of("name").unwrap();
+                let network = match m.value_of("network") {
+                    Some(network) => NetworkType::from_raw_str(network).unwrap(),
+                    None => get_network_type(self.rpc_client).map_err(|err| {
+                        format!(
+                            "Get network type from CKB node failed: {}, try `--network` argument",
+                            err
+                        )
+                    })?,
+                };
+                let address: Address = AddressParser::default()
+                    .set_network(network)
+                    .from_matches(m, "address")?;
+                let mut address_book = AddressBook::load()?;
+                address_book.add(name, address.to_string(), network)?;
+                address_book.save()?;
+                Ok(Output::new_output(address_book.get(name)))
+            }
+            ("list", Some(m)) => {
+                let network = m.value_of("network");
+                let address_book = AddressBook::load()?;
+                let entries = address_book
+                    .entries()
+                    .filter(|entry| network.map(|n| n == entry.network).unwrap_or(true))
+                    .collect::<Vec<_>>();
+                Ok(Output::new_output(entries))
+            }
+            ("remove", Some(m)) => {
+                let name = m.value_of("name").unwrap();
+                let mut address_book = AddressBook::load()?;
+                let entry = address_book.remove(name)?;
+                address_book.save()?;
+                Ok(Output::new_output(entry))
+            }
+            ("rename", Some(m)) => {
+                let name = m.value_of("name").unwrap();
+                let new_name = m.value_of("new-name").unwrap();
+                let mut address_book = AddressBook::load()?;
+                address_book.rename(name, new_name)?;
+                address_book.save()?;
+                Ok(Output::new_output(address_book.get(new_name)))
+            }
+            _ => Err(Self::subcommand("address-book").generate_usage()),
+        }
+    }
+}
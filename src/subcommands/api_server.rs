@@ -1,4 +1,5 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -28,6 +29,7 @@ pub struct ApiServerSubCommand<'a> {
     rpc_client: &'a mut HttpRpcClient,
     plugin_mgr: Option<PluginManager>,
     genesis_info: Option<GenesisInfo>,
+    ckb_cli_dir: PathBuf,
 }
 
 impl<'a> ApiServerSubCommand<'a> {
@@ -35,11 +37,13 @@ impl<'a> ApiServerSubCommand<'a> {
         rpc_client: &'a mut HttpRpcClient,
         plugin_mgr: PluginManager,
         genesis_info: Option<GenesisInfo>,
+        ckb_cli_dir: PathBuf,
     ) -> ApiServerSubCommand<'a> {
         ApiServerSubCommand {
             rpc_client,
             plugin_mgr: Some(plugin_mgr),
             genesis_info,
+            ckb_cli_dir,
         }
     }
 
@@ -100,6 +104,7 @@ impl<'a> CliSubCommand for ApiServerSubCommand<'a> {
             plugin_mgr: Arc::new(Mutex::new(self.plugin_mgr.take().unwrap())),
             genesis_info: Arc::new(Mutex::new(self.genesis_info.clone())),
             privkey_path,
+            ckb_cli_dir: self.ckb_cli_dir.clone(),
         };
         io_handler.extend_with(handler.to_delegate());
 
@@ -188,6 +193,7 @@ struct ApiRpcImpl {
     plugin_mgr: Arc<Mutex<PluginManager>>,
     genesis_info: Arc<Mutex<Option<GenesisInfo>>>,
     privkey_path: Option<String>,
+    ckb_cli_dir: PathBuf,
 }
 
 impl ApiRpcImpl {
@@ -211,6 +217,7 @@ impl ApiRpcImpl {
             &mut rpc_client,
             &mut plugin_mgr,
             Some(genesis_info),
+            &self.ckb_cli_dir,
         ))
     }
 }
@@ -396,13 +403,25 @@ impl HttpTransferArgs {
             password: None,
             derive_receiving_address_length: None,
             derive_change_address: None,
-            capacity,
+            capacity: Some(capacity),
             fee_rate,
+            max_fee_rate: None,
             force_small_change_as_fee: self.force_small_change_as_fee,
-            to_address: self.to_address,
+            to_address: Some(self.to_address),
             to_data: self.to_data,
+            to_file: None,
             is_type_id: false,
             skip_check_to_address: false,
+            skip_lint: false,
+            transfer_all: false,
+            coin_selection: None,
+            lock_until: None,
+            unsigned_out: None,
+            from_omnilock_address: None,
+            cell_deps: None,
+            sighash_addresses: Vec::new(),
+            require_first_n: None,
+            threshold: None,
         }
     }
 }
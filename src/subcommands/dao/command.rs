@@ -6,7 +6,10 @@ use crate::utils::{
         AddressParser, ArgParser, CapacityParser, FixedHashParser, FromStrParser, OutPointParser,
         PrivkeyPathParser, PrivkeyWrapper,
     },
+    coin_selection::CoinSelection,
+    fee_rate::get_fee_rate,
     other::{get_address, get_network_type},
+    rpc::HttpRpcClient,
 };
 use ckb_crypto::secp::SECP256K1;
 use ckb_sdk::{Address, AddressPayload, HumanCapacity, NetworkType};
@@ -19,13 +22,13 @@ impl<'a> CliSubCommand for DAOSubCommand<'a> {
         let network_type = get_network_type(self.rpc_client)?;
         match matches.subcommand() {
             ("deposit", Some(m)) => {
-                let args = TransactArgs::from_matches(m, network_type)?;
+                let args = TransactArgs::from_matches(m, network_type, self.rpc_client)?;
                 let capacity: u64 = CapacityParser.from_matches(m, "capacity")?;
                 let transaction = self.deposit(&args, capacity)?;
                 send_transaction(self.rpc_client, transaction, debug)
             }
             ("prepare", Some(m)) => {
-                let args = TransactArgs::from_matches(m, network_type)?;
+                let args = TransactArgs::from_matches(m, network_type, self.rpc_client)?;
                 let out_points = OutPointParser.from_matches_vec(m, "out-point")?;
                 if out_points.len() != out_points.iter().collect::<HashSet<_>>().len() {
                     return Err("Duplicated out-points".to_string());
@@ -34,7 +37,7 @@ impl<'a> CliSubCommand for DAOSubCommand<'a> {
                 send_transaction(self.rpc_client, transaction, debug)
             }
             ("withdraw", Some(m)) => {
-                let args = TransactArgs::from_matches(m, network_type)?;
+                let args = TransactArgs::from_matches(m, network_type, self.rpc_client)?;
                 let out_points = OutPointParser.from_matches_vec(m, "out-point")?;
                 if out_points.len() != out_points.iter().collect::<HashSet<_>>().len() {
                     return Err("Duplicated out-points".to_string());
@@ -110,10 +113,15 @@ pub struct TransactArgs {
     pub(crate) address: Address,
     pub(crate) fee_rate: u64,
     pub(crate) force_small_change_as_fee: Option<u64>,
+    pub(crate) coin_selection: CoinSelection,
 }
 
 impl TransactArgs {
-    fn from_matches(m: &ArgMatches, network_type: NetworkType) -> Result<Self, String> {
+    fn from_matches(
+        m: &ArgMatches,
+        network_type: NetworkType,
+        rpc_client: &mut HttpRpcClient,
+    ) -> Result<Self, String> {
         let privkey: Option<PrivkeyWrapper> =
             PrivkeyPathParser.from_matches_opt(m, "privkey-path")?;
         let address = if let Some(privkey) = privkey.as_ref() {
@@ -142,15 +150,18 @@ impl TransactArgs {
             let payload = AddressPayload::from_pubkey_hash(account);
             Address::new(network_type, payload, false)
         };
-        let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+        let fee_rate = get_fee_rate(rpc_client, m)?;
 
         let force_small_change_as_fee =
             FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+        let coin_selection: CoinSelection =
+            FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
         Ok(Self {
             privkey,
             address,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         })
     }
 
@@ -159,7 +170,9 @@ impl TransactArgs {
             arg::privkey_path().required_unless(arg::from_account().get_name()),
             arg::from_account().required_unless(arg::privkey_path().get_name()),
             arg::fee_rate(),
+            arg::max_fee_rate(),
             arg::max_tx_fee(),
+            arg::coin_selection(),
         ]
     }
 }
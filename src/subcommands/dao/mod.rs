@@ -34,6 +34,7 @@ use self::command::TransactArgs;
 use crate::{
     plugin::PluginManager,
     utils::{
+        coin_selection::CoinSelectionCellCollector,
         genesis_info::GenesisInfo,
         other::{map_tx_builder_error_2_str, read_password, to_live_cell_info},
         rpc::HttpRpcClient,
@@ -118,9 +119,15 @@ impl<'a> DAOSubCommand<'a> {
         let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
         unlockers.insert(script_id, Box::new(sighash_unlocker));
 
+        let mut cell_collector = CoinSelectionCellCollector::new(
+            self.cell_collector.clone(),
+            self.rpc_client.url(),
+            args.coin_selection,
+        );
+        cell_collector.set_tolerance(args.force_small_change_as_fee.unwrap_or(0));
         let (tx, still_locked_groups) = builder
             .build_unlocked(
-                &mut self.cell_collector,
+                &mut cell_collector,
                 &self.cell_dep_resolver,
                 &self.header_dep_resolver,
                 &self.tx_dep_provider,
@@ -17,15 +17,18 @@ use ckb_sdk::{
 use ckb_types::{bytes::Bytes, packed, prelude::*, H160, H256};
 use clap::{App, Arg, ArgMatches};
 
-use super::{CliSubCommand, Output};
+use super::{sudt, CliSubCommand, Output};
 use crate::plugin::PluginManager;
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, DirPathParser, FilePathParser, FixedHashParser, FromStrParser,
+        AddressParser, ArgParser, CellDepsParser, DirPathParser, FilePathParser, FixedHashParser,
         PrivkeyPathParser, PrivkeyWrapper,
     },
+    cell_dep::{CellDepName, CellDeps},
+    fee_rate::get_fee_rate,
     genesis_info::GenesisInfo,
+    multisig::MultisigScript,
     other::{get_live_cell_with_cache, get_network_type, read_password},
     rpc::HttpRpcClient,
     signer::KeyStoreHandlerSigner,
@@ -105,9 +108,15 @@ impl<'a> DeploySubCommand<'a> {
                             .about("Collect cells from this address (short sighash address)")
                     )
                     .arg(arg::fee_rate().required(true))
+                    .arg(arg::max_fee_rate())
                     .arg(arg_deployment.clone())
                     .arg(arg_info_file.clone().validator(|input| FilePathParser::new(false).validate(input)))
                     .arg(arg_migration_dir.clone())
+                    .arg(
+                        sudt::arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information, the `multisig_v2` item is required when `multisig_config.multisig_script` is v2"),
+                    )
                     .arg(
                         Arg::with_name("sign-now")
                             .long("sign-now")
@@ -145,12 +154,14 @@ impl<'a> CliSubCommand for DeploySubCommand<'a> {
                 let from_address: Address = AddressParser::new_sighash()
                     .set_network(network)
                     .from_matches(m, "from-address")?;
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let deployment_config: PathBuf =
                     FilePathParser::new(true).from_matches(m, "deployment-config")?;
                 let migration_dir: PathBuf =
                     DirPathParser::new(true).from_matches(m, "migration-dir")?;
                 let info_file: PathBuf = FilePathParser::new(false).from_matches(m, "info-file")?;
+                let cell_deps_opt: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
 
                 if info_file.exists() {
                     return Err(format!("Output info-file already exists: {:?}", info_file));
@@ -176,16 +187,42 @@ impl<'a> CliSubCommand for DeploySubCommand<'a> {
 
                 let mut multisig_config = None;
                 if !deployment.multisig_config.sighash_addresses.is_empty() {
-                    multisig_config = Some(MultisigConfig::try_from(
-                        deployment.multisig_config.clone(),
-                    )?);
+                    let config = MultisigConfig::try_from(deployment.multisig_config.clone())?;
+                    let multisig_script = deployment.multisig_config.multisig_script;
+                    if let Some(lock_variant) = MultisigScript::from_lock(&lock_script) {
+                        if lock_variant != multisig_script {
+                            return Err(format!(
+                                "The deployment lock is a {} multisig lock, but multisig_config.multisig_script is {}",
+                                lock_variant, multisig_script
+                            ));
+                        }
+                    }
+                    let cell_dep = match multisig_script {
+                        MultisigScript::Legacy => self.genesis_info.multisig_dep(),
+                        MultisigScript::V2 => {
+                            let item = cell_deps_opt
+                                .as_ref()
+                                .and_then(|cell_deps| cell_deps.get_item(CellDepName::MultisigV2))
+                                .ok_or_else(|| {
+                                    String::from("The multisig_v2 item of <cell-deps> is required by v2 multisig_config")
+                                })?;
+                            packed::CellDep::from(item.cell_dep.clone())
+                        }
+                    };
+                    multisig_config = Some((config, multisig_script, cell_dep));
                 }
+                let multisig =
+                    multisig_config
+                        .as_ref()
+                        .map(|(config, multisig_script, cell_dep)| {
+                            (config, *multisig_script, cell_dep)
+                        });
                 // * Build new cell transaction
                 let cell_tx_opt = {
                     log::info!("Building cell transaction ...");
                     build_tx(
                         (&from_address, fee_rate),
-                        multisig_config.as_ref(),
+                        multisig,
                         &lock_script,
                         &cell_changes,
                         None,
@@ -218,7 +255,7 @@ impl<'a> CliSubCommand for DeploySubCommand<'a> {
                     log::info!("Building dep_group transaction ...");
                     build_tx(
                         (&from_address, fee_rate),
-                        multisig_config.as_ref(),
+                        multisig,
                         &lock_script,
                         &dep_group_changes,
                         cell_tx_opt.clone(),
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use anyhow::{anyhow, Result};
 use ckb_sdk::{
-    constants::{MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
+    constants::SIGHASH_TYPE_HASH,
     traits::{
         CellCollector, CellQueryOptions, DefaultCellCollector, DefaultHeaderDepResolver,
         DefaultTransactionDependencyProvider, OffchainTransactionDependencyProvider, Signer,
@@ -23,12 +23,13 @@ use ckb_types::{
 };
 
 use super::state_change::ChangeInfo;
-use crate::utils::genesis_info::GenesisInfo;
+use crate::utils::{genesis_info::GenesisInfo, multisig::MultisigScript};
 
-// build balanced transaction
+// build balanced transaction, `multisig` is the config, script variant and cell dep of multisig
+// inputs
 pub fn build_tx<T: ChangeInfo>(
     (from_address, fee_rate): (&Address, u64),
-    multisig_config: Option<&MultisigConfig>,
+    multisig: Option<(&MultisigConfig, MultisigScript, &packed::CellDep)>,
     lock_script: &packed::Script,
     infos: &[T],
     pending_tx: Option<packed::Transaction>,
@@ -79,8 +80,8 @@ pub fn build_tx<T: ChangeInfo>(
         .filter_map(|info| info.build_cell_output(lock_script, first_cell_input))
         .unzip();
     let mut cell_deps = vec![genesis_info.sighash_dep()];
-    if multisig_config.is_some() {
-        cell_deps.push(genesis_info.multisig_dep());
+    if let Some((_, _, cell_dep)) = multisig {
+        cell_deps.push(cell_dep.clone());
     }
     let mut unlockers = HashMap::new();
     let signer = DummySigner {
@@ -92,12 +93,11 @@ pub fn build_tx<T: ChangeInfo>(
         sighash_script_id,
         Box::new(sighash_unlocker) as Box<dyn ScriptUnlocker>,
     );
-    if let Some(cfg) = multisig_config {
+    if let Some((cfg, multisig_script, _)) = multisig {
         let multisig_signer = SecpMultisigScriptSigner::new(Box::new(signer), cfg.clone());
         let multisig_unlocker = SecpMultisigUnlocker::new(multisig_signer);
-        let multisig_script_id = ScriptId::new_type(MULTISIG_TYPE_HASH.clone());
         unlockers.insert(
-            multisig_script_id,
+            multisig_script.script_id(),
             Box::new(multisig_unlocker) as Box<dyn ScriptUnlocker>,
         );
     }
@@ -1,18 +1,18 @@
 use std::convert::TryFrom;
 use std::fs;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use ckb_jsonrpc_types as json_types;
 use ckb_mock_tx_types::{
-    MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction, ReprMockCellDep,
-    ReprMockInfo, ReprMockInput, ReprMockTransaction,
+    MockCellDep, MockInfo, MockInput, MockResourceLoader, MockTransaction, ReprMockTransaction,
 };
-use ckb_sdk::constants::SIGHASH_TYPE_HASH;
+use ckb_sdk::{constants::SIGHASH_TYPE_HASH, ScriptId};
 use ckb_types::{
     bytes::Bytes,
     core::{
-        capacity_bytes, Capacity, HeaderBuilder, HeaderView, ScriptHashType, TransactionBuilder,
+        self, capacity_bytes, Capacity, DepType, HeaderBuilder, HeaderView, ScriptHashType,
+        TransactionBuilder,
     },
     h256,
     packed::{self, Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
@@ -21,16 +21,21 @@ use ckb_types::{
 };
 use clap::{App, Arg, ArgMatches};
 
-use super::{tx::ReprTxHelper, CliSubCommand, Output};
+use super::{sudt, tx::ReprTxHelper, CliSubCommand, Output};
 use crate::plugin::PluginManager;
 use crate::utils::{
-    arg::lock_arg,
-    arg_parser::{ArgParser, FilePathParser, FixedHashParser},
+    arg::{self, lock_arg},
+    arg_parser::{
+        ArgParser, CapacityParser, CellDepsParser, DirPathParser, FilePathParser, FixedHashParser,
+        FromStrParser, HexParser, OutPointParser, PairParser, ScriptIdParser,
+    },
+    cell_dep::CellDeps,
     genesis_info::GenesisInfo,
-    mock_tx_helper::MockTransactionHelper,
-    other::{get_genesis_info, get_signer},
+    mock_tx_helper::{CycleReport, MockTransactionHelper},
+    other::{get_genesis_info, get_network_type, get_signer},
     rpc::HttpRpcClient,
     tx_helper::TxHelper,
+    tx_lint::{known_cell_deps, known_script_names, lint_tx_online},
 };
 
 pub struct MockTxSubCommand<'a> {
@@ -103,12 +108,99 @@ impl<'a> MockTxSubCommand<'a> {
                             .required(true)
                             .about("Dumped mock transaction data file (format: json)"),
                     ),
+                App::new("dump-block")
+                    .about("Dump every transaction (except the cellbase) of a block or a block range into mock transaction files, with an index file `index.json`")
+                    .arg(
+                        Arg::with_name("block-number")
+                            .long("block-number")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .required_unless("block-hash")
+                            .conflicts_with("block-hash")
+                            .about("The number of the (first) block"),
+                    )
+                    .arg(
+                        Arg::with_name("block-hash")
+                            .long("block-hash")
+                            .takes_value(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .required_unless("block-number")
+                            .conflicts_with("block-number")
+                            .about("The hash of the (first) block"),
+                    )
+                    .arg(
+                        Arg::with_name("to-block")
+                            .long("to-block")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u64>::default().validate(input))
+                            .about("The number of the last block (inclusive), only one block is dumped by default"),
+                    )
+                    .arg(
+                        Arg::with_name("output-dir")
+                            .long("output-dir")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| DirPathParser::new(false).validate(input))
+                            .about("The directory to save the mock transaction files, created if not exists"),
+                    ),
+                App::new("patch")
+                    .about("Patch a mock transaction for local script testing: replace cell dep data, script code hash/hash type and witnesses (applied in this order), the result can be checked by `mock-tx verify`")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_output_file.clone().about("Patched mock transaction data file (format: json)"))
+                    .arg(
+                        Arg::with_name("cell-dep-data")
+                            .long("cell-dep-data")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| {
+                                PairParser::new(OutPointParser, FilePathParser::new(true))
+                                    .validate(input)
+                            })
+                            .about("Replace the data of a cell dep (or a cell of dep group) with a local binary file, format: {tx-hash}-{index}:{file}"),
+                    )
+                    .arg(
+                        Arg::with_name("replace-script")
+                            .long("replace-script")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| PairParser::new(ScriptIdParser, ScriptIdParser).validate(input))
+                            .about("Replace the code hash and hash type of the lock/type scripts in inputs and outputs, the args are kept, format: {code_hash}-{hash_type}:{code_hash}-{hash_type}. The transaction hash is changed if any output is patched"),
+                    )
+                    .arg(
+                        Arg::with_name("witness")
+                            .long("witness")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| {
+                                PairParser::new(FromStrParser::<usize>::default(), HexParser)
+                                    .validate(input)
+                            })
+                            .about("Replace the whole witness, format: {index}:{hex}"),
+                    )
+                    .arg(
+                        Arg::with_name("witness-lock")
+                            .long("witness-lock")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| {
+                                PairParser::new(FromStrParser::<usize>::default(), HexParser)
+                                    .validate(input)
+                            })
+                            .about("Replace the lock field of the witness (WitnessArgs), format: {index}:{hex}"),
+                    ),
                 App::new("verify")
-                    .about("Verify a mock transaction in local")
-                    .arg(arg_tx_file.clone()),
+                    .about("Verify a mock transaction in local, report the cycles of every script group, the transaction size, fee and fee rate")
+                    .arg(arg_tx_file.clone())
+                    .arg(
+                        sudt::arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information, used to name the scripts in the report"),
+                    ),
                 App::new("send")
                     .about("Complete then send a transaction")
-                    .arg(arg_tx_file.clone()),
+                    .arg(arg_tx_file.clone())
+                    .arg(arg::max_send_tx_fee())
+                    .arg(arg::skip_lint()),
             ])
     }
 }
@@ -118,7 +210,7 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
         let mut complete_tx = |m: &ArgMatches,
                                complete: bool,
                                verify: bool|
-         -> Result<(MockTransaction, u64), String> {
+         -> Result<(MockTransaction, Option<CycleReport>), String> {
             let path: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
             let mut content = String::new();
             let mut file = fs::File::open(path).map_err(|err| err.to_string())?;
@@ -138,8 +230,9 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
             let mut rpc_client = HttpRpcClient::new(self.rpc_client.url().to_string());
             let mut loader = Loader {
                 rpc_client: self.rpc_client,
+                dead_cells: false,
             };
-            let cycle = {
+            let report = {
                 let mut helper = MockTransactionHelper::new(&mut mock_tx);
                 if complete {
                     let genesis_info = get_genesis_info(&self.genesis_info, &mut rpc_client)?;
@@ -148,12 +241,12 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
                     })?;
                 }
                 if verify {
-                    helper.verify(u64::max_value(), loader)?
+                    Some(helper.verify_groups(u64::max_value(), loader)?)
                 } else {
-                    0
+                    None
                 }
             };
-            Ok((mock_tx, cycle))
+            Ok((mock_tx, report))
         };
 
         let output_tx = |m: &ArgMatches,
@@ -243,7 +336,7 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
                 }
             }
             ("complete", Some(m)) => {
-                let (mock_tx, _cycle) = complete_tx(m, true, false)?;
+                let (mock_tx, _report) = complete_tx(m, true, false)?;
                 let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
                 if let Some(repr_mock_tx) = output_tx(m, &mock_tx)? {
                     let mut value = serde_json::to_value(repr_mock_tx).unwrap();
@@ -294,85 +387,18 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
                 } else {
                     return Err(String::from("<tx-hash> or <tx-file> is required"));
                 };
-                let mock_inputs = src_tx
-                    .inputs
-                    .iter()
-                    .map(|input| {
-                        let (output, data, block_hash) =
-                            load_output_and_data(self.rpc_client, input.previous_output.clone())?;
-                        Ok(ReprMockInput {
-                            input: input.clone(),
-                            output,
-                            data,
-                            header: Some(block_hash),
-                        })
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let mock_cell_deps = src_tx
-                    .cell_deps
-                    .iter()
-                    .flat_map(|cell_dep| {
-                        let (output, data, block_hash) =
-                            match load_output_and_data(self.rpc_client, cell_dep.out_point.clone())
-                            {
-                                Ok((output, data, block_hash)) => (output, data, block_hash),
-                                Err(err) => return vec![Err(err)],
-                            };
-                        let mut cell_deps = if cell_dep.dep_type == json_types::DepType::DepGroup {
-                            let out_points = match packed::OutPointVec::from_slice(data.as_bytes())
-                            {
-                                Ok(out_points) => out_points,
-                                Err(err) => return vec![Err(err.to_string())],
-                            };
-                            out_points
-                                .into_iter()
-                                .map(json_types::OutPoint::from)
-                                .map(|out_point| {
-                                    let (output, data, block_hash) =
-                                        load_output_and_data(self.rpc_client, out_point.clone())?;
-                                    Ok(ReprMockCellDep {
-                                        cell_dep: json_types::CellDep {
-                                            out_point,
-                                            dep_type: json_types::DepType::Code,
-                                        },
-                                        output,
-                                        data,
-                                        header: Some(block_hash),
-                                    })
-                                })
-                                .collect::<Vec<_>>()
-                        } else {
-                            Vec::new()
-                        };
-                        cell_deps.push(Ok(ReprMockCellDep {
-                            cell_dep: cell_dep.clone(),
-                            output,
-                            data,
-                            header: Some(block_hash),
-                        }));
-                        cell_deps
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let mock_header_deps = src_tx
-                    .header_deps
-                    .iter()
-                    .map(|block_hash| {
-                        self.rpc_client
-                            .get_header(block_hash.clone())?
-                            .map(HeaderView::from)
-                            .map(json_types::HeaderView::from)
-                            .ok_or_else(|| format!("header not exists: {:x}", block_hash))
-                    })
-                    .collect::<Result<Vec<_>, String>>()?;
-                let repr_tx = ReprMockTransaction {
-                    mock_info: ReprMockInfo {
-                        inputs: mock_inputs,
-                        cell_deps: mock_cell_deps,
-                        header_deps: mock_header_deps,
-                        extensions: vec![],
+                let tx = packed::Transaction::from(src_tx).into_view();
+                let mock_info = load_mock_info(
+                    &tx,
+                    &mut Loader {
+                        rpc_client: self.rpc_client,
+                        dead_cells: true,
                     },
-                    tx: src_tx,
-                };
+                )?;
+                let repr_tx = ReprMockTransaction::from(MockTransaction {
+                    mock_info,
+                    tx: tx.data(),
+                });
                 let content =
                     serde_json::to_string_pretty(&repr_tx).map_err(|err| err.to_string())?;
                 let mut out_file = fs::File::create(output_path).map_err(|err| err.to_string())?;
@@ -381,17 +407,162 @@ impl<'a> CliSubCommand for MockTxSubCommand<'a> {
                     .map_err(|err| err.to_string())?;
                 Ok(Output::new_success())
             }
-            ("verify", Some(m)) => {
-                let (mock_tx, cycle) = complete_tx(m, false, true)?;
+            ("dump-block", Some(m)) => {
+                let block_number_opt: Option<u64> =
+                    FromStrParser::<u64>::default().from_matches_opt(m, "block-number")?;
+                let block_hash_opt: Option<H256> =
+                    FixedHashParser::<H256>::default().from_matches_opt(m, "block-hash")?;
+                let to_block_opt: Option<u64> =
+                    FromStrParser::<u64>::default().from_matches_opt(m, "to-block")?;
+                let output_dir: PathBuf =
+                    DirPathParser::new(false).from_matches(m, "output-dir")?;
+
+                let first_block = if let Some(block_hash) = block_hash_opt {
+                    self.rpc_client
+                        .get_block(block_hash.clone())?
+                        .ok_or_else(|| format!("Block not found: {:#x}", block_hash))?
+                } else if let Some(block_number) = block_number_opt {
+                    self.rpc_client
+                        .get_block_by_number(block_number)?
+                        .ok_or_else(|| format!("Block not found: {}", block_number))?
+                } else {
+                    return Err(String::from("<block-number> or <block-hash> is required"));
+                };
+                let from_number = first_block.header.inner.number;
+                let to_number = to_block_opt.unwrap_or(from_number);
+                if to_number < from_number {
+                    return Err(format!(
+                        "<to-block> ({}) is less than the first block number ({})",
+                        to_number, from_number
+                    ));
+                }
+                fs::create_dir_all(&output_dir).map_err(|err| err.to_string())?;
+
+                let mut loader = Loader {
+                    rpc_client: self.rpc_client,
+                    dead_cells: true,
+                };
+                let mut index = Vec::new();
+                let mut next_block = Some(first_block);
+                for number in from_number..=to_number {
+                    let block = match next_block.take() {
+                        Some(block) => block,
+                        None => loader
+                            .rpc_client
+                            .get_block_by_number(number)?
+                            .ok_or_else(|| format!("Block not found: {}", number))?,
+                    };
+                    index.extend(dump_block(
+                        &core::BlockView::from(block),
+                        &mut loader,
+                        &output_dir,
+                    )?);
+                }
+                let index_path = output_dir.join("index.json");
+                let content =
+                    serde_json::to_string_pretty(&index).map_err(|err| err.to_string())?;
+                fs::write(&index_path, content).map_err(|err| err.to_string())?;
+                let resp = serde_json::json!({
+                    "blocks": to_number - from_number + 1,
+                    "transactions": index.len(),
+                    "index-file": index_path,
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("patch", Some(m)) => {
+                let path: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+                let repr_tx: ReprMockTransaction =
+                    serde_json::from_str(content.as_str()).map_err(|err| err.to_string())?;
+                let mut mock_tx: MockTransaction = repr_tx.into();
+
+                let cell_dep_data: Vec<(OutPoint, PathBuf)> =
+                    PairParser::new(OutPointParser, FilePathParser::new(true))
+                        .from_matches_vec(m, "cell-dep-data")?;
+                let replace_scripts: Vec<(ScriptId, ScriptId)> =
+                    PairParser::new(ScriptIdParser, ScriptIdParser)
+                        .from_matches_vec(m, "replace-script")?;
+                let witness_parser = PairParser::new(FromStrParser::<usize>::default(), HexParser);
+                let witnesses: Vec<(usize, Vec<u8>)> =
+                    witness_parser.from_matches_vec(m, "witness")?;
+                let witness_locks: Vec<(usize, Vec<u8>)> =
+                    witness_parser.from_matches_vec(m, "witness-lock")?;
+
+                let mut data_hashes = Vec::new();
+                {
+                    let mut helper = MockTransactionHelper::new(&mut mock_tx);
+                    for (out_point, path) in cell_dep_data {
+                        let data = fs::read(&path).map_err(|err| {
+                            format!("Read file {} failed: {}", path.display(), err)
+                        })?;
+                        let data_hash: H256 = helper
+                            .replace_cell_dep_data(&out_point, Bytes::from(data))?
+                            .unpack();
+                        data_hashes.push(serde_json::json!({
+                            "out_point": json_types::OutPoint::from(out_point),
+                            "data_hash": data_hash,
+                        }));
+                    }
+                    for (from, to) in replace_scripts {
+                        if helper.replace_script_id(&from, &to) == 0 {
+                            return Err(format!("No script matches: {}", from));
+                        }
+                    }
+                    for (index, witness) in witnesses {
+                        helper.set_witness(index, Bytes::from(witness));
+                    }
+                    for (index, lock) in witness_locks {
+                        helper.set_witness_lock(index, Bytes::from(lock))?;
+                    }
+                }
+
                 let tx_hash: H256 = mock_tx.core_transaction().hash().unpack();
+                if let Some(repr_mock_tx) = output_tx(m, &mock_tx)? {
+                    let mut value = serde_json::to_value(repr_mock_tx).unwrap();
+                    value["tx-hash"] = serde_json::json!(tx_hash);
+                    value["cell-dep-data-hashes"] = serde_json::json!(data_hashes);
+                    Ok(Output::new_output(value))
+                } else {
+                    let resp = serde_json::json!({
+                        "tx-hash": tx_hash,
+                        "cell-dep-data-hashes": data_hashes,
+                    });
+                    Ok(Output::new_output(resp))
+                }
+            }
+            ("verify", Some(m)) => {
+                let cell_deps: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
+                let (mock_tx, report) = complete_tx(m, false, true)?;
+                let report = report.expect("verified");
+                let cycle = report.total_cycles()?;
+                let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client).ok();
+                let names = known_script_names(genesis_info.as_ref(), cell_deps.as_ref());
+                let tx = mock_tx.core_transaction();
+                let tx_hash: H256 = tx.hash().unpack();
                 let resp = serde_json::json!({
                     "tx-hash": tx_hash,
                     "cycle": cycle,
+                    "cycle-report": report.to_json(&tx, &names),
                 });
                 Ok(Output::new_output(resp))
             }
             ("send", Some(m)) => {
-                let (mock_tx, _cycle) = complete_tx(m, false, true)?;
+                let max_tx_fee: u64 = CapacityParser.from_matches(m, "max-tx-fee")?;
+                let (mock_tx, report) = complete_tx(m, false, true)?;
+                report.expect("verified").total_cycles()?;
+                if !m.is_present("skip-lint") {
+                    let network = get_network_type(self.rpc_client)?;
+                    let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+                    lint_tx_online(
+                        self.rpc_client,
+                        &mock_tx.core_transaction(),
+                        network,
+                        known_cell_deps(&genesis_info, None),
+                        Some(max_tx_fee),
+                    )?
+                    .check_before_send()?;
+                }
                 let resp = self
                     .rpc_client
                     .send_transaction(
@@ -441,8 +612,108 @@ fn load_output_and_data(
     Ok((output, data, block_hash))
 }
 
-struct Loader<'a> {
-    rpc_client: &'a mut HttpRpcClient,
+/// Load the inputs, cell deps (the cells of dep groups are included) and header deps of a
+/// transaction into mock info
+fn load_mock_info<L: MockResourceLoader>(
+    tx: &core::TransactionView,
+    loader: &mut L,
+) -> Result<MockInfo, String> {
+    let mut load_cell = |out_point: OutPoint| {
+        loader
+            .get_live_cell(out_point.clone())?
+            .ok_or_else(|| format!("Can not load cell: {}", out_point))
+    };
+    let mut inputs = Vec::new();
+    for input in tx.inputs().into_iter() {
+        let (output, data, header) = load_cell(input.previous_output())?;
+        inputs.push(MockInput {
+            input,
+            output,
+            data,
+            header,
+        });
+    }
+    let mut cell_deps = Vec::new();
+    for cell_dep in tx.cell_deps().into_iter() {
+        let (output, data, header) = load_cell(cell_dep.out_point())?;
+        if cell_dep.dep_type() == DepType::DepGroup.into() {
+            let out_points = packed::OutPointVec::from_slice(&data)
+                .map_err(|err| format!("Parse dep group data error: {}", err))?;
+            for out_point in out_points.into_iter() {
+                let (output, data, header) = load_cell(out_point.clone())?;
+                cell_deps.push(MockCellDep {
+                    cell_dep: CellDep::new_builder()
+                        .out_point(out_point)
+                        .dep_type(DepType::Code.into())
+                        .build(),
+                    output,
+                    data,
+                    header,
+                });
+            }
+        }
+        cell_deps.push(MockCellDep {
+            cell_dep,
+            output,
+            data,
+            header,
+        });
+    }
+    let mut header_deps = Vec::new();
+    for block_hash in tx.header_deps().into_iter() {
+        let header = loader
+            .get_header(block_hash.unpack())?
+            .ok_or_else(|| format!("Header not found: {:#x}", block_hash))?;
+        header_deps.push(header);
+    }
+    Ok(MockInfo {
+        inputs,
+        cell_deps,
+        header_deps,
+        extensions: vec![],
+    })
+}
+
+/// Dump the transactions (except the cellbase) of a block into mock transaction files of
+/// `output_dir`, named as `{block_number}-{tx_index}-{tx_hash}.json`.
+///
+/// Return the index entries of the dumped files.
+fn dump_block<L: MockResourceLoader>(
+    block: &core::BlockView,
+    loader: &mut L,
+    output_dir: &Path,
+) -> Result<Vec<serde_json::Value>, String> {
+    let number = block.number();
+    let block_hash: H256 = block.hash().unpack();
+    let mut index = Vec::new();
+    for (tx_index, tx) in block.transactions().into_iter().enumerate().skip(1) {
+        let tx_hash: H256 = tx.hash().unpack();
+        let mock_info = load_mock_info(&tx, loader)
+            .map_err(|err| format!("Dump transaction {:#x} failed: {}", tx_hash, err))?;
+        let mock_tx = MockTransaction {
+            mock_info,
+            tx: tx.data(),
+        };
+        let file_name = format!("{}-{}-{:#x}.json", number, tx_index, tx_hash);
+        let content = serde_json::to_string_pretty(&ReprMockTransaction::from(mock_tx))
+            .map_err(|err| err.to_string())?;
+        fs::write(output_dir.join(&file_name), content).map_err(|err| err.to_string())?;
+        index.push(serde_json::json!({
+            "block-number": number,
+            "block-hash": block_hash,
+            "tx-index": tx_index,
+            "tx-hash": tx_hash,
+            "file": file_name,
+        }));
+    }
+    Ok(index)
+}
+
+pub(crate) struct Loader<'a> {
+    pub(crate) rpc_client: &'a mut HttpRpcClient,
+    /// Also load the dead cells from their transactions, used to dump the committed
+    /// transactions
+    pub(crate) dead_cells: bool,
 }
 
 impl<'a> MockResourceLoader for Loader<'a> {
@@ -456,6 +727,15 @@ impl<'a> MockResourceLoader for Loader<'a> {
         &mut self,
         out_point: OutPoint,
     ) -> Result<Option<(CellOutput, Bytes, Option<Byte32>)>, String> {
+        if self.dead_cells {
+            let (output, data, block_hash) =
+                load_output_and_data(self.rpc_client, out_point.into())?;
+            return Ok(Some((
+                output.into(),
+                data.into_bytes(),
+                Some(block_hash.pack()),
+            )));
+        }
         let output: Option<CellOutput> = self
             .rpc_client
             .get_live_cell(out_point.clone(), true)
@@ -481,3 +761,114 @@ impl<'a> MockResourceLoader for Loader<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::{BlockBuilder, EpochNumberWithFraction};
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct CellsLoader {
+        cells: HashMap<OutPoint, (CellOutput, Bytes)>,
+    }
+
+    impl CellsLoader {
+        fn add_cell(&mut self, tx_hash_byte: u8, data: Bytes) -> OutPoint {
+            let out_point = OutPoint::new(Byte32::new([tx_hash_byte; 32]), 0);
+            let output = CellOutput::new_builder()
+                .capacity(capacity_bytes!(1000).pack())
+                .build();
+            self.cells.insert(out_point.clone(), (output, data));
+            out_point
+        }
+    }
+
+    impl MockResourceLoader for CellsLoader {
+        fn get_header(&mut self, hash: H256) -> Result<Option<HeaderView>, String> {
+            Err(format!("Can not call header getter, hash={:?}", hash))
+        }
+        fn get_live_cell(
+            &mut self,
+            out_point: OutPoint,
+        ) -> Result<Option<(CellOutput, Bytes, Option<Byte32>)>, String> {
+            Ok(self
+                .cells
+                .get(&out_point)
+                .cloned()
+                .map(|(output, data)| (output, data, None)))
+        }
+    }
+
+    #[test]
+    fn test_dump_block() {
+        let mut loader = CellsLoader::default();
+        let input_out_point = loader.add_cell(1, Bytes::new());
+        let code_out_point = loader.add_cell(2, Bytes::from(vec![0u8; 8]));
+        let group_data = packed::OutPointVec::new_builder()
+            .push(code_out_point.clone())
+            .build()
+            .as_bytes();
+        let group_out_point = loader.add_cell(3, group_data);
+
+        let cellbase = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(42))
+            .output(CellOutput::default())
+            .output_data(Bytes::new().pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(input_out_point, 0))
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(group_out_point.clone())
+                    .dep_type(DepType::DepGroup.into())
+                    .build(),
+            )
+            .output(CellOutput::default())
+            .output_data(Bytes::new().pack())
+            .build();
+        let block = BlockBuilder::default()
+            .number(42.pack())
+            .epoch(EpochNumberWithFraction::new(1, 0, 1000).full_value().pack())
+            .transaction(cellbase)
+            .transaction(tx.clone())
+            .build();
+
+        let output_dir =
+            std::env::temp_dir().join(format!("ckb-cli-dump-block-{}", rand::random::<u64>()));
+        fs::create_dir_all(&output_dir).unwrap();
+        let index = dump_block(&block, &mut loader, &output_dir).expect("dump block failed");
+        let tx_hash: H256 = tx.hash().unpack();
+        let block_hash: H256 = block.hash().unpack();
+        let file_name = format!("42-1-{:#x}.json", tx_hash);
+        assert_eq!(
+            index,
+            vec![serde_json::json!({
+                "block-number": 42,
+                "block-hash": block_hash,
+                "tx-index": 1,
+                "tx-hash": tx_hash,
+                "file": file_name,
+            })],
+            "The cellbase is skipped"
+        );
+
+        let content = fs::read_to_string(output_dir.join(&file_name)).unwrap();
+        let repr_tx: ReprMockTransaction = serde_json::from_str(&content).unwrap();
+        let mock_tx = MockTransaction::from(repr_tx);
+        assert_eq!(mock_tx.core_transaction().hash(), tx.hash());
+        assert_eq!(mock_tx.mock_info.inputs.len(), 1);
+        let dep_out_points = mock_tx
+            .mock_info
+            .cell_deps
+            .iter()
+            .map(|dep| dep.cell_dep.out_point())
+            .collect::<Vec<_>>();
+        assert_eq!(dep_out_points, vec![code_out_point, group_out_point]);
+
+        // A missing cell fails the whole block
+        let mut empty_loader = CellsLoader::default();
+        assert!(dump_block(&block, &mut empty_loader, &output_dir).is_err());
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 pub mod account;
+pub mod address_book;
 pub mod api_server;
 pub mod dao;
 pub mod deploy;
@@ -13,6 +14,7 @@ pub mod util;
 pub mod wallet;
 
 pub use account::AccountSubCommand;
+pub use address_book::AddressBookSubCommand;
 pub use api_server::ApiServerSubCommand;
 pub use dao::DAOSubCommand;
 pub use deploy::DeploySubCommand;
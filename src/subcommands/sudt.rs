@@ -43,6 +43,8 @@ use crate::{
             PrivkeyWrapper, UdtTargetParser,
         },
         cell_dep::{CellDepName, CellDeps},
+        coin_selection::{CoinSelection, CoinSelectionCellCollector},
+        fee_rate::get_fee_rate,
         genesis_info::GenesisInfo,
         other::{get_network_type, map_tx_builder_error_2_str, read_password},
         rpc::HttpRpcClient,
@@ -64,6 +66,7 @@ struct SudtCommonArgs {
     cell_deps: CellDeps,
     fee_rate: u64,
     force_small_change_as_fee: Option<u64>,
+    coin_selection: CoinSelection,
     debug: bool,
 }
 
@@ -120,7 +123,9 @@ impl<'a> SudtSubCommand<'a> {
                     )
                     .arg(arg::privkey_path().multiple(true))
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_fee_rate())
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::coin_selection()),
                 App::new("transfer")
                     .about("Transfer SUDT to multiple addresses (all target addresses must have same lock script id)")
                     .arg(arg_owner())
@@ -139,7 +144,9 @@ impl<'a> SudtSubCommand<'a> {
                     .arg(arg_capacity_provider())
                     .arg(arg::privkey_path().multiple(true))
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_fee_rate())
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::coin_selection()),
                 App::new("get-amount")
                     .about("Get SUDT total amount of an address")
                     .arg(arg_owner())
@@ -167,7 +174,9 @@ impl<'a> SudtSubCommand<'a> {
                     .arg(arg_cell_deps())
                     .arg(arg::privkey_path().multiple(true))
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_fee_rate())
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::coin_selection()),
                 App::new("cheque-claim")
                     .about("Claim all cheque cells identified by given lock script and type script")
                     .arg(arg_owner())
@@ -181,7 +190,9 @@ impl<'a> SudtSubCommand<'a> {
                     .arg(arg_cell_deps())
                     .arg(arg::privkey_path().multiple(true))
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_fee_rate())
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::coin_selection()),
                 App::new("cheque-withdraw")
                     .about("Withdraw all cheque cells identified by given lock script and type script")
                     .arg(arg_owner())
@@ -192,7 +203,9 @@ impl<'a> SudtSubCommand<'a> {
                     .arg(arg_cell_deps())
                     .arg(arg::privkey_path().multiple(true))
                     .arg(arg::fee_rate())
-                    .arg(arg::max_tx_fee()),
+                    .arg(arg::max_fee_rate())
+                    .arg(arg::max_tx_fee())
+                    .arg(arg::coin_selection()),
                 // TODO: move this subcommand to `util`
                 App::new("build-acp-address")
                     .about("Build an anyone-can-pay address by sighash address and anyone-can-pay script id.")
@@ -230,6 +243,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
             debug,
         } = common_args;
         let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
@@ -311,6 +325,7 @@ impl<'a> SudtSubCommand<'a> {
             None,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         )?;
 
         let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
@@ -356,6 +371,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
             debug,
         } = common_args;
         let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
@@ -454,6 +470,7 @@ impl<'a> SudtSubCommand<'a> {
             None,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         )?;
 
         let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
@@ -542,6 +559,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
             debug,
         } = common_args;
         let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
@@ -598,6 +616,7 @@ impl<'a> SudtSubCommand<'a> {
             None,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         )?;
         let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
         let tx_hash = self
@@ -639,6 +658,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
             debug,
         } = common_args;
         let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
@@ -733,6 +753,7 @@ impl<'a> SudtSubCommand<'a> {
             Some((cheque_script_id, ChequeAction::Claim)),
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         )?;
 
         let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
@@ -770,6 +791,7 @@ impl<'a> SudtSubCommand<'a> {
             cell_deps,
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
             debug,
         } = common_args;
         let udt_script_id = get_script_id(&cell_deps, CellDepName::Sudt)?;
@@ -848,6 +870,7 @@ impl<'a> SudtSubCommand<'a> {
             Some((cheque_script_id, ChequeAction::Withdraw)),
             fee_rate,
             force_small_change_as_fee,
+            coin_selection,
         )?;
 
         let outputs_validator = Some(json_types::OutputsValidator::Passthrough);
@@ -885,9 +908,11 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
                 let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+                let coin_selection: CoinSelection =
+                    FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
                 let to_cheque_address = m.is_present("to-cheque-address");
                 let to_acp_address = m.is_present("to-acp-address");
 
@@ -911,6 +936,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
+                        coin_selection,
                         debug,
                     },
                     network,
@@ -936,9 +962,11 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
                 let to_cheque_address = m.is_present("to-cheque-address");
                 let to_acp_address = m.is_present("to-acp-address");
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+                let coin_selection: CoinSelection =
+                    FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
 
                 check_udt_args(
                     &udt_to_vec,
@@ -962,6 +990,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
+                        coin_selection,
                         debug,
                     },
                     network,
@@ -990,9 +1019,11 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
                 let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+                let coin_selection: CoinSelection =
+                    FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
                 self.new_empty_acp(
                     NewAcpArgs {
                         owner,
@@ -1004,6 +1035,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
+                        coin_selection,
                         debug,
                     },
                     network,
@@ -1025,9 +1057,11 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
                 let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+                let coin_selection: CoinSelection =
+                    FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
 
                 if capacity_provider.as_ref() == Some(&sender) {
                     return Err("<capacity-provider> can't be the same with <sender>".to_string());
@@ -1044,6 +1078,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
+                        coin_selection,
                         debug,
                     },
                 )
@@ -1065,9 +1100,11 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                 let privkeys: Vec<PrivkeyWrapper> =
                     PrivkeyPathParser.from_matches_vec(m, "privkey-path")?;
                 let cell_deps: CellDeps = CellDepsParser.from_matches(m, "cell-deps")?;
-                let fee_rate: u64 = FromStrParser::<u64>::default().from_matches(m, "fee-rate")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
                 let force_small_change_as_fee =
                     FromStrParser::<HumanCapacity>::default().from_matches_opt(m, "max-tx-fee")?;
+                let coin_selection: CoinSelection =
+                    FromStrParser::<CoinSelection>::default().from_matches(m, "coin-selection")?;
                 self.cheque_withdraw(
                     WithdrawArgs {
                         owner,
@@ -1081,6 +1118,7 @@ impl<'a> CliSubCommand for SudtSubCommand<'a> {
                         cell_deps,
                         fee_rate,
                         force_small_change_as_fee,
+                        coin_selection,
                         debug,
                     },
                 )
@@ -1263,6 +1301,7 @@ impl<'a> UdtTxBuilder<'a> {
         cheque_script_id: Option<(ScriptId, ChequeAction)>,
         fee_rate: u64,
         force_small_change_as_fee: Option<u64>,
+        coin_selection: CoinSelection,
     ) -> Result<TransactionView, String> {
         let mut passwords: HashMap<H160, String> = HashMap::with_capacity(accounts.len());
         let sighash_script_id = ScriptId::new_type(SIGHASH_TYPE_HASH.clone());
@@ -1340,10 +1379,16 @@ impl<'a> UdtTxBuilder<'a> {
 
         cell_deps.apply_to_resolver(self.cell_dep_resolver)?;
 
+        let mut cell_collector = CoinSelectionCellCollector::new(
+            self.cell_collector.clone(),
+            self.rpc_client.url(),
+            coin_selection,
+        );
+        cell_collector.set_tolerance(force_small_change_as_fee.unwrap_or(0));
         let (tx, still_locked_groups) = self
             .builder
             .build_unlocked(
-                self.cell_collector,
+                &mut cell_collector,
                 self.cell_dep_resolver,
                 self.header_dep_resolver,
                 self.tx_dep_provider,
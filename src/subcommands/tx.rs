@@ -7,16 +7,18 @@ use std::str::FromStr;
 
 use ckb_jsonrpc_types as json_types;
 use ckb_jsonrpc_types::JsonBytes;
+use ckb_mock_tx_types::{MockInfo, MockTransaction};
 use ckb_sdk::{
-    constants::{MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE},
+    constants::{SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH},
+    traits::DefaultCellCollector,
     unlock::MultisigConfig,
     Address, AddressPayload, CodeHashIndex, HumanCapacity, NetworkType,
 };
 use ckb_types::{
     bytes::Bytes,
-    core::Capacity,
+    core::{Capacity, DepType, ScriptHashType, TransactionView},
     h256,
-    packed::{self, CellOutput, OutPoint, Script},
+    packed::{self, CellDep, CellOutput, OutPoint, Script},
     prelude::*,
     H160, H256,
 };
@@ -24,21 +26,28 @@ use clap::{App, Arg, ArgMatches};
 use faster_hex::hex_string;
 use serde_derive::{Deserialize, Serialize};
 
-use super::{CliSubCommand, Output};
+use super::{mock_tx::Loader, sudt, CliSubCommand, Output};
 use crate::plugin::{KeyStoreHandler, PluginManager, SignTarget};
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, CapacityParser, FilePathParser, FixedHashParser, FromStrParser,
-        HexParser, PrivkeyPathParser, PrivkeyWrapper,
+        AddressParser, ArgParser, CapacityParser, CellDepsParser, FilePathParser, FixedHashParser,
+        FromStrParser, HexParser, PrivkeyPathParser, PrivkeyWrapper, ScriptParser,
     },
+    cell_dep::{CellDepName, CellDeps},
+    fee_rate::get_fee_rate,
     genesis_info::GenesisInfo,
+    mock_tx_helper::MockTransactionHelper,
+    multisig::MultisigScript,
+    omnilock::{is_omnilock_type_hash, OmnilockArgs},
     other::{
         check_capacity, get_genesis_info, get_live_cell, get_live_cell_with_cache,
         get_network_type, get_privkey_signer, get_to_data, read_password,
     },
     rpc::HttpRpcClient,
-    tx_helper::{SignerFn, TxHelper},
+    tx_frames::{decode_frames, encode_frames},
+    tx_helper::{check_lock_script, SignerFn, SigningGroup, TxHelper},
+    tx_lint::{known_cell_deps, known_script_names, lint_tx_online, LintReport},
 };
 
 pub struct TxSubCommand<'a> {
@@ -60,6 +69,65 @@ impl<'a> TxSubCommand<'a> {
         }
     }
 
+    /// Lint the transaction before sending, `tx_file_network` is the network embedded in the
+    /// transaction file
+    fn lint(
+        &mut self,
+        tx: &TransactionView,
+        tx_file_network: Option<&str>,
+        network: NetworkType,
+        max_tx_fee: u64,
+        cell_deps: Option<&CellDeps>,
+    ) -> Result<LintReport, String> {
+        let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+        let mut report = lint_tx_online(
+            self.rpc_client,
+            tx,
+            network,
+            known_cell_deps(&genesis_info, cell_deps),
+            Some(max_tx_fee),
+        )?;
+        if let Some(tx_file_network) = tx_file_network {
+            if tx_file_network != network.to_str() {
+                report.error(
+                    "network",
+                    format!(
+                        "The transaction file is built for {}, but the node is {}",
+                        tx_file_network, network
+                    ),
+                );
+            }
+        }
+        Ok(report)
+    }
+
+    /// The cycles of each script group of the transaction, the unsigned groups are kept and
+    /// their verification errors are reported
+    fn cycle_report(
+        &mut self,
+        helper: &TxHelper,
+        cell_deps: Option<&CellDeps>,
+    ) -> Result<serde_json::Value, String> {
+        let full_tx = helper.build_tx(
+            |out_point, _with_data| helper.get_input_cell(&out_point).map(|(output, _)| output),
+            true,
+        )?;
+        let mut mock_tx = MockTransaction {
+            mock_info: MockInfo::default(),
+            tx: full_tx.data(),
+        };
+        let report = MockTransactionHelper::new(&mut mock_tx).verify_groups(
+            u64::max_value(),
+            Loader {
+                rpc_client: self.rpc_client,
+                dead_cells: false,
+            },
+        )?;
+        let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+        let names = known_script_names(Some(&genesis_info), cell_deps);
+        Ok(report.to_json(&full_tx, &names))
+    }
+
     pub fn subcommand(name: &'static str) -> App<'static> {
         let arg_tx_file = Arg::with_name("tx-file")
             .long("tx-file")
@@ -86,11 +154,20 @@ impl<'a> TxSubCommand<'a> {
             .default_value("1")
             .validator(|input| FromStrParser::<u8>::default().validate(input))
             .about("Multisig threshold");
+        let arg_multisig_script = Arg::with_name("multisig-script")
+            .long("multisig-script")
+            .takes_value(true)
+            .possible_values(&MultisigScript::NAMES)
+            .default_value("legacy")
+            .about("The multisig script variant, legacy: deployed in genesis block, v2: the new multisig script (data1) which checks the since of every input");
         let arg_since_absolute_epoch = Arg::with_name("since-absolute-epoch")
             .long("since-absolute-epoch")
             .takes_value(true)
             .validator(|input| FromStrParser::<u64>::default().validate(input))
             .about("Since absolute epoch number");
+        let arg_offline = Arg::with_name("offline")
+            .long("offline")
+            .about("Run without the node, only use the input cells and network embedded in the transaction file (the input cells are embedded when the inputs are added online)");
         let arg_skip_check = Arg::with_name("skip-check")
             .long("skip-check")
             .about("Send transaction without any check, be cautious to use this flag");
@@ -102,10 +179,11 @@ impl<'a> TxSubCommand<'a> {
                     .about("Init a common (sighash/multisig) transaction")
                     .arg(arg_tx_file.clone()),
                 App::new("add-multisig-config")
-                    .about("Add multisig config")
+                    .about("Add multisig config (the config unlocks both legacy and v2 multisig inputs, the address of the chosen variant is printed)")
                     .arg(arg_sighash_address.clone())
                     .arg(arg_require_first_n.clone())
                     .arg(arg_threshold.clone())
+                    .arg(arg_multisig_script.clone())
                     .arg(arg_tx_file.clone()),
                 App::new("clear-field")
                     .about("Remove all field items in transaction")
@@ -119,7 +197,7 @@ impl<'a> TxSubCommand<'a> {
                     )
                     .arg(arg_tx_file.clone()),
                 App::new("add-input")
-                    .about("Add cell input (with secp/multisig lock)")
+                    .about("Add cell input (with secp/multisig/omnilock lock), the omnilock cell dep is added by the `omnilock` item of <cell-deps>, otherwise the cell deps of omnilock and multisig v2 inputs must be added by `add-cell-dep`")
                     .arg(
                         Arg::with_name("tx-hash")
                             .long("tx-hash")
@@ -138,6 +216,11 @@ impl<'a> TxSubCommand<'a> {
                     )
                     .arg(arg_since_absolute_epoch.clone())
                     .arg(arg_tx_file.clone())
+                    .arg(
+                        sudt::arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information, the `omnilock` item is used to recognize the omnilock of other networks (like dev chain) and add its cell dep"),
+                    )
                     .arg(arg_skip_check.clone()),
                 App::new("add-output")
                     .about("Add cell output")
@@ -169,7 +252,83 @@ impl<'a> TxSubCommand<'a> {
                     )
                     .arg(arg::capacity().required(true))
                     .arg(arg::to_data())
-                    .arg(arg::to_data_path())
+                    .arg(arg::to_data_path().visible_alias("data-file").conflicts_with("to-data"))
+                    .arg(
+                        Arg::with_name("type-script")
+                            .long("type-script")
+                            .takes_value(true)
+                            .validator(|input| ScriptParser.validate(input))
+                            .conflicts_with("type-id")
+                            .about("The type script of the output, format: {code_hash}-{hash_type}[-{args}]"),
+                    )
+                    .arg(
+                        Arg::with_name("type-id")
+                            .long("type-id")
+                            .about("Add type id type script to the output (computed from the first input, so add inputs first and do not change the first input afterwards)"),
+                    )
+                    .arg(arg_tx_file.clone()),
+                App::new("add-cell-dep")
+                    .about("Add cell dep (the cell deps of sighash/multisig inputs are added automatically)")
+                    .arg(
+                        Arg::with_name("tx-hash")
+                            .long("tx-hash")
+                            .takes_value(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .required(true)
+                            .about("Transaction hash of the cell dep"),
+                    )
+                    .arg(
+                        Arg::with_name("index")
+                            .long("index")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u32>::default().validate(input))
+                            .required(true)
+                            .about("Transaction output index of the cell dep"),
+                    )
+                    .arg(
+                        Arg::with_name("dep-type")
+                            .long("dep-type")
+                            .takes_value(true)
+                            .possible_values(&["code", "dep_group"])
+                            .default_value("code")
+                            .about("The dep type"),
+                    )
+                    .arg(arg_tx_file.clone()),
+                App::new("add-header-dep")
+                    .about("Add header dep")
+                    .arg(
+                        Arg::with_name("block-hash")
+                            .long("block-hash")
+                            .takes_value(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .required(true)
+                            .about("The block hash"),
+                    )
+                    .arg(arg_tx_file.clone()),
+                App::new("balance")
+                    .about("Collect inputs from <from-address> / <lock-arg> to pay the outputs and the transaction fee, and add a change output")
+                    .arg(
+                        Arg::with_name("from-address")
+                            .long("from-address")
+                            .takes_value(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .conflicts_with("lock-arg")
+                            .about("Collect inputs from this address (sighash/multisig/omnilock, the multisig config must be added first)"),
+                    )
+                    .arg(
+                        arg::lock_arg()
+                            .required_unless("from-address")
+                            .about("Collect inputs from the sighash address of this lock arg (the derived addresses of a HD account are not included)"),
+                    )
+                    .arg(
+                        Arg::with_name("change-address")
+                            .long("change-address")
+                            .takes_value(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .about("The change address (default: the sender address)"),
+                    )
+                    .arg(arg::fee_rate())
+                    .arg(arg::max_fee_rate())
                     .arg(arg_tx_file.clone()),
                 App::new("add-signature")
                     .about("Add signature")
@@ -197,10 +356,33 @@ impl<'a> TxSubCommand<'a> {
                             })
                             .about("The signature"),
                     )
-                    .arg(arg_tx_file.clone()),
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_offline.clone()),
                 App::new("info")
-                    .about("Show detail of this multisig transaction (capacity, tx-fee, etc.)")
-                    .arg(arg_tx_file.clone()),
+                    .about("Show detail of this multisig transaction (capacity, tx-fee, etc.), the cycles of every script group, tx size and fee rate are also reported when online")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg_offline.clone())
+                    .arg(
+                        sudt::arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information, used to name the scripts in cycle report"),
+                    ),
+                App::new("merge")
+                    .about("Merge the signatures of independently signed copies of a transaction, the signatures are verified")
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .multiple(true)
+                            .about("The signed copies of a transaction (format: json), must have same transaction hash"),
+                    )
+                    .arg(
+                        Arg::with_name("output")
+                            .long("output")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("The merged transaction file"),
+                    ),
                 App::new("sign-inputs")
                     .about("Sign all sighash/multisig inputs in this transaction")
                     .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
@@ -211,19 +393,64 @@ impl<'a> TxSubCommand<'a> {
                             .long("add-signatures")
                             .about("Sign and add signatures"),
                     )
+                    .arg(arg_offline)
                     .arg(arg_skip_check.clone()),
                 App::new("send")
                     .about("Send multisig transaction")
                     .arg(arg_tx_file.clone())
+                    .arg(arg::max_send_tx_fee())
+                    .arg(arg::skip_lint())
+                    .arg(arg_skip_check),
+                App::new("lint")
+                    .about("Check the transaction before sending: occupied capacity, dust change, duplicate inputs, cell/header deps of known scripts, since values, witnesses, fee and network, the unsigned inputs are reported as errors")
+                    .arg(arg_tx_file.clone())
+                    .arg(arg::max_send_tx_fee())
                     .arg(
-                        Arg::with_name("max-tx-fee")
-                            .long("max-tx-fee")
+                        sudt::arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps information, the cell deps of these scripts are also checked"),
+                    ),
+                App::new("export-frames")
+                    .about("Split a transaction file into checksummed and sequence-numbered text frames (base32), to move it to/from an air-gapped machine")
+                    .arg(arg_tx_file.clone().validator(|input| FilePathParser::new(true).validate(input)))
+                    .arg(
+                        Arg::with_name("chunk-size")
+                            .long("chunk-size")
                             .takes_value(true)
-                            .default_value("1.0")
-                            .validator(|input| CapacityParser.validate(input))
-                            .about("Max transaction fee (unit: CKB)"),
+                            .default_value("256")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("The number of transaction file bytes in one frame"),
                     )
-                    .arg(arg_skip_check),
+                    .arg(
+                        Arg::with_name("frames-file")
+                            .long("frames-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Write the frames to this file (one frame per line), print them if not given"),
+                    ),
+                App::new("import-frames")
+                    .about("Reassemble a transaction file from the frames of `tx export-frames` (in any order), the frames and the transaction are verified")
+                    .arg(
+                        Arg::with_name("frames-file")
+                            .long("frames-file")
+                            .takes_value(true)
+                            .required_unless("frame")
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .about("The file contains the frames (one frame per line)"),
+                    )
+                    .arg(
+                        Arg::with_name("frame")
+                            .long("frame")
+                            .takes_value(true)
+                            .multiple(true)
+                            .conflicts_with("frames-file")
+                            .about("The frame text"),
+                    )
+                    .arg(
+                        arg_tx_file
+                            .clone()
+                            .about("The reassembled transaction file (format: json)"),
+                    ),
                 App::new("build-multisig-address")
                     .about(
                         "Build multisig address with multisig config and since(optional) argument",
@@ -231,6 +458,7 @@ impl<'a> TxSubCommand<'a> {
                     .arg(arg_sighash_address.clone())
                     .arg(arg_require_first_n.clone())
                     .arg(arg_threshold.clone())
+                    .arg(arg_multisig_script)
                     .arg(arg_since_absolute_epoch.clone()),
             ])
     }
@@ -238,7 +466,19 @@ impl<'a> TxSubCommand<'a> {
 
 impl<'a> CliSubCommand for TxSubCommand<'a> {
     fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
-        let network = get_network_type(self.rpc_client)?;
+        // Frames are used on air-gapped machines, the node is not required
+        match matches.subcommand() {
+            ("export-frames", Some(m)) => return export_frames(m),
+            ("import-frames", Some(m)) => return import_frames(m),
+            _ => {}
+        }
+        let network = match matches.subcommand() {
+            (_, Some(m)) if m.is_present("offline") => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                get_tx_file_network(&tx_file)?
+            }
+            _ => get_network_type(self.rpc_client)?,
+        };
 
         match matches.subcommand() {
             ("init", Some(m)) => {
@@ -280,23 +520,32 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let since_absolute_epoch_opt: Option<u64> =
                     FromStrParser::<u64>::default().from_matches_opt(m, "since-absolute-epoch")?;
 
+                let cell_deps: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
+                let omnilock = cell_deps
+                    .as_ref()
+                    .and_then(|cell_deps| cell_deps.get_item(CellDepName::Omnilock));
+
                 let skip_check: bool = m.is_present("skip-check");
                 let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
                 let out_point = OutPoint::new_builder()
                     .tx_hash(tx_hash.pack())
                     .index(index.pack())
                     .build();
-                let get_live_cell = |out_point, with_data| {
-                    get_live_cell(self.rpc_client, out_point, with_data).map(|(output, _)| output)
-                };
+                let mut get_live_cell =
+                    |out_point, with_data| get_live_cell(self.rpc_client, out_point, with_data);
                 modify_tx_file(&tx_file, network, |helper| {
                     helper.add_input(
                         out_point,
                         since_absolute_epoch_opt,
-                        get_live_cell,
+                        |out_point, with_data| {
+                            get_live_cell(out_point, with_data).map(|(output, _)| output)
+                        },
                         &genesis_info,
+                        omnilock,
                         skip_check,
-                    )
+                    )?;
+                    helper.snapshot_inputs(|out_point| get_live_cell(out_point, true))
                 })?;
 
                 Ok(Output::new_success())
@@ -312,6 +561,10 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     AddressParser::new_multisig()
                         .from_matches_opt(m, "to-long-multisig-address")?;
 
+                let type_script_opt: Option<Script> =
+                    ScriptParser.from_matches_opt(m, "type-script")?;
+                let is_type_id = m.is_present("type-id");
+
                 let to_data = get_to_data(m)?;
                 check_capacity(capacity, to_data.len())?;
                 if let Some(address) = to_long_multisig_address_opt.as_ref() {
@@ -328,24 +581,154 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .or(to_long_multisig_address_opt)
                     .map(|address| Script::from(address.payload()))
                     .ok_or_else(|| "missing target address".to_string())?;
-                let output = CellOutput::new_builder()
-                    .capacity(Capacity::shannons(capacity).pack())
-                    .lock(lock_script)
-                    .build();
-
                 modify_tx_file(&tx_file, network, |helper| {
+                    let type_script_opt = if is_type_id {
+                        let output_index = helper.transaction().outputs().len() as u64;
+                        Some(
+                            Script::new_builder()
+                                .code_hash(TYPE_ID_CODE_HASH.pack())
+                                .hash_type(ScriptHashType::Type.into())
+                                .args(helper.type_id_args(output_index)?.pack())
+                                .build(),
+                        )
+                    } else {
+                        type_script_opt
+                    };
+                    let output = CellOutput::new_builder()
+                        .capacity(Capacity::shannons(capacity).pack())
+                        .lock(lock_script)
+                        .type_(type_script_opt.pack())
+                        .build();
+                    let occupied = output
+                        .occupied_capacity(Capacity::bytes(to_data.len()).unwrap())
+                        .map_err(|err| err.to_string())?;
+                    if occupied.as_u64() > capacity {
+                        return Err(format!(
+                            "Capacity can not hold the output, occupied capacity: {:#}",
+                            HumanCapacity(occupied.as_u64())
+                        ));
+                    }
                     helper.add_output(output, to_data);
                     Ok(())
                 })?;
 
                 Ok(Output::new_success())
             }
+            ("balance", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let from_address_opt: Option<Address> = AddressParser::default()
+                    .set_network(network)
+                    .from_matches_opt(m, "from-address")?;
+                let from_lock = if let Some(address) = from_address_opt {
+                    Script::from(address.payload())
+                } else {
+                    let lock_arg: H160 =
+                        FixedHashParser::<H160>::default().from_matches(m, "lock-arg")?;
+                    Script::from(&AddressPayload::from_pubkey_hash(lock_arg))
+                };
+                check_lock_script(&from_lock, false)
+                    .map_err(|err| format!("Invalid sender: {}", err))?;
+                let change_lock = AddressParser::default()
+                    .set_network(network)
+                    .from_matches_opt::<Address>(m, "change-address")?
+                    .map(|address| Script::from(address.payload()))
+                    .unwrap_or_else(|| from_lock.clone());
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
+
+                let genesis_info = get_genesis_info(&self.genesis_info, self.rpc_client)?;
+                let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+                let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
+                    Default::default();
+                let mut get_live_cell = |out_point: OutPoint, with_data: bool| {
+                    get_live_cell_with_cache(
+                        &mut live_cell_cache,
+                        self.rpc_client,
+                        out_point,
+                        with_data,
+                    )
+                };
+                let (added_inputs, change_capacity, tx_fee) =
+                    modify_tx_file(&tx_file, network, |helper| {
+                        helper.snapshot_inputs(|out_point| get_live_cell(out_point, true))?;
+                        helper.balance(
+                            &from_lock,
+                            &change_lock,
+                            fee_rate,
+                            &mut cell_collector,
+                            |out_point, with_data| {
+                                get_live_cell(out_point, with_data).map(|(output, _)| output)
+                            },
+                            &genesis_info,
+                        )
+                    })?;
+                let resp = serde_json::json!({
+                    "added_inputs": added_inputs,
+                    "change_capacity": format!("{:#}", HumanCapacity(change_capacity)),
+                    "tx_fee": format!("{:#}", HumanCapacity(tx_fee)),
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("add-cell-dep", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let tx_hash: H256 =
+                    FixedHashParser::<H256>::default().from_matches(m, "tx-hash")?;
+                let index: u32 = FromStrParser::<u32>::default().from_matches(m, "index")?;
+                let dep_type = match m.value_of("dep-type").unwrap() {
+                    "code" => DepType::Code,
+                    "dep_group" => DepType::DepGroup,
+                    dep_type => {
+                        return Err(format!(
+                            "Invalid dep type: {}, expected one of: code, dep_group",
+                            dep_type
+                        ))
+                    }
+                };
+                let cell_dep = CellDep::new_builder()
+                    .out_point(
+                        OutPoint::new_builder()
+                            .tx_hash(tx_hash.pack())
+                            .index(index.pack())
+                            .build(),
+                    )
+                    .dep_type(dep_type.into())
+                    .build();
+                let added =
+                    modify_tx_file(
+                        &tx_file,
+                        network,
+                        |helper| Ok(helper.add_cell_dep(cell_dep)),
+                    )?;
+                if !added {
+                    eprintln!("The cell dep already exists");
+                }
+                Ok(Output::new_success())
+            }
+            ("add-header-dep", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let block_hash: H256 =
+                    FixedHashParser::<H256>::default().from_matches(m, "block-hash")?;
+                if self.rpc_client.get_header(block_hash.clone())?.is_none() {
+                    return Err(format!("Block header not found: {:#x}", block_hash));
+                }
+                let added = modify_tx_file(&tx_file, network, |helper| {
+                    Ok(helper.add_header_dep(block_hash.pack()))
+                })?;
+                if !added {
+                    eprintln!("The header dep already exists");
+                }
+                Ok(Output::new_success())
+            }
             ("add-signature", Some(m)) => {
                 let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
                 let lock_arg: Bytes = HexParser.from_matches(m, "lock-arg")?;
                 let signature: Bytes = HexParser.from_matches(m, "signature")?;
 
+                let offline = m.is_present("offline");
+
                 modify_tx_file(&tx_file, network, |helper| {
+                    if offline {
+                        helper.check_input_cells()?;
+                    }
                     helper.add_signature(lock_arg, signature)
                 })?;
                 Ok(Output::new_success())
@@ -358,6 +741,8 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let require_first_n: u8 =
                     FromStrParser::<u8>::default().from_matches(m, "require-first-n")?;
                 let threshold: u8 = FromStrParser::<u8>::default().from_matches(m, "threshold")?;
+                let multisig_script: MultisigScript = FromStrParser::<MultisigScript>::default()
+                    .from_matches(m, "multisig-script")?;
 
                 let sighash_addresses = sighash_addresses
                     .into_iter()
@@ -365,35 +750,43 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .collect::<Vec<_>>();
                 let cfg = MultisigConfig::new_with(sighash_addresses, require_first_n, threshold)
                     .map_err(|err| err.to_string())?;
-                modify_tx_file(&tx_file, network, |helper| {
-                    helper.add_multisig_config(cfg);
-                    Ok(())
+                let hash160 = cfg.hash160();
+                // The inputs locked by the config decide the variant if any
+                let multisig_script = modify_tx_file(&tx_file, network, |helper| {
+                    helper.add_multisig_config(cfg.clone());
+                    helper.set_multisig_script(hash160.clone(), multisig_script);
+                    Ok(helper.multisig_script(&hash160))
                 })?;
-                Ok(Output::new_success())
+                let address_payload = multisig_script.address_payload(&cfg, None);
+                let resp = serde_json::json!({
+                    "multisig-script": multisig_script.to_string(),
+                    "address": Address::new(network, address_payload, true).to_string(),
+                });
+                Ok(Output::new_output(resp))
             }
             ("info", Some(m)) => {
                 let tx_file: PathBuf = FilePathParser::new(false).from_matches(m, "tx-file")?;
-
-                let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
-                    Default::default();
-                let mut get_live_cell = |out_point: OutPoint, with_data: bool| {
-                    get_live_cell_with_cache(
-                        &mut live_cell_cache,
-                        self.rpc_client,
-                        out_point,
-                        with_data,
-                    )
-                };
+                let offline = m.is_present("offline");
 
                 let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
                 let repr: ReprTxHelper =
                     serde_json::from_reader(&file).map_err(|err| err.to_string())?;
-                let helper = TxHelper::try_from(repr)?;
+                let version = repr.version;
+                let mut helper = TxHelper::try_from(repr)?;
+                if offline {
+                    helper.check_input_cells()?;
+                } else {
+                    helper.snapshot_inputs(|out_point| {
+                        get_live_cell(self.rpc_client, out_point, true)
+                    })?;
+                }
                 let tx = helper.transaction();
+                let cell_deps: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
 
                 let mut input_total = 0;
                 for input in tx.inputs().into_iter() {
-                    let (output, data) = get_live_cell(input.previous_output(), true)?;
+                    let (output, data) = helper.get_input_cell(&input.previous_output())?;
                     let capacity: u64 = output.capacity().unpack();
                     input_total += capacity;
 
@@ -437,10 +830,89 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     format!("-{:#}", HumanCapacity(output_total - input_total))
                 };
 
+                let signing_groups = helper.signing_groups()?;
+                for group in &signing_groups {
+                    print_signing_group(network, group);
+                }
+                let signing_progress = signing_groups
+                    .iter()
+                    .map(|group| {
+                        let lock_hash: H256 = group.lock_script.calc_script_hash().unpack();
+                        serde_json::json!({
+                            "lock_hash": lock_hash,
+                            "input_indexes": group.input_indexes,
+                            "signatures": group.signatures,
+                            "threshold": group.threshold,
+                            "complete": group.is_complete(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                let cycle_report = if offline {
+                    serde_json::Value::Null
+                } else {
+                    self.cycle_report(&helper, cell_deps.as_ref())
+                        .unwrap_or_else(|err| serde_json::json!({ "error": err }))
+                };
+
                 let resp = serde_json::json!({
+                    "version": version,
                     "input_total": format!("{:#}", HumanCapacity(input_total)),
                     "output_total": format!("{:#}", HumanCapacity(output_total)),
                     "tx_fee": tx_fee_string,
+                    "signing_progress": signing_progress,
+                    "fully_signed": signing_groups.iter().all(SigningGroup::is_complete),
+                    "cycle_report": cycle_report,
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("merge", Some(m)) => {
+                let tx_files: Vec<PathBuf> =
+                    FilePathParser::new(true).from_matches_vec(m, "tx-file")?;
+                let output: PathBuf = FilePathParser::new(false).from_matches(m, "output")?;
+
+                let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
+                    Default::default();
+                let mut get_live_cell = |out_point: OutPoint, with_data: bool| {
+                    get_live_cell_with_cache(
+                        &mut live_cell_cache,
+                        self.rpc_client,
+                        out_point,
+                        with_data,
+                    )
+                };
+
+                let mut merged: Option<TxHelper> = None;
+                for tx_file in &tx_files {
+                    let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
+                    let repr: ReprTxHelper =
+                        serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+                    let helper = TxHelper::try_from(repr)
+                        .map_err(|err| format!("Load {} failed: {}", tx_file.display(), err))?;
+                    if let Some(merged) = merged.as_mut() {
+                        merged.merge(helper).map_err(|err| {
+                            format!("Merge {} failed: {}", tx_file.display(), err)
+                        })?;
+                    } else {
+                        merged = Some(helper);
+                    }
+                }
+                let mut helper = merged.ok_or_else(|| String::from("<tx-file> is required"))?;
+                helper.snapshot_inputs(|out_point| get_live_cell(out_point, true))?;
+                helper.check_signatures()?;
+                for group in helper.signing_groups()? {
+                    print_signing_group(network, &group);
+                }
+
+                let tx_hash: H256 = helper.transaction().hash().unpack();
+                let signatures: usize = helper.signatures().values().map(HashSet::len).sum();
+                let repr = ReprTxHelper::new(helper, network);
+                let content = serde_json::to_string_pretty(&repr).map_err(|err| err.to_string())?;
+                fs::write(&output, content).map_err(|err| err.to_string())?;
+                let resp = serde_json::json!({
+                    "transaction_hash": tx_hash,
+                    "signatures": signatures,
+                    "output": output,
                 });
                 Ok(Output::new_output(resp))
             }
@@ -466,6 +938,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     })
                     .transpose()?;
                 let skip_check: bool = m.is_present("skip-check");
+                let offline = m.is_present("offline");
 
                 let mut signer = if let Some(privkey) = privkey_opt {
                     get_privkey_signer(privkey)
@@ -477,24 +950,27 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     };
                     let account = account_opt.unwrap();
                     let keystore = self.plugin_mgr.keystore_handler();
-                    let new_client = HttpRpcClient::new(self.rpc_client.url().to_owned());
+                    let new_client = if offline {
+                        None
+                    } else {
+                        Some(HttpRpcClient::new(self.rpc_client.url().to_owned()))
+                    };
                     get_keystore_signer(keystore, new_client, account, password)
                 };
 
-                let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
-                    Default::default();
-                let get_live_cell = |out_point: OutPoint, with_data: bool| {
-                    get_live_cell_with_cache(
-                        &mut live_cell_cache,
-                        self.rpc_client,
-                        out_point,
-                        with_data,
-                    )
-                    .map(|(output, _)| output)
-                };
-
                 let signatures = modify_tx_file(&tx_file, network, |helper| {
-                    let signatures = helper.sign_inputs(&mut signer, get_live_cell, skip_check)?;
+                    if offline {
+                        helper.check_input_cells()?;
+                    } else {
+                        helper.snapshot_inputs(|out_point| {
+                            get_live_cell(self.rpc_client, out_point, true)
+                        })?;
+                    }
+                    // The input cells are all snapshotted
+                    let get_input_cell = |out_point: OutPoint, _with_data: bool| {
+                        helper.get_input_cell(&out_point).map(|(output, _)| output)
+                    };
+                    let signatures = helper.sign_inputs(&mut signer, get_input_cell, skip_check)?;
                     if m.is_present("add-signatures") {
                         for (lock_arg, signature) in signatures.clone() {
                             helper.add_signature(lock_arg, signature)?;
@@ -517,6 +993,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let tx_file: PathBuf = FilePathParser::new(false).from_matches(m, "tx-file")?;
                 let max_tx_fee: u64 = CapacityParser.from_matches(m, "max-tx-fee")?;
                 let skip_check: bool = m.is_present("skip-check");
+                let skip_lint: bool = m.is_present("skip-lint");
 
                 let mut live_cell_cache: HashMap<(OutPoint, bool), (CellOutput, Bytes)> =
                     Default::default();
@@ -533,6 +1010,7 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
                 let repr: ReprTxHelper =
                     serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+                let tx_file_network = repr.network.clone();
                 let helper = TxHelper::try_from(repr)?;
 
                 if !skip_check {
@@ -547,6 +1025,10 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     }
                 }
                 let tx = helper.build_tx(&mut get_live_cell, skip_check)?;
+                if !skip_check && !skip_lint {
+                    self.lint(&tx, tx_file_network.as_deref(), network, max_tx_fee, None)?
+                        .check_before_send()?;
+                }
                 let rpc_tx = json_types::Transaction::from(tx.data());
                 if debug {
                     eprintln!(
@@ -560,6 +1042,39 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .map_err(|err| format!("Send transaction error: {}", err))?;
                 Ok(Output::new_output(resp))
             }
+            ("lint", Some(m)) => {
+                let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+                let max_tx_fee: u64 = CapacityParser.from_matches(m, "max-tx-fee")?;
+                let cell_deps: Option<CellDeps> =
+                    CellDepsParser.from_matches_opt(m, "cell-deps")?;
+
+                let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
+                let repr: ReprTxHelper =
+                    serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+                let tx_file_network = repr.network.clone();
+                let helper = TxHelper::try_from(repr)?;
+                // The dead inputs and unsigned inputs are kept, they are reported by the lint
+                let tx = helper.build_tx(
+                    |out_point, _with_data| {
+                        helper
+                            .get_input_cell(&out_point)
+                            .or_else(|_| get_live_cell(self.rpc_client, out_point, false))
+                            .map(|(output, _)| output)
+                    },
+                    true,
+                )?;
+                let report = self.lint(
+                    &tx,
+                    tx_file_network.as_deref(),
+                    network,
+                    max_tx_fee,
+                    cell_deps.as_ref(),
+                )?;
+                let tx_hash: H256 = tx.hash().unpack();
+                let mut resp = report.to_json();
+                resp["transaction_hash"] = serde_json::json!(tx_hash);
+                Ok(Output::new_output(resp))
+            }
             ("build-multisig-address", Some(m)) => {
                 let sighash_addresses: Vec<Address> = AddressParser::new_sighash()
                     .set_network(network)
@@ -569,6 +1084,8 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                 let threshold: u8 = FromStrParser::<u8>::default().from_matches(m, "threshold")?;
                 let since_absolute_epoch_opt: Option<u64> =
                     FromStrParser::<u64>::default().from_matches_opt(m, "since-absolute-epoch")?;
+                let multisig_script: MultisigScript = FromStrParser::<MultisigScript>::default()
+                    .from_matches(m, "multisig-script")?;
 
                 let sighash_addresses = sighash_addresses
                     .into_iter()
@@ -576,9 +1093,11 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
                     .collect::<Vec<_>>();
                 let cfg = MultisigConfig::new_with(sighash_addresses, require_first_n, threshold)
                     .map_err(|err| err.to_string())?;
-                let address_payload = cfg.to_address_payload(since_absolute_epoch_opt);
+                let address_payload =
+                    multisig_script.address_payload(&cfg, since_absolute_epoch_opt);
                 let lock_script = Script::from(&address_payload);
                 let resp = serde_json::json!({
+                    "multisig-script": multisig_script.to_string(),
                     "mainnet": Address::new(NetworkType::Mainnet, address_payload.clone(), true).to_string(),
                     "testnet": Address::new(NetworkType::Testnet, address_payload.clone(), true).to_string(),
                     "lock-arg": format!("0x{}", hex_string(address_payload.args().as_ref())),
@@ -591,6 +1110,124 @@ impl<'a> CliSubCommand for TxSubCommand<'a> {
     }
 }
 
+fn export_frames(m: &ArgMatches) -> Result<Output, String> {
+    let tx_file: PathBuf = FilePathParser::new(true).from_matches(m, "tx-file")?;
+    let chunk_size: usize = FromStrParser::<usize>::default().from_matches(m, "chunk-size")?;
+    let frames_file_opt: Option<PathBuf> =
+        FilePathParser::new(false).from_matches_opt(m, "frames-file")?;
+
+    let file = fs::File::open(tx_file).map_err(|err| err.to_string())?;
+    let repr: ReprTxHelper = serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+    let helper = TxHelper::try_from(repr.clone())?;
+    let tx_hash: H256 = helper.transaction().hash().unpack();
+    let content = serde_json::to_vec(&repr).map_err(|err| err.to_string())?;
+    let frames = encode_frames(&content, chunk_size)?;
+
+    let resp = if let Some(frames_file) = frames_file_opt {
+        let mut content = frames.join("\n");
+        content.push('\n');
+        fs::write(&frames_file, content).map_err(|err| err.to_string())?;
+        serde_json::json!({
+            "transaction_hash": tx_hash,
+            "frames": frames.len(),
+            "frames_file": frames_file,
+        })
+    } else {
+        serde_json::json!({
+            "transaction_hash": tx_hash,
+            "frames": frames,
+        })
+    };
+    Ok(Output::new_output(resp))
+}
+
+fn import_frames(m: &ArgMatches) -> Result<Output, String> {
+    let tx_file: PathBuf = FilePathParser::new(false).from_matches(m, "tx-file")?;
+    let frames_file_opt: Option<PathBuf> =
+        FilePathParser::new(true).from_matches_opt(m, "frames-file")?;
+
+    let frames: Vec<String> = if let Some(frames_file) = frames_file_opt {
+        fs::read_to_string(frames_file)
+            .map_err(|err| err.to_string())?
+            .lines()
+            .map(ToString::to_string)
+            .collect()
+    } else {
+        m.values_of_lossy("frame").unwrap_or_default()
+    };
+    let content = decode_frames(frames.iter().map(String::as_str))?;
+    let repr: ReprTxHelper = serde_json::from_slice(&content)
+        .map_err(|err| format!("Invalid transaction file content: {}", err))?;
+    let helper = TxHelper::try_from(repr.clone())?;
+    let tx_hash: H256 = helper.transaction().hash().unpack();
+
+    let content = serde_json::to_string_pretty(&repr).map_err(|err| err.to_string())?;
+    fs::write(&tx_file, content).map_err(|err| err.to_string())?;
+    let resp = serde_json::json!({
+        "transaction_hash": tx_hash,
+        "tx_file": tx_file,
+    });
+    Ok(Output::new_output(resp))
+}
+
+fn print_signing_group(network: NetworkType, group: &SigningGroup) {
+    let lock_hash: H256 = group.lock_script.calc_script_hash().unpack();
+    let lock_hash = format!("{:#x}", lock_hash);
+    let inputs = group
+        .input_indexes
+        .iter()
+        .map(|idx| idx.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let lock_kind = lock_kind(network, group.lock_script.clone());
+    let status = if group.threshold == 0 {
+        if lock_kind.contains("multisig") {
+            "missing multisig config".to_string()
+        } else {
+            "unknown lock, can not sign".to_string()
+        }
+    } else if group.is_complete() {
+        "complete".to_string()
+    } else {
+        format!("missing {}", group.threshold as usize - group.signatures)
+    };
+    eprintln!(
+        "[signing] {}/{} signatures for group {}… (lock-kind: {}, inputs: {}), {}",
+        group.signatures,
+        group.threshold,
+        &lock_hash[..10],
+        lock_kind,
+        inputs,
+        status,
+    );
+}
+
+fn lock_kind(network: NetworkType, lock: packed::Script) -> String {
+    if let Some(multisig_script) = MultisigScript::from_lock(&lock) {
+        let prefix = match multisig_script {
+            MultisigScript::Legacy => "multisig",
+            MultisigScript::V2 => "multisig(v2)",
+        };
+        return if lock.args().raw_data().len() == 20 {
+            format!("{} without since", prefix)
+        } else {
+            format!("{} with since", prefix)
+        };
+    }
+    let address_payload = AddressPayload::from(lock);
+    let code_hash = address_payload.code_hash(Some(network));
+    if is_omnilock_type_hash(&code_hash.unpack()) {
+        match OmnilockArgs::from_slice(address_payload.args().as_ref()) {
+            Ok(args) => format!("omnilock({})", args.mode()),
+            Err(_) => "omnilock(unsupported)".to_string(),
+        }
+    } else if code_hash == SIGHASH_TYPE_HASH.pack() {
+        "sighash(secp)".to_string()
+    } else {
+        "unknown".to_string()
+    }
+}
+
 fn print_cell_info(
     prefix: &str,
     network: NetworkType,
@@ -599,17 +1236,8 @@ fn print_cell_info(
     data_len: usize,
     type_script_empty: bool,
 ) {
-    let address_payload = AddressPayload::from(lock);
-    let lock_kind = if address_payload.code_hash(Some(network)) == MULTISIG_TYPE_HASH.pack() {
-        if address_payload.args().len() == 20 {
-            "multisig without since"
-        } else {
-            "multisig with since"
-        }
-    } else {
-        "sighash(secp)"
-    };
-    let address = Address::new(network, address_payload, true);
+    let lock_kind = lock_kind(network, lock.clone());
+    let address = Address::new(network, AddressPayload::from(lock), true);
     let type_script_status = if type_script_empty { "none" } else { "some" };
     eprintln!(
         "[{}] {} => {}, (data-length: {}, type-script: {}, lock-kind: {})",
@@ -624,7 +1252,7 @@ fn print_cell_info(
 
 fn get_keystore_signer(
     keystore: KeyStoreHandler,
-    mut client: HttpRpcClient,
+    mut client_opt: Option<HttpRpcClient>,
     account: H160,
     password: Option<String>,
 ) -> SignerFn {
@@ -638,6 +1266,11 @@ fn get_keystore_signer(
                     let sign_target = if keystore.has_account_in_default(account.clone())? {
                         SignTarget::AnyData(Default::default())
                     } else {
+                        let client = client_opt.as_mut().ok_or_else(|| {
+                            String::from(
+                                "The keystore plugin requires the input transactions from the node, can not sign in offline mode",
+                            )
+                        })?;
                         let inputs = tx
                             .inputs
                             .iter()
@@ -705,26 +1338,132 @@ fn modify_tx_file<T, F: FnOnce(&mut TxHelper) -> Result<T, String>>(
     Ok(result)
 }
 
+/// Read the network type embedded in the transaction file (for offline mode)
+fn get_tx_file_network(path: &Path) -> Result<NetworkType, String> {
+    let file = fs::File::open(path).map_err(|err| err.to_string())?;
+    let repr: ReprTxHelper = serde_json::from_reader(&file).map_err(|err| err.to_string())?;
+    let network = repr.network.ok_or_else(|| {
+        format!(
+            "No network embedded in transaction file {}, it can not be used in offline mode",
+            path.display()
+        )
+    })?;
+    NetworkType::from_raw_str(&network)
+        .ok_or_else(|| format!("Unexpected network type: {}", network))
+}
+
+/// The current version of the transaction file format, the files without `version` field are
+/// version 0 (without input cell snapshots and signing groups).
+pub(crate) const TX_FILE_VERSION: u32 = 1;
+
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct ReprTxHelper {
+    #[serde(default)]
+    pub(crate) version: u32,
+    /// The network type string (ckb, ckb_testnet, ckb_staging, ckb_dev), used in offline mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) network: Option<String>,
     pub(crate) transaction: json_types::Transaction,
     pub(crate) multisig_configs: HashMap<H160, ReprMultisigConfig>,
     pub(crate) signatures: HashMap<JsonBytes, Vec<JsonBytes>>,
+    /// The snapshots of input cells (in inputs order)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) input_cells: Vec<ReprInputCell>,
+    /// The Omnilock code hash of other networks (like dev chain)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) omnilock_code_hash: Option<H256>,
+    /// The signing progress of input groups, for co-signers to inspect (ignored when loading)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) signing_groups: Vec<ReprSigningGroup>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReprInputCell {
+    pub(crate) out_point: json_types::OutPoint,
+    pub(crate) output: json_types::CellOutput,
+    pub(crate) data: JsonBytes,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReprSigningGroup {
+    pub(crate) lock_hash: H256,
+    pub(crate) lock_script: json_types::Script,
+    pub(crate) input_indexes: Vec<u32>,
+    pub(crate) required_signers: Vec<String>,
+    pub(crate) require_first_n: u8,
+    pub(crate) threshold: u8,
+    pub(crate) signatures: u32,
+}
+
+impl ReprSigningGroup {
+    fn new(group: SigningGroup, network: NetworkType) -> Self {
+        ReprSigningGroup {
+            lock_hash: group.lock_script.calc_script_hash().unpack(),
+            lock_script: group.lock_script.into(),
+            input_indexes: group
+                .input_indexes
+                .into_iter()
+                .map(|idx| idx as u32)
+                .collect(),
+            required_signers: group
+                .signers
+                .into_iter()
+                .map(|hash160| {
+                    let payload = AddressPayload::from_pubkey_hash(hash160);
+                    Address::new(network, payload, false).to_string()
+                })
+                .collect(),
+            require_first_n: group.require_first_n,
+            threshold: group.threshold,
+            signatures: group.signatures as u32,
+        }
+    }
 }
 
 impl ReprTxHelper {
     pub(crate) fn new(tx: TxHelper, network: NetworkType) -> Self {
+        let input_cells = tx
+            .transaction()
+            .inputs()
+            .into_iter()
+            .filter_map(|input| {
+                let out_point = input.previous_output();
+                tx.input_cells()
+                    .get(&out_point)
+                    .map(|(output, data)| ReprInputCell {
+                        out_point: out_point.into(),
+                        output: output.clone().into(),
+                        data: JsonBytes::from_bytes(data.clone()),
+                    })
+            })
+            .collect();
+        // Only available when all input cells are snapshotted
+        let signing_groups = tx
+            .signing_groups()
+            .map(|groups| {
+                groups
+                    .into_iter()
+                    .map(|group| ReprSigningGroup::new(group, network))
+                    .collect()
+            })
+            .unwrap_or_default();
         ReprTxHelper {
+            version: TX_FILE_VERSION,
+            network: Some(network.to_str().to_string()),
+            input_cells,
+            omnilock_code_hash: tx.omnilock_code_hash().cloned(),
+            signing_groups,
             transaction: tx.transaction().data().into(),
             multisig_configs: tx
                 .multisig_configs()
                 .iter()
                 .map(|(lock_arg, cfg)| {
-                    (
-                        lock_arg.clone(),
-                        ReprMultisigConfig::new((*cfg).clone(), network),
-                    )
+                    let mut repr = ReprMultisigConfig::new((*cfg).clone(), network);
+                    repr.multisig_script = tx.multisig_script(lock_arg);
+                    (lock_arg.clone(), repr)
                 })
                 .collect(),
             signatures: tx
@@ -748,11 +1487,20 @@ impl ReprTxHelper {
 impl TryFrom<ReprTxHelper> for TxHelper {
     type Error = String;
     fn try_from(repr: ReprTxHelper) -> Result<Self, Self::Error> {
+        if repr.version > TX_FILE_VERSION {
+            return Err(format!(
+                "Unsupported transaction file version: {}, the max supported version: {}",
+                repr.version, TX_FILE_VERSION
+            ));
+        }
         let transaction = packed::Transaction::from(repr.transaction).into_view();
         let multisig_configs = repr
             .multisig_configs
             .into_values()
-            .map(MultisigConfig::try_from)
+            .map(|repr| {
+                let multisig_script = repr.multisig_script;
+                MultisigConfig::try_from(repr).map(|cfg| (cfg, multisig_script))
+            })
             .collect::<Result<Vec<_>, String>>()?;
         let signatures: HashMap<Bytes, HashSet<Bytes>> = repr
             .signatures
@@ -766,7 +1514,18 @@ impl TryFrom<ReprTxHelper> for TxHelper {
             .collect();
 
         let mut tx_helper = TxHelper::new(transaction);
-        for cfg in multisig_configs {
+        if let Some(code_hash) = repr.omnilock_code_hash {
+            tx_helper.set_omnilock_code_hash(code_hash);
+        }
+        for input_cell in repr.input_cells {
+            tx_helper.add_input_cell(
+                input_cell.out_point.into(),
+                input_cell.output.into(),
+                input_cell.data.into_bytes(),
+            );
+        }
+        for (cfg, multisig_script) in multisig_configs {
+            tx_helper.set_multisig_script(cfg.hash160(), multisig_script);
             tx_helper.add_multisig_config(cfg);
         }
         for (lock_arg, sub_signatures) in signatures {
@@ -784,6 +1543,9 @@ pub struct ReprMultisigConfig {
     pub sighash_addresses: Vec<String>,
     pub require_first_n: u8,
     pub threshold: u8,
+    /// The multisig script variant (legacy/v2) of the lock using this config
+    #[serde(default, skip_serializing_if = "MultisigScript::is_legacy")]
+    pub multisig_script: MultisigScript,
 }
 
 impl ReprMultisigConfig {
@@ -800,6 +1562,7 @@ impl ReprMultisigConfig {
             sighash_addresses,
             require_first_n: cfg.require_first_n(),
             threshold: cfg.threshold(),
+            multisig_script: MultisigScript::Legacy,
         }
     }
 }
@@ -36,6 +36,8 @@ use crate::utils::{
         PrivkeyPathParser, PrivkeyWrapper, PubkeyHexParser,
     },
     genesis_info::GenesisInfo,
+    multisig::MultisigScript,
+    omnilock::{is_omnilock_type_hash, OmnilockArgs},
     other::{address_json, get_address, get_network_type, read_password},
     rpc::{ChainInfo, HttpRpcClient},
 };
@@ -651,6 +653,30 @@ message = "0x"
                         "args": format!("0x{}", hex_string(address.payload().args().as_ref())),
                     },
                 });
+                let code_hash: H256 = address
+                    .payload()
+                    .code_hash(Some(address.network()))
+                    .unpack();
+                if let Some(multisig_script) =
+                    MultisigScript::from_lock(&packed::Script::from(address.payload()))
+                {
+                    resp["extra"]["multisig-script"] = serde_json::json!(multisig_script);
+                }
+                if is_omnilock_type_hash(&code_hash) {
+                    resp["extra"]["omnilock"] =
+                        match OmnilockArgs::from_slice(address.payload().args().as_ref()) {
+                            Ok(args) => serde_json::json!({
+                                "mode": args.mode(),
+                                "auth-content": format!("{:#x}", args.auth_content()),
+                                "acp": args.acp.map(|(ckb_minimum, udt_minimum)| serde_json::json!({
+                                    "ckb-minimum": ckb_minimum,
+                                    "udt-minimum": udt_minimum,
+                                })),
+                                "time-lock": args.time_lock.map(|since| format!("{:#x}", since)),
+                            }),
+                            Err(_) => serde_json::json!("unsupported"),
+                        };
+                }
                 let other_format = if address.is_new() {
                     "old-format(deprecated)"
                 } else {
@@ -1,18 +1,25 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+};
 
 use bitcoin::util::bip32::DerivationPath;
+use chrono::{TimeZone, Utc};
 use clap::{App, Arg, ArgMatches};
 use serde::{Deserialize, Serialize};
 
 use ckb_chain_spec::consensus::TYPE_ID_CODE_HASH;
-use ckb_hash::new_blake2b;
+use ckb_hash::{blake2b_256, new_blake2b};
 use ckb_jsonrpc_types as json_types;
 use ckb_sdk::{
-    constants::{DAO_TYPE_HASH, MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
+    constants::{DAO_TYPE_HASH, MIN_SECP_CELL_CAPACITY, MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
+    rpc::ckb_indexer::{Cell, Order, Pagination, ScriptType, SearchKey, SearchKeyFilter, Tx},
     traits::{
-        CellCollector, CellQueryOptions, DefaultCellCollector, DefaultHeaderDepResolver,
-        DefaultTransactionDependencyProvider, MaturityOption, PrimaryScriptType, Signer,
-        ValueRangeOption,
+        CellCollector, CellDepResolver, CellQueryOptions, DefaultCellCollector,
+        DefaultHeaderDepResolver, DefaultTransactionDependencyProvider, MaturityOption, Signer,
+        TransactionDependencyProvider,
     },
     tx_builder::{
         transfer::CapacityTransferBuilder, unlock_tx, BalanceTxCapacityError, CapacityBalancer,
@@ -20,45 +27,66 @@ use ckb_sdk::{
     },
     types::ScriptId,
     unlock::{
-        MultisigConfig, ScriptUnlocker, SecpMultisigScriptSigner, SecpMultisigUnlocker,
-        SecpSighashScriptSigner, SecpSighashUnlocker,
+        MultisigConfig, OmniLockScriptSigner, OmniLockUnlocker, OmniUnlockMode, ScriptUnlocker,
+        SecpMultisigScriptSigner, SecpMultisigUnlocker, SecpSighashScriptSigner,
+        SecpSighashUnlocker,
     },
     util::{get_max_mature_number, is_mature},
-    Address, AddressPayload, HumanCapacity, Since, SinceType, SECP256K1,
+    Address, AddressPayload, HumanCapacity, NetworkType, Since, SinceType, SECP256K1,
 };
 use ckb_types::{
     bytes::Bytes,
-    core::{Capacity, FeeRate, ScriptHashType, TransactionView},
-    packed::{CellOutput, Script, WitnessArgs},
+    core::{
+        Capacity, EpochNumberWithFraction, FeeRate, ScriptHashType, TransactionBuilder,
+        TransactionView,
+    },
+    packed::{self, CellDep, CellInput, CellOutput, Script, WitnessArgs},
     prelude::*,
     H160, H256,
 };
 use plugin_protocol::LiveCellInfo;
 
-use super::{CliSubCommand, Output};
+use super::{
+    sudt::{arg_cell_deps, get_script_id},
+    tx::ReprTxHelper,
+    CliSubCommand, Output,
+};
 use crate::plugin::PluginManager;
 use crate::utils::{
     arg,
     arg_parser::{
-        AddressParser, ArgParser, CapacityParser, FixedHashParser, FromStrParser,
-        PrivkeyPathParser, PrivkeyWrapper,
+        AddressParser, ArgParser, CapacityParser, CellDepsParser, FilePathParser, FixedHashParser,
+        FromStrParser, HexParser, PrivkeyPathParser, PrivkeyWrapper, ScriptParser, SinceParser,
     },
+    cell_dep::{CellDepName, CellDeps},
+    coin_selection::{CoinSelection, CoinSelectionCellCollector},
+    fee_rate::{get_fee_rate, FeeRateSpec, DEFAULT_MAX_FEE_RATE},
     genesis_info::GenesisInfo,
+    hd_scan::{is_address_used, scan_key_chain, HdScanCache, HdScanState, DEFAULT_GAP_LIMIT},
+    omnilock::{OmnilockArgs, OmnilockIdentity},
     other::{
-        check_capacity, get_address, get_arg_value, get_genesis_info, get_network_type,
-        get_to_data, map_tx_builder_error_2_str, read_password, to_live_cell_info,
+        check_capacity, get_address, get_arg_value, get_genesis_info, get_live_cell,
+        get_network_type, get_to_data, map_tx_builder_error_2_str, read_password,
+        to_live_cell_info, write_csv,
     },
-    rpc::HttpRpcClient,
+    rpc::{HttpRpcClient, Timestamp},
     signer::KeyStoreHandlerSigner,
+    tx_helper::{check_lock_script, TxHelper},
+    tx_lint::{known_cell_deps, lint_tx_online, DEFAULT_MAX_TX_FEE},
 };
 
 // Max derived change address to search
 const DERIVE_CHANGE_ADDRESS_MAX_LEN: u32 = 10000;
+// Keep consolidate transaction well below the block size limit (597000 bytes)
+const MAX_CONSOLIDATE_TX_SIZE: u64 = 500_000;
+// The page size of collecting live cells to sweep by indexer
+const SWEEP_CELLS_PAGE_SIZE: u32 = 1000;
 
 pub struct WalletSubCommand<'a> {
     plugin_mgr: &'a mut PluginManager,
     rpc_client: &'a mut HttpRpcClient,
     genesis_info: Option<GenesisInfo>,
+    ckb_cli_dir: &'a Path,
 }
 
 impl<'a> WalletSubCommand<'a> {
@@ -66,11 +94,13 @@ impl<'a> WalletSubCommand<'a> {
         rpc_client: &'a mut HttpRpcClient,
         plugin_mgr: &'a mut PluginManager,
         genesis_info: Option<GenesisInfo>,
+        ckb_cli_dir: &'a Path,
     ) -> WalletSubCommand<'a> {
         WalletSubCommand {
             rpc_client,
             plugin_mgr,
             genesis_info,
+            ckb_cli_dir,
         }
     }
 
@@ -80,6 +110,11 @@ impl<'a> WalletSubCommand<'a> {
     }
 
     pub fn subcommand() -> App<'static> {
+        let arg_lock_until = Arg::with_name("lock-until")
+            .long("lock-until")
+            .takes_value(true)
+            .validator(|input| SinceParser.validate(input))
+            .about("Absolute since value of the time-locked address, format: epoch:{number}[.{index}/{length}], timestamp:{unix seconds or RFC3339 datetime} or block:{number}");
         App::new("wallet")
             .about("Transfer / query balance (with local index) / key utils")
             .subcommands(vec![
@@ -92,13 +127,63 @@ impl<'a> WalletSubCommand<'a> {
                             .conflicts_with(arg::privkey_path().get_name()),
                     )
                     .arg(arg::from_locked_address())
-                    .arg(arg::to_address().required(true))
-                    .arg(arg::to_data())
-                    .arg(arg::to_data_path())
-                    .arg(arg::capacity().required(true))
+                    .arg(
+                        Arg::with_name("from-omnilock-address")
+                            .long("from-omnilock-address")
+                            .takes_value(true)
+                            .validator(|input| AddressParser::default().validate(input))
+                            .conflicts_with("from-locked-address")
+                            .requires("cell-deps")
+                            .about("Transfer from this omnilock address (secp256k1 or multisig identity, the identity must be the sender key or include the sender key), the change goes back to it unless <derive-change-address> is given"),
+                    )
+                    .arg(
+                        arg_cell_deps()
+                            .required(false)
+                            .about("The cell deps file contains the `omnilock` item, required by <from-omnilock-address>"),
+                    )
+                    .arg(
+                        Arg::with_name("sighash-address")
+                            .long("sighash-address")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| AddressParser::new_sighash().validate(input))
+                            .requires("from-omnilock-address")
+                            .about("The sighash addresses of the multisig config, required by the omnilock address with multisig identity"),
+                    )
+                    .arg(
+                        Arg::with_name("require-first-n")
+                            .long("require-first-n")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u8>::default().validate(input))
+                            .requires("sighash-address")
+                            .about("Require first n signatures of corresponding pubkey of the multisig config (default: 0)"),
+                    )
+                    .arg(
+                        Arg::with_name("threshold")
+                            .long("threshold")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u8>::default().validate(input))
+                            .requires("sighash-address")
+                            .about("The threshold of the multisig config (default: 1), <unsigned-out> is required when it is greater than 1"),
+                    )
+                    .arg(arg::to_address().required_unless("to-file"))
+                    .arg(arg::to_data().conflicts_with("to-file"))
+                    .arg(arg::to_data_path().conflicts_with("to-file"))
+                    .arg(arg::capacity().required_unless_one(&["to-file", "all"]))
+                    .arg(
+                        Arg::with_name("to-file")
+                            .long("to-file")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(true).validate(input))
+                            .conflicts_with_all(&["to-address", "capacity", "type-id", "all"])
+                            .about("Transfer to multiple addresses in one transaction, the file is a JSON array of {\"address\", \"capacity\", \"data\"} objects or CSV lines of `address,capacity[,data]` (data is optional hex string)"),
+                    )
                     .arg(arg::fee_rate())
+                    .arg(arg::max_fee_rate())
                     .arg(arg::max_tx_fee())
-                    .arg(arg::derive_receiving_address_length())
+                    .arg(arg::coin_selection())
+                    .arg(arg::skip_lint())
+                    .arg(arg::derive_receiving_address_length().about("Search derived receiving address length, with <all> the first N derived receiving and change addresses of <from-account> are also swept"))
                     .arg(
                         arg::derive_change_address().conflicts_with(arg::privkey_path().get_name()),
                     )
@@ -110,6 +195,168 @@ impl<'a> WalletSubCommand<'a> {
                         Arg::with_name("type-id")
                             .long("type-id")
                             .about("Add type id type script to target output cell"),
+                    )
+                    .arg(
+                        arg_lock_until
+                            .clone()
+                            .about("Transfer to the time-locked (multisig with since) address built from <to-address> (sighash or multisig address), it can only be unlocked after the since value, format: epoch:{number}[.{index}/{length}], timestamp:{unix seconds or RFC3339 datetime} or block:{number}"),
+                    )
+                    .arg(
+                        Arg::with_name("all")
+                            .long("all")
+                            .conflicts_with_all(&["capacity", "to-data", "to-data-path", "type-id", "max-tx-fee"])
+                            .about("Transfer all capacity of the sender (include derived addresses when <derive-change-address> or <derive-receiving-address-length> is given) to <to-address>, the transaction fee is subtracted from the transferred capacity and no change cell is created"),
+                    )
+                    .arg(
+                        Arg::with_name("unsigned-out")
+                            .long("unsigned-out")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .conflicts_with("derive-change-address")
+                            .about("Do not sign and send the balanced transaction, write it to this file instead (the format of `tx` subcommand), then sign it by `tx sign-inputs` / `tx add-signature` and send it by `tx send`"),
+                    ),
+                App::new("consolidate")
+                    .about("Merge small live cells (without type script and data) of an address into large cells, in chunked transactions")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(arg::to_address().about("Target address of merged cells (default: the sender address)"))
+                    .arg(arg::fee_rate())
+                    .arg(arg::max_fee_rate())
+                    .arg(
+                        Arg::with_name("max-inputs")
+                            .long("max-inputs")
+                            .takes_value(true)
+                            .default_value("500")
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("Max number of inputs in one consolidate transaction"),
+                    ),
+                App::new("unlock-timelocked")
+                    .about("Transfer all capacity of a time-locked (multisig with since) address to <to-address> once it is mature")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(
+                        arg::from_locked_address()
+                            .required_unless("lock-until")
+                            .conflicts_with("lock-until"),
+                    )
+                    .arg(arg_lock_until.about("Build the time-locked address from the sender (or the multisig config of <sighash-address>) and this since value, used when <from-locked-address> is not given"))
+                    .arg(
+                        Arg::with_name("sighash-address")
+                            .long("sighash-address")
+                            .takes_value(true)
+                            .multiple(true)
+                            .validator(|input| AddressParser::new_sighash().validate(input))
+                            .about("The sighash addresses of the multisig config of a multi-key time-locked address, must include the sender (default: the sender only)"),
+                    )
+                    .arg(
+                        Arg::with_name("require-first-n")
+                            .long("require-first-n")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u8>::default().validate(input))
+                            .requires("sighash-address")
+                            .about("Require first n signatures of corresponding pubkey of the multisig config (default: 0)"),
+                    )
+                    .arg(
+                        Arg::with_name("threshold")
+                            .long("threshold")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u8>::default().validate(input))
+                            .requires("sighash-address")
+                            .about("The threshold of the multisig config (default: 1), <unsigned-out> is required when it is greater than 1"),
+                    )
+                    .arg(arg::to_address().about("Target address (default: the sender address)"))
+                    .arg(arg::fee_rate())
+                    .arg(arg::max_fee_rate())
+                    .arg(
+                        Arg::with_name("unsigned-out")
+                            .long("unsigned-out")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Do not sign and send the unlock transaction, write it to this file instead (the format of `tx` subcommand), then collect the other signatures by `tx sign-inputs` / `tx add-signature` and send it by `tx send`"),
+                    ),
+                App::new("bump-fee")
+                    .about("Replace a pending transaction (all inputs are owned by the sender or its derived addresses) with a higher fee rate, by reducing the change output or adding inputs")
+                    .arg(arg::privkey_path().required_unless(arg::from_account().get_name()))
+                    .arg(
+                        arg::from_account()
+                            .required_unless(arg::privkey_path().get_name())
+                            .conflicts_with(arg::privkey_path().get_name()),
+                    )
+                    .arg(
+                        Arg::with_name("tx-hash")
+                            .long("tx-hash")
+                            .takes_value(true)
+                            .required(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .about("The hash of the pending transaction to replace"),
+                    )
+                    .arg(arg::fee_rate().about("The new transaction fee rate (unit: shannons/KB), or `auto[:target_blocks]` to pick the median fee rate of recent blocks (not higher than --max-fee-rate), must be greater than the fee rate of the pending transaction"))
+                    .arg(arg::max_fee_rate())
+                    .arg(
+                        Arg::with_name("change-index")
+                            .long("change-index")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<usize>::default().validate(input))
+                            .about("The index of the change output to pay the new fee (default: the only output locked by the sender without type script and data)"),
+                    )
+                    .arg(arg::derive_receiving_address_length().about("The derived receiving address length of <from-account> to search the owner of the inputs and the change output"))
+                    .arg(arg::derive_change_address_length().about("The derived change address length of <from-account> to search the owner of the inputs and the change output")),
+                App::new("hd-scan")
+                    .about("Scan the receiving and change addresses of a HD account until <gap-limit> consecutive unused addresses are found, the last used indexes are cached and used by `wallet transfer` to pick the next unused change address")
+                    .arg(arg::from_account().required(true))
+                    .arg(
+                        Arg::with_name("gap-limit")
+                            .long("gap-limit")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<u32>::default().validate(input))
+                            .about("The number of consecutive unused addresses to stop the scan (default: 20)"),
+                    )
+                    .arg(
+                        Arg::with_name("rescan")
+                            .long("rescan")
+                            .about("Ignore the cached result, scan from the first address"),
+                    ),
+                App::new("history")
+                    .about("List the transactions of an address or an account (include the derived addresses found by `wallet hd-scan`), by indexer")
+                    .arg(arg::address().conflicts_with("account"))
+                    .arg(arg::account())
+                    .arg(
+                        Arg::with_name("limit")
+                            .long("limit")
+                            .takes_value(true)
+                            .default_value("20")
+                            .validator(|input| FromStrParser::<u32>::default().validate(input))
+                            .about("Max number of transactions in one page"),
+                    )
+                    .arg(
+                        Arg::with_name("after")
+                            .long("after")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<HistoryCursor>::new().validate(input))
+                            .about("Get the page after this cursor (the <next_cursor> of previous page)"),
+                    )
+                    .arg(
+                        Arg::with_name("order")
+                            .long("order")
+                            .takes_value(true)
+                            .possible_values(&["desc", "asc"])
+                            .default_value("desc")
+                            .about("Order by block number"),
+                    )
+                    .arg(
+                        Arg::with_name("csv")
+                            .long("csv")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Also export the page to this CSV file"),
                     ),
                 App::new("get-capacity")
                     .about("Get capacity address or lock arg or pubkey")
@@ -122,9 +369,73 @@ impl<'a> WalletSubCommand<'a> {
                 App::new("get-live-cells")
                     .about("Get live cells by address")
                     .arg(arg::address())
+                    .arg(arg::lock_arg())
                     .arg(arg::live_cells_limit())
                     .arg(arg::from_block_number())
                     .arg(arg::to_block_number())
+                    .arg(arg::derive_receiving_address_length())
+                    .arg(arg::derive_change_address_length())
+                    .arg(arg::derived())
+                    .arg(
+                        Arg::with_name("type-script")
+                            .long("type-script")
+                            .takes_value(true)
+                            .validator(|input| ScriptParser.validate(input))
+                            .conflicts_with("no-type")
+                            .about("Only the cells with this type script, format: {code_hash}-{hash_type}[-{args}]"),
+                    )
+                    .arg(
+                        Arg::with_name("no-type")
+                            .long("no-type")
+                            .about("Only the cells without type script"),
+                    )
+                    .arg(
+                        Arg::with_name("with-data")
+                            .long("with-data")
+                            .conflicts_with("without-data")
+                            .about("Only the cells with non-empty data"),
+                    )
+                    .arg(
+                        Arg::with_name("without-data")
+                            .long("without-data")
+                            .conflicts_with("data-hash")
+                            .about("Only the cells with empty data"),
+                    )
+                    .arg(
+                        Arg::with_name("data-hash")
+                            .long("data-hash")
+                            .takes_value(true)
+                            .validator(|input| FixedHashParser::<H256>::default().validate(input))
+                            .about("Only the cells with data of this hash (blake2b-256)"),
+                    )
+                    .arg(
+                        Arg::with_name("min-capacity")
+                            .long("min-capacity")
+                            .takes_value(true)
+                            .validator(|input| CapacityParser.validate(input))
+                            .about("Only the cells with capacity >= this value (unit: CKB)"),
+                    )
+                    .arg(
+                        Arg::with_name("max-capacity")
+                            .long("max-capacity")
+                            .takes_value(true)
+                            .validator(|input| CapacityParser.validate(input))
+                            .about("Only the cells with capacity <= this value (unit: CKB)"),
+                    )
+                    .arg(
+                        Arg::with_name("after")
+                            .long("after")
+                            .takes_value(true)
+                            .validator(|input| FromStrParser::<LiveCellsCursor>::new().validate(input))
+                            .about("Get the page after this cursor (the <next_cursor> of previous page)"),
+                    )
+                    .arg(
+                        Arg::with_name("csv")
+                            .long("csv")
+                            .takes_value(true)
+                            .validator(|input| FilePathParser::new(false).validate(input))
+                            .about("Also export the page to this CSV file"),
+                    )
             ])
     }
 
@@ -142,11 +453,23 @@ impl<'a> WalletSubCommand<'a> {
             derive_change_address,
             capacity,
             fee_rate,
+            max_fee_rate,
             force_small_change_as_fee,
             to_address,
             to_data,
+            to_file,
             is_type_id,
             skip_check_to_address,
+            skip_lint,
+            transfer_all,
+            coin_selection,
+            lock_until,
+            unsigned_out,
+            from_omnilock_address,
+            cell_deps,
+            sighash_addresses,
+            require_first_n,
+            threshold,
         } = args;
 
         let network_type = get_network_type(self.rpc_client)?;
@@ -154,45 +477,112 @@ impl<'a> WalletSubCommand<'a> {
             .map(|input| PrivkeyPathParser.parse(&input))
             .transpose()?;
         let from_account: Option<H160> = from_account
+            .map(|input| parse_from_account(&input, network_type))
+            .transpose()?;
+        let from_locked_address: Option<Address> = from_locked_address
             .map(|input| {
-                FixedHashParser::<H160>::default()
+                AddressParser::new_multisig()
+                    .set_network(network_type)
                     .parse(&input)
-                    .or_else(|err| {
-                        let result: Result<Address, String> = AddressParser::new_sighash()
-                            .set_network(network_type)
-                            .parse(&input);
-                        result
-                            .map(|address| H160::from_slice(&address.payload().args()).unwrap())
-                            .map_err(|_| err)
-                    })
             })
             .transpose()?;
-        let from_locked_address: Option<Address> = from_locked_address
+        let from_omnilock_address: Option<Address> = from_omnilock_address
             .map(|input| {
-                AddressParser::new_multisig()
+                AddressParser::default()
                     .set_network(network_type)
                     .parse(&input)
             })
             .transpose()?;
-        let to_capacity: u64 = CapacityParser.parse(&capacity)?.into();
-        let fee_rate: u64 = FromStrParser::<u64>::default().parse(&fee_rate)?;
+        let cell_deps: Option<CellDeps> = cell_deps
+            .map(|input| CellDepsParser.parse(&input))
+            .transpose()?;
+        let omnilock_multisig_config = if sighash_addresses.is_empty() {
+            None
+        } else {
+            let sighash_addresses = sighash_addresses
+                .iter()
+                .map(|input| {
+                    AddressParser::new_sighash()
+                        .set_network(network_type)
+                        .parse(input)
+                        .map(|address| H160::from_slice(address.payload().args().as_ref()).unwrap())
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let require_first_n: u8 = require_first_n
+                .map(|input| FromStrParser::<u8>::default().parse(&input))
+                .transpose()?
+                .unwrap_or(0);
+            let threshold: u8 = threshold
+                .map(|input| FromStrParser::<u8>::default().parse(&input))
+                .transpose()?
+                .unwrap_or(1);
+            Some(
+                MultisigConfig::new_with(sighash_addresses, require_first_n, threshold)
+                    .map_err(|err| err.to_string())?,
+            )
+        };
+        let max_fee_rate: u64 = max_fee_rate
+            .map(|input| FromStrParser::<u64>::default().parse(&input))
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_FEE_RATE);
+        let fee_rate: u64 = FromStrParser::<FeeRateSpec>::new()
+            .parse(&fee_rate)?
+            .resolve(self.rpc_client, max_fee_rate)?;
         let force_small_change_as_fee: Option<u64> =
             force_small_change_as_fee.map(|s| CapacityParser.parse(&s).unwrap().into());
-        let receiving_address_length: u32 = derive_receiving_address_length
+        let lock_until: Option<Since> = lock_until
+            .map(|input| SinceParser.parse(&input))
+            .transpose()?;
+        let coin_selection: CoinSelection = coin_selection
+            .map(|input| FromStrParser::<CoinSelection>::default().parse(&input))
+            .transpose()?
+            .unwrap_or_default();
+        let receiving_address_length_given = derive_receiving_address_length.is_some();
+        let mut receiving_address_length: u32 = derive_receiving_address_length
             .map(|input| FromStrParser::<u32>::default().parse(&input))
             .transpose()?
             .unwrap_or(1000);
-        let last_change_address_opt: Option<Address> = derive_change_address
+        let mut last_change_address_opt: Option<Address> = derive_change_address
             .map(|input| {
                 AddressParser::default()
                     .set_network(network_type)
                     .parse(&input)
             })
             .transpose()?;
-        let to_address: Address = AddressParser::default()
-            .set_network(network_type)
-            .parse(&to_address)?;
-        let to_data = to_data.unwrap_or_default();
+        let mut targets: Vec<(Address, u64, Bytes)> = match (to_file, to_address, capacity) {
+            (Some(path), None, None) => {
+                let content = fs::read_to_string(&path)
+                    .map_err(|err| format!("Read to-file {} failed: {}", path, err))?;
+                parse_transfer_targets(&content)?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, target)| {
+                        target
+                            .resolve(network_type)
+                            .map_err(|err| format!("Invalid target(no.{}): {}", idx + 1, err))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?
+            }
+            (None, Some(to_address), Some(capacity)) if !transfer_all => {
+                let to_address: Address = AddressParser::default()
+                    .set_network(network_type)
+                    .parse(&to_address)?;
+                let to_capacity: u64 = CapacityParser.parse(&capacity)?.into();
+                vec![(to_address, to_capacity, to_data.unwrap_or_default())]
+            }
+            (None, Some(to_address), None) if transfer_all => {
+                let to_address: Address = AddressParser::default()
+                    .set_network(network_type)
+                    .parse(&to_address)?;
+                // The capacity will be filled after all live cells are collected
+                vec![(to_address, MIN_SECP_CELL_CAPACITY, Bytes::new())]
+            }
+            _ => {
+                return Err(String::from(
+                    "Either <to-file>, <to-address> with <capacity> or <to-address> with <all> is required",
+                ))
+            }
+        };
 
         let (from_address_payload, password) = if let Some(from_privkey) = from_privkey.as_ref() {
             let from_pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, from_privkey);
@@ -200,56 +590,58 @@ impl<'a> WalletSubCommand<'a> {
         } else {
             let password = if let Some(password) = password {
                 Some(password)
-            } else if self.plugin_mgr.keystore_require_password() {
+            } else if unsigned_out.is_none() && self.plugin_mgr.keystore_require_password() {
                 Some(read_password(false, None)?)
             } else {
                 None
             };
             (
-                AddressPayload::from_pubkey_hash(from_account.unwrap()),
+                AddressPayload::from_pubkey_hash(from_account.clone().unwrap()),
                 password,
             )
         };
         let from_address = Address::new(network_type, from_address_payload.clone(), false);
 
-        if let Some(from_locked_address) = from_locked_address.as_ref() {
-            let args = from_locked_address.payload().args();
-            let err_prefix = "Invalid from-locked-address's args";
-            if args.len() != 28 {
-                return Err(format!("{}: invalid {}", err_prefix, args.len()));
-            }
-            let mut since_bytes = [0u8; 8];
-            since_bytes.copy_from_slice(&args[20..]);
-            let since = Since::from_raw_value(u64::from_le_bytes(since_bytes));
-            if !since.flags_is_valid() {
-                return Err(format!("{}: invalid since flags", err_prefix));
+        // Pick the next unused change address by the result of `wallet hd-scan`
+        let mut hd_scan_state = None;
+        if let (None, Some(lock_arg), None, None) = (
+            last_change_address_opt.as_ref(),
+            from_account.as_ref(),
+            unsigned_out.as_ref(),
+            from_omnilock_address.as_ref(),
+        ) {
+            if let Some(state) = HdScanCache::load(self.ckb_cli_dir)?.get(network_type, lock_arg) {
+                let change_index = state.next_change_index();
+                let (_, change_lock_arg) =
+                    self.derived_lock_arg(lock_arg.clone(), true, change_index, password.clone())?;
+                if !receiving_address_length_given {
+                    receiving_address_length =
+                        receiving_address_length.max(state.next_receiving_index());
+                }
+                last_change_address_opt = Some(Address::new(
+                    network_type,
+                    AddressPayload::from_pubkey_hash(change_lock_arg),
+                    false,
+                ));
+                hd_scan_state = Some((state, change_index));
             }
-            if !since.is_absolute() {
-                return Err(format!("{}: only support absolute since value", err_prefix));
+        }
+
+        if let Some(from_locked_address) = from_locked_address.as_ref() {
+            timelocked_address_since(from_locked_address)
+                .map_err(|err| format!("Invalid from-locked-address's args: {}", err))?;
+        }
+        if let Some(since) = lock_until {
+            for (to_address, _, _) in targets.iter_mut() {
+                *to_address = build_timelocked_address(to_address, since)?;
             }
-            if since.extract_metric().map(|(ty, _)| ty) != Some(SinceType::EpochNumberWithFraction)
-            {
-                return Err(format!("{}: only support epoch since value", err_prefix));
-            }
-        }
-
-        let to_address_hash_type = to_address.payload().hash_type();
-        let to_address_code_hash: H256 = to_address
-            .payload()
-            .code_hash(Some(to_address.network()))
-            .unpack();
-        let to_address_args_len = to_address.payload().args().len();
-        if !(skip_check_to_address
-            || (to_address_hash_type == ScriptHashType::Type
-                && to_address_code_hash == SIGHASH_TYPE_HASH
-                && to_address_args_len == 20)
-            || (to_address_hash_type == ScriptHashType::Type
-                && to_address_code_hash == MULTISIG_TYPE_HASH
-                && (to_address_args_len == 20 || to_address_args_len == 28)))
-        {
-            return Err(format!("Invalid to-address: {}\n[Hint]: Add `--skip-check-to-address` flag to transfer to any address", to_address));
         }
-        check_capacity(to_capacity, to_data.len())?;
+
+        for (to_address, to_capacity, to_data) in &targets {
+            check_lock_script(&Script::from(to_address.payload()), skip_check_to_address)
+                .map_err(|err| format!("Invalid to-address: {}, {}\n[Hint]: Add `--skip-check-to-address` flag to transfer to any address", to_address, err))?;
+            check_capacity(*to_capacity, to_data.len())?;
+        }
 
         let genesis_info = self.genesis_info()?;
 
@@ -304,6 +696,35 @@ impl<'a> WalletSubCommand<'a> {
                     self.plugin_mgr.root_key_path(from_lock_arg.clone())?,
                 )
             };
+        // Also sweep the first N derived receiving and change addresses of the account
+        let sweep_key_set_length = if transfer_all
+            && receiving_address_length_given
+            && from_account.is_some()
+            && last_change_address_opt.is_none()
+        {
+            Some(receiving_address_length)
+        } else {
+            None
+        };
+        if let Some(length) = sweep_key_set_length {
+            let key_set = self
+                .plugin_mgr
+                .keystore_handler()
+                .derived_key_set_by_index(from_lock_arg.clone(), 0, length, 0, length, None)?;
+            for (path, hash160) in key_set
+                .external
+                .into_iter()
+                .chain(key_set.change.into_iter())
+            {
+                path_map.insert(hash160.clone(), path);
+                let payload = AddressPayload::from_pubkey_hash(hash160);
+                lock_scripts.push((
+                    Script::from(&payload),
+                    sighash_placeholder_witness.clone(),
+                    Default::default(),
+                ));
+            }
+        }
 
         let get_signer = || -> Result<Box<dyn Signer>, String> {
             if let Some(privkey) = from_privkey.as_ref() {
@@ -328,6 +749,8 @@ impl<'a> WalletSubCommand<'a> {
                         change_last,
                         DERIVE_CHANGE_ADDRESS_MAX_LEN,
                     )?;
+                } else if let Some(length) = sweep_key_set_length {
+                    signer.cache_key_set_by_index(from_lock_arg.clone(), length, length)?;
                 }
                 signer.set_change_path(from_lock_arg.clone(), change_path.to_string());
                 Ok(Box::new(signer))
@@ -341,6 +764,7 @@ impl<'a> WalletSubCommand<'a> {
         };
         unlockers.insert(sighash_script_id, Box::new(sighash_unlocker));
 
+        let mut locked_multisig_config = None;
         if let Some(from_locked_address) = from_locked_address.as_ref() {
             let mut found_lock_arg = false;
             for lock_arg in std::iter::once(&from_lock_arg).chain(path_map.keys()) {
@@ -361,9 +785,13 @@ impl<'a> WalletSubCommand<'a> {
                     let multisig_script_id = ScriptId::new_type(MULTISIG_TYPE_HASH.clone());
                     let multisig_unlocker = {
                         let signer = get_signer()?;
-                        SecpMultisigUnlocker::new(SecpMultisigScriptSigner::new(signer, config))
+                        SecpMultisigUnlocker::new(SecpMultisigScriptSigner::new(
+                            signer,
+                            config.clone(),
+                        ))
                     };
                     unlockers.insert(multisig_script_id, Box::new(multisig_unlocker));
+                    locked_multisig_config = Some(config);
                     break;
                 }
             }
@@ -374,14 +802,81 @@ impl<'a> WalletSubCommand<'a> {
             }
         }
 
+        let mut cell_dep_resolver = genesis_info.cell_dep_resolver.clone();
+        let mut change_lock_script = Script::from(&change_address_payload);
+        if let Some(from_omnilock_address) = from_omnilock_address.as_ref() {
+            let cell_deps = cell_deps.as_ref().ok_or_else(|| {
+                String::from("<cell-deps> is required by <from-omnilock-address>")
+            })?;
+            let omnilock_script_id = get_script_id(cell_deps, CellDepName::Omnilock)?;
+            let lock_script = Script::from(from_omnilock_address.payload());
+            if ScriptId::from(&lock_script) != omnilock_script_id {
+                return Err(format!(
+                    "from-omnilock-address's code_hash/hash_type not match the omnilock item in cell deps: {:#x}",
+                    omnilock_script_id.code_hash
+                ));
+            }
+            let omnilock_args = OmnilockArgs::from_slice(&lock_script.args().raw_data())
+                .map_err(|err| format!("Invalid from-omnilock-address's args: {}", err))?;
+            let config = omnilock_args.to_config(omnilock_multisig_config.as_ref())?;
+            let is_owned =
+                |lock_arg: &H160| &from_lock_arg == lock_arg || path_map.contains_key(lock_arg);
+            let owned = match omnilock_args.identity {
+                OmnilockIdentity::PubkeyHash(ref hash160) => is_owned(hash160),
+                OmnilockIdentity::Multisig(_) => omnilock_multisig_config
+                    .as_ref()
+                    .map(|config| config.sighash_addresses().iter().any(is_owned))
+                    .unwrap_or(false),
+            };
+            if !owned {
+                return Err(String::from(
+                    "from-omnilock-address's identity is not the key or derived keys",
+                ));
+            }
+            if let Some(multisig_config) = omnilock_multisig_config.as_ref() {
+                if multisig_config.threshold() > 1 && unsigned_out.is_none() {
+                    return Err(String::from(
+                        "<unsigned-out> is required when the multisig threshold is greater than 1",
+                    ));
+                }
+            }
+            cell_deps.apply_to_resolver(&mut cell_dep_resolver)?;
+            lock_scripts.insert(
+                0,
+                (
+                    lock_script.clone(),
+                    config
+                        .placeholder_witness(OmniUnlockMode::Normal)
+                        .map_err(|err| err.to_string())?,
+                    config.get_since_source(),
+                ),
+            );
+            let omnilock_unlocker = {
+                let signer = get_signer()?;
+                OmniLockUnlocker::new(
+                    OmniLockScriptSigner::new(signer, config.clone(), OmniUnlockMode::Normal),
+                    config,
+                )
+            };
+            unlockers.insert(omnilock_script_id, Box::new(omnilock_unlocker));
+            if last_change_address_opt.is_none() {
+                change_lock_script = lock_script;
+            }
+        }
+
         let balancer = CapacityBalancer {
             fee_rate: FeeRate::from_u64(fee_rate),
-            change_lock_script: Some(Script::from(&change_address_payload)),
+            change_lock_script: Some(change_lock_script),
             capacity_provider: CapacityProvider::new(lock_scripts),
             force_small_change_as_fee,
         };
         let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        let mut cell_collector = CoinSelectionCellCollector::new(
+            DefaultCellCollector::new(self.rpc_client.url()),
+            self.rpc_client.url(),
+            coin_selection,
+        );
+        cell_collector.set_tolerance(force_small_change_as_fee.unwrap_or(0));
         let header_dep_resolver = DefaultHeaderDepResolver::new(self.rpc_client.url());
 
         // Add outputs
@@ -396,16 +891,37 @@ impl<'a> WalletSubCommand<'a> {
         } else {
             None
         };
-        let to_output = CellOutput::new_builder()
-            .capacity(Capacity::shannons(to_capacity).pack())
-            .lock(to_address.payload().into())
-            .type_(placeholder_type_script.pack())
-            .build();
-        let builder = CapacityTransferBuilder::new(vec![(to_output, to_data)]);
-        let mut tx = builder
+        let single_to_capacity = if targets.len() == 1 {
+            Some(targets[0].1)
+        } else {
+            None
+        };
+        let outputs = targets
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (to_address, to_capacity, to_data))| {
+                let type_script = if idx == 0 {
+                    placeholder_type_script.clone()
+                } else {
+                    None
+                };
+                let output = CellOutput::new_builder()
+                    .capacity(Capacity::shannons(to_capacity).pack())
+                    .lock(to_address.payload().into())
+                    .type_(type_script.pack())
+                    .build();
+                (output, to_data)
+            })
+            .collect::<Vec<_>>();
+        let mut tx = if transfer_all {
+            let (output, _) = outputs.into_iter().next().expect("sweep target");
+            let sweep_cells = self.collect_sweep_cells(&balancer.capacity_provider.lock_scripts)?;
+            build_sweep_tx(&sweep_cells, output.lock(), &cell_dep_resolver, fee_rate)?.0
+        } else {
+            CapacityTransferBuilder::new(outputs)
             .build_balanced(
                 &mut cell_collector,
-                &genesis_info.cell_dep_resolver,
+                &cell_dep_resolver,
                 &header_dep_resolver,
                 &tx_dep_provider,
                 &balancer,
@@ -419,7 +935,7 @@ impl<'a> WalletSubCommand<'a> {
                         let prefix = "can not create change cell, left capacity=";
                         if msg.contains(prefix) {
                             let left_capacity = HumanCapacity::from_str(&msg[prefix.len()..]);
-                            if let Ok(left_capacity) = left_capacity {
+                            if let (Ok(left_capacity), Some(to_capacity)) = (left_capacity, single_to_capacity) {
                                 let suggest_capacity = HumanCapacity(left_capacity.0 + to_capacity);
 
                                 return format!("{}, try to transfer {} or try parameter `--max-tx-fee` to make small left capacity as transaction fee", err, suggest_capacity);
@@ -428,7 +944,8 @@ impl<'a> WalletSubCommand<'a> {
                     }
                 }
                 map_tx_builder_error_2_str(balancer.force_small_change_as_fee.is_none(), err)
-            })?;
+            })?
+        };
         if is_type_id {
             let mut blake2b = new_blake2b();
             let first_cell_input = tx.inputs().into_iter().next().expect("inputs empty");
@@ -450,10 +967,36 @@ impl<'a> WalletSubCommand<'a> {
                 .build();
             tx = tx.as_advanced_builder().set_outputs(outputs).build();
         }
+        if let Some(path) = unsigned_out {
+            let mut helper = TxHelper::new(tx.clone());
+            helper.snapshot_inputs(|out_point| get_live_cell(self.rpc_client, out_point, true))?;
+            if let Some(config) = locked_multisig_config {
+                helper.add_multisig_config(config);
+            }
+            if let Some(config) = omnilock_multisig_config {
+                helper.add_multisig_config(config);
+            }
+            let repr = ReprTxHelper::new(helper, network_type);
+            let content = serde_json::to_string_pretty(&repr).map_err(|err| err.to_string())?;
+            fs::write(&path, content)
+                .map_err(|err| format!("Write unsigned transaction to {} failed: {}", path, err))?;
+            return Ok(tx);
+        }
         let (tx, still_locked_groups) =
             unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
         assert!(still_locked_groups.is_empty());
 
+        if !skip_check && !skip_lint {
+            lint_tx_online(
+                self.rpc_client,
+                &tx,
+                network_type,
+                known_cell_deps(&genesis_info, cell_deps.as_ref()),
+                Some(force_small_change_as_fee.unwrap_or(DEFAULT_MAX_TX_FEE)),
+            )?
+            .check_before_send()?;
+        }
+
         let outputs_validator = if is_type_id || skip_check || skip_check_to_address {
             Some(json_types::OutputsValidator::Passthrough)
         } else {
@@ -465,77 +1008,853 @@ impl<'a> WalletSubCommand<'a> {
             .send_transaction(tx.data(), outputs_validator)
             .map_err(|err| format!("Send transaction error: {}", err))?;
         assert_eq!(tx.hash(), tx_hash.pack());
+        if let Some((mut state, change_index)) = hd_scan_state {
+            let change_lock_script = Script::from(&change_address_payload);
+            if tx
+                .outputs()
+                .into_iter()
+                .any(|output| output.lock() == change_lock_script)
+            {
+                state.use_change_index(change_index);
+                let mut cache = HdScanCache::load(self.ckb_cli_dir)?;
+                cache.set(network_type, &from_lock_arg, state);
+                cache.save(self.ckb_cli_dir)?;
+            }
+        }
         Ok(tx)
     }
 
-    pub fn get_capacity(&mut self, lock_scripts: Vec<Script>) -> Result<(u64, u64, u64), String> {
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
-        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
-        let mut total_all = 0;
-        let mut total_immature = 0;
-        let mut total_dao = 0;
-        for script in lock_scripts {
-            let mut query = CellQueryOptions::new_lock(script);
-            query.maturity = MaturityOption::Both;
-            query.min_total_capacity = u64::max_value();
-            let (cells, total_capacity) = cell_collector
-                .collect_live_cells(&query, false)
+    #[allow(clippy::too_many_arguments)]
+    pub fn bump_fee(
+        &mut self,
+        from_privkey: Option<PrivkeyWrapper>,
+        from_account: Option<H160>,
+        tx_hash: H256,
+        fee_rate: u64,
+        receiving_address_length: u32,
+        change_address_length: u32,
+        change_index: Option<usize>,
+    ) -> Result<BumpFeeResult, String> {
+        let (from_lock_arg, password) =
+            self.sender_lock_arg(from_privkey.as_ref(), from_account)?;
+        let from_lock = Script::from(&AddressPayload::from_pubkey_hash(from_lock_arg.clone()));
+
+        let tx_with_status = self
+            .rpc_client
+            .get_transaction(tx_hash.clone())?
+            .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?;
+        if tx_with_status.tx_status.status != json_types::Status::Pending {
+            return Err(format!(
+                "Only pending transaction can be replaced, the status of transaction {:#x} is: {:?}",
+                tx_hash, tx_with_status.tx_status.status
+            ));
+        }
+        let old_tx = tx_with_status
+            .transaction
+            .map(|tx| packed::Transaction::from(tx.inner).into_view())
+            .ok_or_else(|| format!("Transaction not found: {:#x}", tx_hash))?;
+
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let mut input_locks = Vec::new();
+        let mut input_total: u64 = 0;
+        for input in old_tx.inputs().into_iter() {
+            let cell = tx_dep_provider
+                .get_cell(&input.previous_output())
                 .map_err(|err| err.to_string())?;
-            total_all += total_capacity;
-            for cell in &cells {
-                let capacity: u64 = cell.output.capacity().unpack();
-                if !is_mature(cell, max_mature_number) {
-                    total_immature += capacity;
-                }
-                if cell
-                    .output
-                    .type_()
-                    .to_opt()
-                    .map(|script| script.code_hash().as_slice() == DAO_TYPE_HASH.as_bytes())
-                    .unwrap_or(false)
-                {
-                    total_dao += capacity;
-                }
+            let capacity: u64 = cell.capacity().unpack();
+            input_total += capacity;
+            input_locks.push(cell.lock());
+        }
+
+        // The lock scripts of the sender: the root key, and the derived keys of a keystore
+        // account (the change may go to a derived change address even if all inputs are
+        // locked by the root key)
+        let mut owned_locks: HashSet<Script> = HashSet::default();
+        owned_locks.insert(from_lock.clone());
+        let mut key_set_lengths = None;
+        if from_privkey.is_none() {
+            let payload = AddressPayload::from_pubkey_hash(from_lock_arg.clone());
+            owned_locks.extend(self.derived_lock_scripts(
+                &payload,
+                receiving_address_length,
+                change_address_length,
+            )?);
+            key_set_lengths = Some((receiving_address_length, change_address_length));
+        }
+        if let Some(idx) = input_locks
+            .iter()
+            .position(|lock| !owned_locks.contains(lock))
+        {
+            return Err(format!(
+                "Input #{} is not owned by the sender, only transaction with all inputs owned by the sender (include the derived addresses) can be replaced",
+                idx
+            ));
+        }
+
+        let output_total: u64 = old_tx
+            .outputs_capacity()
+            .map_err(|err| err.to_string())?
+            .as_u64();
+        let old_fee = input_total
+            .checked_sub(output_total)
+            .ok_or_else(|| String::from("Invalid transaction, outputs capacity overflow"))?;
+        let old_size = old_tx.data().as_reader().serialized_size_in_block() as u64;
+        let old_fee_rate = old_fee * 1000 / old_size;
+        if fee_rate <= old_fee_rate {
+            return Err(format!(
+                "The new fee rate ({} shannons/KB) must be greater than the fee rate of the pending transaction ({} shannons/KB)",
+                fee_rate, old_fee_rate
+            ));
+        }
+        let min_rbf_rate = self.rpc_client.min_rbf_rate()?;
+
+        let change_idx = find_change_output(&old_tx, &owned_locks, change_index)?;
+        let replacement =
+            ReplacementTx::new(&old_tx, input_locks, input_total, change_idx, from_lock)?;
+        let (tx, new_fee) = match replacement.build(&[], fee_rate)? {
+            Some(result) => result,
+            None => {
+                // Extra inputs are collected from the lock scripts of the replaced inputs
+                let placeholder_witness = WitnessArgs::new_builder()
+                    .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                    .build();
+                let lock_scripts = replacement
+                    .input_locks
+                    .iter()
+                    .map(|lock| {
+                        (
+                            lock.clone(),
+                            placeholder_witness.clone(),
+                            SinceSource::default(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let old_inputs = old_tx.input_pts_iter().collect::<HashSet<_>>();
+                let candidates = self
+                    .collect_sweep_cells(&lock_scripts)?
+                    .into_iter()
+                    .map(|sweep_cell| sweep_cell.cell)
+                    .filter(|cell| !old_inputs.contains(&cell.out_point))
+                    .collect::<Vec<_>>();
+                replacement
+                    .build_with_candidates(candidates, fee_rate)?
+                    .ok_or_else(|| {
+                        format!(
+                            "Capacity not enough to pay the fee of fee rate {} shannons/KB",
+                            fee_rate
+                        )
+                    })?
             }
+        };
+        // The placeholder witnesses have the same size as the signed ones
+        let new_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        if let Some(min_rbf_rate) = min_rbf_rate {
+            check_rbf_fee(old_fee, new_fee, new_size, min_rbf_rate)?;
         }
-        Ok((total_all, total_immature, total_dao))
+
+        let signer = self.simple_signer(
+            from_privkey.as_ref(),
+            &from_lock_arg,
+            password,
+            key_set_lengths,
+        )?;
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(SecpSighashUnlocker::new(SecpSighashScriptSigner::new(
+                signer,
+            ))),
+        );
+        let (tx, still_locked_groups) =
+            unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
+        if !still_locked_groups.is_empty() {
+            return Err(String::from("Can not sign the replacement transaction"));
+        }
+        self.rpc_client
+            .send_transaction(tx.data(), None)
+            .map_err(|err| format!("Send replacement transaction error: {}", err))?;
+        Ok(BumpFeeResult {
+            old_tx_hash: tx_hash,
+            old_fee,
+            old_fee_rate,
+            new_fee,
+            new_fee_rate: new_fee * 1000 / new_size,
+            tx,
+        })
     }
 
-    pub fn get_live_cells(
+    /// Get the sender lock arg (and the keystore password if required)
+    fn sender_lock_arg(
         &mut self,
-        script: Script,
-        script_type: PrimaryScriptType,
-        from_number: u64,
-        to_number: u64,
-        limit: u32,
-    ) -> Result<Vec<LiveCell>, String> {
-        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        from_privkey: Option<&PrivkeyWrapper>,
+        from_account: Option<H160>,
+    ) -> Result<(H160, Option<String>), String> {
+        if let Some(privkey) = from_privkey {
+            let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, privkey);
+            let payload = AddressPayload::from_pubkey(&pubkey);
+            Ok((H160::from_slice(payload.args().as_ref()).unwrap(), None))
+        } else {
+            let password = if self.plugin_mgr.keystore_require_password() {
+                Some(read_password(false, None)?)
+            } else {
+                None
+            };
+            let from_account = from_account
+                .ok_or_else(|| String::from("<privkey-path> or <from-account> is required!"))?;
+            Ok((from_account, password))
+        }
+    }
 
-        let mut query = CellQueryOptions::new(script, script_type);
-        query.maturity = MaturityOption::Both;
-        query.min_total_capacity = u64::max_value();
-        query.limit = Some(limit);
-        query.block_range = Some(ValueRangeOption::new(from_number, to_number));
-        let (cells, _total_capacity) = cell_collector
-            .collect_live_cells(&query, false)
-            .map_err(|err| err.to_string())?;
+    /// Build signer of a private key or a keystore account (and its derived keys of given
+    /// receiving/change address lengths)
+    fn simple_signer(
+        &mut self,
+        from_privkey: Option<&PrivkeyWrapper>,
+        from_lock_arg: &H160,
+        password: Option<String>,
+        key_set_lengths: Option<(u32, u32)>,
+    ) -> Result<Box<dyn Signer>, String> {
+        if let Some(privkey) = from_privkey {
+            Ok(Box::new(privkey.clone()))
+        } else {
+            let change_path = self.plugin_mgr.root_key_path(from_lock_arg.clone())?;
+            let mut signer = KeyStoreHandlerSigner::new(
+                self.plugin_mgr.keystore_handler(),
+                Box::new(DefaultTransactionDependencyProvider::new(
+                    self.rpc_client.url(),
+                    0,
+                )),
+            );
+            if let Some(password) = password {
+                signer.set_password(from_lock_arg.clone(), password);
+            }
+            if let Some((receiving_length, change_length)) = key_set_lengths {
+                signer.cache_key_set_by_index(
+                    from_lock_arg.clone(),
+                    receiving_length,
+                    change_length,
+                )?;
+            }
+            signer.set_change_path(from_lock_arg.clone(), change_path.to_string());
+            Ok(Box::new(signer))
+        }
+    }
 
-        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
-        let live_cells = cells
-            .into_iter()
-            .map(|cell| {
-                let mature = is_mature(&cell, max_mature_number);
-                LiveCell {
-                    info: to_live_cell_info(&cell),
-                    mature,
-                }
-            })
-            .collect::<Vec<_>>();
-        Ok(live_cells)
+    /// Collect the sweep cells of the lock scripts by indexer from `cursor`, see
+    /// `collect_sweep_cells_page`
+    fn sweep_cells_page(
+        &mut self,
+        lock_scripts: &[(Script, WitnessArgs, SinceSource)],
+        max_mature_number: u64,
+        limit: u32,
+        cursor: LiveCellsCursor,
+    ) -> Result<(Vec<SweepCell>, Option<LiveCellsCursor>), String> {
+        collect_sweep_cells_page(
+            lock_scripts,
+            &mut |search_key, limit, after| {
+                self.rpc_client
+                    .get_cells(search_key, Order::Asc, limit, after)
+            },
+            max_mature_number,
+            limit,
+            cursor,
+        )
     }
-}
 
-impl<'a> CliSubCommand for WalletSubCommand<'a> {
+    /// Collect all sweep cells of the lock scripts by indexer, see `collect_sweep_cells`
+    fn collect_sweep_cells(
+        &mut self,
+        lock_scripts: &[(Script, WitnessArgs, SinceSource)],
+    ) -> Result<Vec<SweepCell>, String> {
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        collect_sweep_cells(
+            lock_scripts,
+            &mut |search_key, limit, after| {
+                self.rpc_client
+                    .get_cells(search_key, Order::Asc, limit, after)
+            },
+            max_mature_number,
+            SWEEP_CELLS_PAGE_SIZE,
+        )
+    }
+
+    /// Merge the live cells of the sender, the sent transactions are pushed to `rounds` (kept
+    /// when an error occurs part-way)
+    pub fn consolidate(
+        &mut self,
+        from_privkey: Option<PrivkeyWrapper>,
+        from_account: Option<H160>,
+        to_address: Option<Address>,
+        fee_rate: u64,
+        max_inputs: usize,
+        rounds: &mut Vec<ConsolidateRound>,
+    ) -> Result<(), String> {
+        let (from_lock_arg, password) =
+            self.sender_lock_arg(from_privkey.as_ref(), from_account)?;
+        let from_lock = Script::from(&AddressPayload::from_pubkey_hash(from_lock_arg.clone()));
+        let to_lock = to_address
+            .map(|address| Script::from(address.payload()))
+            .unwrap_or_else(|| from_lock.clone());
+        check_lock_script(&to_lock, false).map_err(|err| format!("Invalid to-address: {}", err))?;
+
+        let genesis_info = self.genesis_info()?;
+        let signer = self.simple_signer(from_privkey.as_ref(), &from_lock_arg, password, None)?;
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH.clone()),
+            Box::new(SecpSighashUnlocker::new(SecpSighashScriptSigner::new(
+                signer,
+            ))),
+        );
+        let sighash_placeholder_witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+            .build();
+        let lock_scripts = vec![(
+            from_lock,
+            sighash_placeholder_witness,
+            SinceSource::default(),
+        )];
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        // The cells are loaded page by page, at most `max_inputs` cells are pending
+        let mut pending_cells: Vec<SweepCell> = Vec::new();
+        let mut cursor = Some(LiveCellsCursor::default());
+        loop {
+            while pending_cells.len() < max_inputs {
+                let current = match cursor.take() {
+                    Some(current) => current,
+                    None => break,
+                };
+                let (cells, next) = self.sweep_cells_page(
+                    &lock_scripts,
+                    max_mature_number,
+                    (max_inputs - pending_cells.len()) as u32,
+                    current,
+                )?;
+                pending_cells.extend(cells);
+                cursor = next;
+            }
+            // A single left cell is not worth a transaction
+            if pending_cells.len() < 2 {
+                break;
+            }
+            let (tx, fee, chunk_len) = build_consolidate_tx(
+                &pending_cells,
+                max_inputs,
+                to_lock.clone(),
+                &genesis_info.cell_dep_resolver,
+                fee_rate,
+            )?;
+            let (tx, still_locked_groups) =
+                unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
+            if !still_locked_groups.is_empty() {
+                return Err(String::from("Can not sign the consolidate transaction"));
+            }
+            let capacity: u64 = tx.output(0).expect("output").capacity().unpack();
+            let tx_hash = self
+                .rpc_client
+                .send_transaction(tx.data(), None)
+                .map_err(|err| {
+                    format!(
+                        "Send transaction of round {} error: {}",
+                        rounds.len() + 1,
+                        err
+                    )
+                })?;
+            rounds.push(ConsolidateRound {
+                tx_hash,
+                cells: chunk_len,
+                capacity,
+                fee,
+            });
+            pending_cells.drain(..chunk_len);
+        }
+        if rounds.is_empty() {
+            return Err(format!(
+                "No need to consolidate, only {} live cell found",
+                pending_cells.len()
+            ));
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn unlock_timelocked(
+        &mut self,
+        from_privkey: Option<PrivkeyWrapper>,
+        from_account: Option<H160>,
+        locked_address: Option<Address>,
+        lock_until: Option<Since>,
+        multisig_config: Option<MultisigConfig>,
+        to_address: Option<Address>,
+        fee_rate: u64,
+        unsigned_out: Option<String>,
+    ) -> Result<TransactionView, String> {
+        let network_type = get_network_type(self.rpc_client)?;
+        let (from_lock_arg, password) =
+            self.sender_lock_arg(from_privkey.as_ref(), from_account)?;
+        let from_address = Address::new(
+            network_type,
+            AddressPayload::from_pubkey_hash(from_lock_arg.clone()),
+            false,
+        );
+        let config = timelocked_multisig_config(&from_lock_arg, multisig_config)?;
+        if config.threshold() > 1 && unsigned_out.is_none() {
+            return Err(String::from(
+                "<unsigned-out> is required when the multisig threshold is greater than 1",
+            ));
+        }
+        let locked_address = match (locked_address, lock_until) {
+            (Some(address), _) => address,
+            (None, Some(since)) => {
+                let multisig_address =
+                    Address::new(network_type, config.to_address_payload(None), true);
+                build_timelocked_address(&multisig_address, since)?
+            }
+            (None, None) => {
+                return Err(String::from(
+                    "<from-locked-address> or <lock-until> is required",
+                ))
+            }
+        };
+        let since = timelocked_address_since(&locked_address)
+            .map_err(|err| format!("Invalid from-locked-address's args: {}", err))?;
+        if config.hash160().as_bytes() != &locked_address.payload().args()[0..20] {
+            return Err(String::from(
+                "from-locked-address is not created from the key (or the multisig config of <sighash-address>)",
+            ));
+        }
+        self.check_since_mature(since)?;
+
+        let to_lock = to_address
+            .map(|address| Script::from(address.payload()))
+            .unwrap_or_else(|| Script::from(from_address.payload()));
+        check_lock_script(&to_lock, false).map_err(|err| format!("Invalid to-address: {}", err))?;
+
+        let genesis_info = self.genesis_info()?;
+        let signer = self.simple_signer(from_privkey.as_ref(), &from_lock_arg, password, None)?;
+        let placeholder_witness = config.placeholder_witness();
+        let mut unlockers: HashMap<_, Box<dyn ScriptUnlocker>> = HashMap::new();
+        unlockers.insert(
+            ScriptId::new_type(MULTISIG_TYPE_HASH.clone()),
+            Box::new(SecpMultisigUnlocker::new(SecpMultisigScriptSigner::new(
+                signer,
+                config.clone(),
+            ))),
+        );
+        let lock_scripts = vec![(
+            Script::from(locked_address.payload()),
+            placeholder_witness,
+            SinceSource::LockArgs(20),
+        )];
+        let sweep_cells = self.collect_sweep_cells(&lock_scripts)?;
+        let (tx, _fee) = build_sweep_tx(
+            &sweep_cells,
+            to_lock,
+            &genesis_info.cell_dep_resolver,
+            fee_rate,
+        )?;
+        if let Some(path) = unsigned_out {
+            let mut helper = TxHelper::new(tx.clone());
+            helper.snapshot_inputs(|out_point| get_live_cell(self.rpc_client, out_point, true))?;
+            helper.add_multisig_config(config);
+            let repr = ReprTxHelper::new(helper, network_type);
+            let content = serde_json::to_string_pretty(&repr).map_err(|err| err.to_string())?;
+            fs::write(&path, content)
+                .map_err(|err| format!("Write unsigned transaction to {} failed: {}", path, err))?;
+            return Ok(tx);
+        }
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let (tx, still_locked_groups) =
+            unlock_tx(tx, &tx_dep_provider, &unlockers).map_err(|err| err.to_string())?;
+        if !still_locked_groups.is_empty() {
+            return Err(String::from("Can not sign the unlock transaction"));
+        }
+        let tx_hash = self
+            .rpc_client
+            .send_transaction(tx.data(), None)
+            .map_err(|err| format!("Send transaction error: {}", err))?;
+        assert_eq!(tx.hash(), tx_hash.pack());
+        Ok(tx)
+    }
+
+    /// Check if an absolute since value is satisfied by current tip block
+    fn check_since_mature(&mut self, since: Since) -> Result<(), String> {
+        let immature_info = match since.extract_metric() {
+            Some((SinceType::BlockNumber, number)) => {
+                let tip_number = self.rpc_client.get_tip_block_number()?;
+                (tip_number < number).then(|| {
+                    format!(
+                        "block number {}, current block number: {}",
+                        number, tip_number
+                    )
+                })
+            }
+            Some((SinceType::EpochNumberWithFraction, value)) => {
+                let epoch = EpochNumberWithFraction::from_full_value(value);
+                let tip_epoch = EpochNumberWithFraction::from_full_value(
+                    self.rpc_client.get_tip_header()?.inner.epoch.0,
+                );
+                (tip_epoch.to_rational() < epoch.to_rational())
+                    .then(|| format!("epoch {:#}, current epoch: {:#}", epoch, tip_epoch))
+            }
+            Some((SinceType::Timestamp, seconds)) => {
+                let median_time = self.rpc_client.get_blockchain_info()?.median_time.0 / 1000;
+                (median_time < seconds).then(|| {
+                    format!(
+                        "timestamp {}, current median time: {}",
+                        seconds, median_time
+                    )
+                })
+            }
+            None => return Err(String::from("Invalid since value")),
+        };
+        if let Some(info) = immature_info {
+            Err(format!(
+                "The time-locked cells are not mature yet, unlock after {}",
+                info
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_capacity(&mut self, lock_scripts: Vec<Script>) -> Result<(u64, u64, u64), String> {
+        let mut cell_collector = DefaultCellCollector::new(self.rpc_client.url());
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        let mut total_all = 0;
+        let mut total_immature = 0;
+        let mut total_dao = 0;
+        for script in lock_scripts {
+            let mut query = CellQueryOptions::new_lock(script);
+            query.maturity = MaturityOption::Both;
+            query.min_total_capacity = u64::max_value();
+            let (cells, total_capacity) = cell_collector
+                .collect_live_cells(&query, false)
+                .map_err(|err| err.to_string())?;
+            total_all += total_capacity;
+            for cell in &cells {
+                let capacity: u64 = cell.output.capacity().unpack();
+                if !is_mature(cell, max_mature_number) {
+                    total_immature += capacity;
+                }
+                if cell
+                    .output
+                    .type_()
+                    .to_opt()
+                    .map(|script| script.code_hash().as_slice() == DAO_TYPE_HASH.as_bytes())
+                    .unwrap_or(false)
+                {
+                    total_dao += capacity;
+                }
+            }
+        }
+        Ok((total_all, total_immature, total_dao))
+    }
+
+    /// Lock scripts of derived receiving/change addresses of a keystore account (include
+    /// watch-only account)
+    fn derived_lock_scripts(
+        &mut self,
+        address_payload: &AddressPayload,
+        receiving_address_length: u32,
+        change_address_length: u32,
+    ) -> Result<Vec<Script>, String> {
+        let lock_arg = H160::from_slice(address_payload.args().as_ref())
+            .map_err(|_| String::from("Derived address space requires a sighash address"))?;
+        let key_set = self
+            .plugin_mgr
+            .keystore_handler()
+            .derived_key_set_by_index(
+                lock_arg,
+                0,
+                receiving_address_length,
+                0,
+                change_address_length,
+                None,
+            )?;
+        Ok(key_set
+            .external
+            .iter()
+            .chain(key_set.change.iter())
+            .map(|(_, hash160)| Script::from(&AddressPayload::from_pubkey_hash(hash160.clone())))
+            .collect())
+    }
+
+    /// Walk the receiving and change chains of a HD account (BIP44 gap limit), the result is
+    /// saved to the scan cache.
+    pub fn hd_scan(
+        &mut self,
+        lock_arg: H160,
+        gap_limit: u32,
+        rescan: bool,
+    ) -> Result<HdScanState, String> {
+        let network_type = get_network_type(self.rpc_client)?;
+        let mut cache = HdScanCache::load(self.ckb_cli_dir)?;
+        let cached = if rescan {
+            HdScanState::default()
+        } else {
+            cache.get(network_type, &lock_arg).unwrap_or_default()
+        };
+        let mut state = HdScanState::default();
+        for is_change in [false, true] {
+            let last_used = if is_change {
+                cached.change_last_used
+            } else {
+                cached.receiving_last_used
+            };
+            let keystore = self.plugin_mgr.keystore_handler();
+            let client = self.rpc_client.client();
+            let derive = |start: u32, length: u32| -> Result<Vec<H160>, String> {
+                let (ext_start, ext_len, change_start, change_len) = if is_change {
+                    (0, 0, start, length)
+                } else {
+                    (start, length, 0, 0)
+                };
+                let key_set = keystore.derived_key_set_by_index(
+                    lock_arg.clone(),
+                    ext_start,
+                    ext_len,
+                    change_start,
+                    change_len,
+                    None,
+                )?;
+                let keys = if is_change {
+                    key_set.change
+                } else {
+                    key_set.external
+                };
+                Ok(keys.into_iter().map(|(_, hash160)| hash160).collect())
+            };
+            let last_used = scan_key_chain(last_used, gap_limit, derive, |hash160| {
+                is_address_used(client, hash160)
+            })?;
+            if is_change {
+                state.change_last_used = last_used;
+            } else {
+                state.receiving_last_used = last_used;
+            }
+        }
+        cache.set(network_type, &lock_arg, state);
+        cache.save(self.ckb_cli_dir)?;
+        Ok(state)
+    }
+
+    /// The lock arg of the derived key at `index` of receiving or change chain
+    fn derived_lock_arg(
+        &mut self,
+        lock_arg: H160,
+        is_change: bool,
+        index: u32,
+        password: Option<String>,
+    ) -> Result<(DerivationPath, H160), String> {
+        let (ext_start, ext_len, change_start, change_len) = if is_change {
+            (0, 0, index, 1)
+        } else {
+            (index, 1, 0, 0)
+        };
+        let key_set = self
+            .plugin_mgr
+            .keystore_handler()
+            .derived_key_set_by_index(
+                lock_arg,
+                ext_start,
+                ext_len,
+                change_start,
+                change_len,
+                password,
+            )?;
+        let keys = if is_change {
+            key_set.change
+        } else {
+            key_set.external
+        };
+        keys.into_iter()
+            .next()
+            .ok_or_else(|| format!("Derive key at index {} failed", index))
+    }
+
+    /// List a page of transactions related to the lock scripts, ordered by position in chain
+    pub fn history(
+        &mut self,
+        lock_scripts: Vec<Script>,
+        order: Order,
+        limit: u32,
+        after: Option<HistoryCursor>,
+    ) -> Result<Vec<HistoryEntry>, String> {
+        let is_asc = matches!(order, Order::Asc);
+        let block_range = match after {
+            Some(cursor) if is_asc => [cursor.block_number, u64::max_value()],
+            Some(cursor) => [0, cursor.block_number + 1],
+            None => [0, u64::max_value()],
+        };
+        let is_after = |position: HistoryCursor| match after {
+            Some(cursor) if is_asc => position > cursor,
+            Some(cursor) => position < cursor,
+            None => true,
+        };
+        // Merge the pages of all lock scripts
+        let mut positions: BTreeMap<HistoryCursor, H256> = BTreeMap::new();
+        for script in &lock_scripts {
+            let search_key = SearchKey {
+                script: script.clone().into(),
+                script_type: ScriptType::Lock,
+                script_search_mode: None,
+                filter: Some(SearchKeyFilter {
+                    block_range: Some([block_range[0].into(), block_range[1].into()]),
+                    ..Default::default()
+                }),
+                with_data: Some(false),
+                group_by_transaction: Some(true),
+            };
+            let mut indexer_cursor = None;
+            let mut count = 0;
+            loop {
+                let page = self.rpc_client.get_transactions(
+                    search_key.clone(),
+                    order.clone(),
+                    limit,
+                    indexer_cursor,
+                )?;
+                let page_len = page.objects.len();
+                for tx in page.objects {
+                    let (tx_hash, block_number, tx_index) = match tx {
+                        Tx::Grouped(tx) => (tx.tx_hash, tx.block_number, tx.tx_index),
+                        Tx::Ungrouped(tx) => (tx.tx_hash, tx.block_number, tx.tx_index),
+                    };
+                    let position = HistoryCursor {
+                        block_number: block_number.value(),
+                        tx_index: tx_index.value(),
+                    };
+                    if is_after(position) {
+                        count += 1;
+                        positions.insert(position, tx_hash);
+                    }
+                }
+                if count >= limit || page_len < limit as usize {
+                    break;
+                }
+                indexer_cursor = Some(page.last_cursor);
+            }
+        }
+        let positions: Vec<_> = if is_asc {
+            positions.into_iter().take(limit as usize).collect()
+        } else {
+            positions.into_iter().rev().take(limit as usize).collect()
+        };
+
+        let tx_dep_provider = DefaultTransactionDependencyProvider::new(self.rpc_client.url(), 10);
+        let mut timestamps: HashMap<u64, u64> = HashMap::new();
+        let mut entries = Vec::with_capacity(positions.len());
+        for (position, tx_hash) in positions {
+            let tx = tx_dep_provider
+                .get_transaction(&tx_hash.pack())
+                .map_err(|err| err.to_string())?;
+            let timestamp = match timestamps.get(&position.block_number) {
+                Some(timestamp) => *timestamp,
+                None => {
+                    let header = self
+                        .rpc_client
+                        .get_header_by_number(position.block_number)?
+                        .ok_or_else(|| format!("Block not found: {}", position.block_number))?;
+                    timestamps.insert(position.block_number, header.inner.timestamp.0);
+                    header.inner.timestamp.0
+                }
+            };
+            let mut income: u64 = 0;
+            let mut expense: u64 = 0;
+            let mut senders = Vec::new();
+            let mut recipients = Vec::new();
+            if !tx.is_cellbase() {
+                for input in tx.inputs() {
+                    let cell = tx_dep_provider
+                        .get_cell(&input.previous_output())
+                        .map_err(|err| err.to_string())?;
+                    let capacity: u64 = cell.capacity().unpack();
+                    if lock_scripts.contains(&cell.lock()) {
+                        expense += capacity;
+                    } else if !senders.contains(&cell.lock()) {
+                        senders.push(cell.lock());
+                    }
+                }
+            }
+            for output in tx.outputs() {
+                let capacity: u64 = output.capacity().unpack();
+                if lock_scripts.contains(&output.lock()) {
+                    income += capacity;
+                } else if !recipients.contains(&output.lock()) {
+                    recipients.push(output.lock());
+                }
+            }
+            let (direction, counterparties) = if expense == 0 {
+                (HistoryDirection::In, senders)
+            } else if recipients.is_empty() {
+                (HistoryDirection::SelfTransfer, recipients)
+            } else {
+                (HistoryDirection::Out, recipients)
+            };
+            entries.push(HistoryEntry {
+                tx_hash,
+                position,
+                timestamp,
+                direction,
+                income,
+                expense,
+                counterparties,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Get a page of live cells of the lock scripts (in order), the next cursor is `None` if all
+    /// cells are returned.
+    pub fn get_live_cells(
+        &mut self,
+        lock_scripts: &[Script],
+        filter: &LiveCellFilter,
+        limit: u32,
+        after: Option<LiveCellsCursor>,
+    ) -> Result<(Vec<LiveCell>, Option<LiveCellsCursor>), String> {
+        let max_mature_number = get_max_mature_number(self.rpc_client.client())?;
+        let mut live_cells = Vec::new();
+        let mut cursor = after.unwrap_or_default();
+        while cursor.script_index < lock_scripts.len() {
+            let rest_limit = limit.saturating_sub(live_cells.len() as u32);
+            if rest_limit == 0 {
+                return Ok((live_cells, Some(cursor)));
+            }
+            let search_key = filter.search_key(&lock_scripts[cursor.script_index]);
+            let page = self.rpc_client.get_cells(
+                search_key,
+                Order::Asc,
+                rest_limit,
+                cursor.after.take(),
+            )?;
+            let page_len = page.objects.len();
+            for cell in page.objects {
+                let cell = ckb_sdk::traits::LiveCell::from(cell);
+                if filter.matches(&cell) {
+                    let mature = is_mature(&cell, max_mature_number);
+                    live_cells.push(LiveCell {
+                        info: to_live_cell_info(&cell),
+                        mature,
+                    });
+                }
+            }
+            if page_len < rest_limit as usize {
+                cursor = LiveCellsCursor {
+                    script_index: cursor.script_index + 1,
+                    after: None,
+                };
+            } else {
+                cursor.after = Some(page.last_cursor);
+            }
+        }
+        Ok((live_cells, None))
+    }
+}
+
+impl<'a> CliSubCommand for WalletSubCommand<'a> {
     fn process(&mut self, matches: &ArgMatches, debug: bool) -> Result<Output, String> {
         match matches.subcommand() {
             ("transfer", Some(m)) => {
@@ -545,22 +1864,162 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     from_account: m.value_of("from-account").map(|s| s.to_string()),
                     from_locked_address: m.value_of("from-locked-address").map(|s| s.to_string()),
                     password: None,
-                    capacity: get_arg_value(m, "capacity")?,
+                    capacity: m.value_of("capacity").map(|s| s.to_string()),
                     fee_rate: get_arg_value(m, "fee-rate")?,
+                    max_fee_rate: m.value_of("max-fee-rate").map(|s| s.to_string()),
                     force_small_change_as_fee: m.value_of("max-tx-fee").map(|s| s.to_string()),
-                    derive_receiving_address_length: Some(get_arg_value(
-                        m,
-                        "derive-receiving-address-length",
-                    )?),
+                    // Only the explicit value, the scan cache is used otherwise
+                    derive_receiving_address_length: if m
+                        .occurrences_of("derive-receiving-address-length")
+                        > 0
+                    {
+                        Some(get_arg_value(m, "derive-receiving-address-length")?)
+                    } else {
+                        None
+                    },
                     derive_change_address: m
                         .value_of("derive-change-address")
                         .map(|s| s.to_string()),
-                    to_address: get_arg_value(m, "to-address")?,
+                    to_address: m.value_of("to-address").map(|s| s.to_string()),
                     to_data: Some(to_data),
+                    to_file: m.value_of("to-file").map(|s| s.to_string()),
+                    transfer_all: m.is_present("all"),
+                    coin_selection: m.value_of("coin-selection").map(|s| s.to_string()),
+                    lock_until: m.value_of("lock-until").map(|s| s.to_string()),
+                    unsigned_out: m.value_of("unsigned-out").map(|s| s.to_string()),
+                    from_omnilock_address: m
+                        .value_of("from-omnilock-address")
+                        .map(|s| s.to_string()),
+                    cell_deps: m.value_of("cell-deps").map(|s| s.to_string()),
+                    sighash_addresses: m
+                        .values_of("sighash-address")
+                        .map(|values| values.map(|s| s.to_string()).collect())
+                        .unwrap_or_default(),
+                    require_first_n: m.value_of("require-first-n").map(|s| s.to_string()),
+                    threshold: m.value_of("threshold").map(|s| s.to_string()),
                     is_type_id: m.is_present("type-id"),
                     skip_check_to_address: m.is_present("skip-check-to-address"),
+                    skip_lint: m.is_present("skip-lint"),
                 };
+                let unsigned_out = args.unsigned_out.clone();
                 let tx = self.transfer(args, false)?;
+                if debug {
+                    let rpc_tx_view = json_types::TransactionView::from(tx);
+                    Ok(Output::new_output(rpc_tx_view))
+                } else if let Some(unsigned_out) = unsigned_out {
+                    let tx_hash: H256 = tx.hash().unpack();
+                    let resp = serde_json::json!({
+                        "transaction_hash": tx_hash,
+                        "unsigned_tx_file": unsigned_out,
+                    });
+                    Ok(Output::new_output(resp))
+                } else {
+                    let tx_hash: H256 = tx.hash().unpack();
+                    Ok(Output::new_output(tx_hash))
+                }
+            }
+            ("consolidate", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let from_privkey: Option<PrivkeyWrapper> =
+                    PrivkeyPathParser.from_matches_opt(m, "privkey-path")?;
+                let from_account: Option<H160> = m
+                    .value_of("from-account")
+                    .map(|input| parse_from_account(input, network_type))
+                    .transpose()?;
+                let to_address: Option<Address> = AddressParser::default()
+                    .set_network(network_type)
+                    .from_matches_opt(m, "to-address")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
+                let max_inputs: usize =
+                    FromStrParser::<usize>::default().from_matches(m, "max-inputs")?;
+                if max_inputs < 2 {
+                    return Err(String::from("<max-inputs> can not less than 2"));
+                }
+
+                let mut rounds = Vec::new();
+                let result = self.consolidate(
+                    from_privkey,
+                    from_account,
+                    to_address,
+                    fee_rate,
+                    max_inputs,
+                    &mut rounds,
+                );
+                if let (Err(err), true) = (result.as_ref(), rounds.is_empty()) {
+                    return Err(err.clone());
+                }
+                let total_cells: usize = rounds.iter().map(|round| round.cells).sum();
+                let total_fee: u64 = rounds.iter().map(|round| round.fee).sum();
+                let mut resp = serde_json::json!({
+                    "rounds": rounds.into_iter().map(|round| {
+                        serde_json::json!({
+                            "tx_hash": round.tx_hash,
+                            "cells": round.cells,
+                            "capacity": format!("{:#}", HumanCapacity::from(round.capacity)),
+                            "fee": format!("{:#}", HumanCapacity::from(round.fee)),
+                        })
+                    }).collect::<Vec<_>>(),
+                    "total_cells": total_cells,
+                    "total_fee": format!("{:#}", HumanCapacity::from(total_fee)),
+                });
+                // Report the sent transactions when stopped part-way
+                match result {
+                    Ok(()) => Ok(Output::new_output(resp)),
+                    Err(err) => {
+                        resp["error"] = serde_json::json!(err);
+                        Ok(Output::new_error(resp))
+                    }
+                }
+            }
+            ("unlock-timelocked", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let from_privkey: Option<PrivkeyWrapper> =
+                    PrivkeyPathParser.from_matches_opt(m, "privkey-path")?;
+                let from_account: Option<H160> = m
+                    .value_of("from-account")
+                    .map(|input| parse_from_account(input, network_type))
+                    .transpose()?;
+                let locked_address: Option<Address> = AddressParser::new_multisig()
+                    .set_network(network_type)
+                    .from_matches_opt(m, "from-locked-address")?;
+                let lock_until: Option<Since> = SinceParser.from_matches_opt(m, "lock-until")?;
+                let sighash_addresses: Vec<Address> = AddressParser::new_sighash()
+                    .set_network(network_type)
+                    .from_matches_vec(m, "sighash-address")?;
+                let multisig_config = if sighash_addresses.is_empty() {
+                    None
+                } else {
+                    let sighash_addresses = sighash_addresses
+                        .iter()
+                        .map(|address| H160::from_slice(address.payload().args().as_ref()).unwrap())
+                        .collect();
+                    let require_first_n: u8 = FromStrParser::<u8>::default()
+                        .from_matches_opt(m, "require-first-n")?
+                        .unwrap_or(0);
+                    let threshold: u8 = FromStrParser::<u8>::default()
+                        .from_matches_opt(m, "threshold")?
+                        .unwrap_or(1);
+                    Some(
+                        MultisigConfig::new_with(sighash_addresses, require_first_n, threshold)
+                            .map_err(|err| err.to_string())?,
+                    )
+                };
+                let to_address: Option<Address> = AddressParser::default()
+                    .set_network(network_type)
+                    .from_matches_opt(m, "to-address")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
+                let unsigned_out = m.value_of("unsigned-out").map(|s| s.to_string());
+
+                let tx = self.unlock_timelocked(
+                    from_privkey,
+                    from_account,
+                    locked_address,
+                    lock_until,
+                    multisig_config,
+                    to_address,
+                    fee_rate,
+                    unsigned_out,
+                )?;
                 if debug {
                     let rpc_tx_view = json_types::TransactionView::from(tx);
                     Ok(Output::new_output(rpc_tx_view))
@@ -569,6 +2028,131 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     Ok(Output::new_output(tx_hash))
                 }
             }
+            ("bump-fee", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let from_privkey: Option<PrivkeyWrapper> =
+                    PrivkeyPathParser.from_matches_opt(m, "privkey-path")?;
+                let from_account: Option<H160> = m
+                    .value_of("from-account")
+                    .map(|input| parse_from_account(input, network_type))
+                    .transpose()?;
+                let tx_hash: H256 =
+                    FixedHashParser::<H256>::default().from_matches(m, "tx-hash")?;
+                let fee_rate = get_fee_rate(self.rpc_client, m)?;
+                let change_index: Option<usize> =
+                    FromStrParser::<usize>::default().from_matches_opt(m, "change-index")?;
+                let receiving_address_length: u32 = FromStrParser::<u32>::default()
+                    .from_matches(m, "derive-receiving-address-length")?;
+                let change_address_length: u32 = FromStrParser::<u32>::default()
+                    .from_matches(m, "derive-change-address-length")?;
+
+                let result = self.bump_fee(
+                    from_privkey,
+                    from_account,
+                    tx_hash,
+                    fee_rate,
+                    receiving_address_length,
+                    change_address_length,
+                    change_index,
+                )?;
+                let new_tx_hash: H256 = result.tx.hash().unpack();
+                let mut resp = serde_json::json!({
+                    "old_tx_hash": result.old_tx_hash,
+                    "new_tx_hash": new_tx_hash,
+                    "old_fee": format!("{:#}", HumanCapacity(result.old_fee)),
+                    "new_fee": format!("{:#}", HumanCapacity(result.new_fee)),
+                    "old_fee_rate": result.old_fee_rate,
+                    "new_fee_rate": result.new_fee_rate,
+                });
+                if debug {
+                    resp["transaction"] =
+                        serde_json::to_value(json_types::TransactionView::from(result.tx))
+                            .map_err(|err| err.to_string())?;
+                }
+                Ok(Output::new_output(resp))
+            }
+            ("hd-scan", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let lock_arg =
+                    parse_from_account(m.value_of("from-account").unwrap(), network_type)?;
+                let gap_limit: u32 = FromStrParser::<u32>::default()
+                    .from_matches_opt(m, "gap-limit")?
+                    .unwrap_or(DEFAULT_GAP_LIMIT);
+                let state = self.hd_scan(lock_arg.clone(), gap_limit, m.is_present("rescan"))?;
+                let (_, receiving_lock_arg) = self.derived_lock_arg(
+                    lock_arg.clone(),
+                    false,
+                    state.next_receiving_index(),
+                    None,
+                )?;
+                let (_, change_lock_arg) =
+                    self.derived_lock_arg(lock_arg, true, state.next_change_index(), None)?;
+                let to_address = |hash160: H160| {
+                    Address::new(
+                        network_type,
+                        AddressPayload::from_pubkey_hash(hash160),
+                        true,
+                    )
+                    .to_string()
+                };
+                let resp = serde_json::json!({
+                    "receiving_last_used_index": state.receiving_last_used,
+                    "change_last_used_index": state.change_last_used,
+                    "next_receiving_address": to_address(receiving_lock_arg),
+                    "next_change_address": to_address(change_lock_arg),
+                });
+                Ok(Output::new_output(resp))
+            }
+            ("history", Some(m)) => {
+                let network_type = get_network_type(self.rpc_client)?;
+                let lock_scripts = if let Some(input) = m.value_of("account") {
+                    let lock_arg = parse_from_account(input, network_type)?;
+                    let payload = AddressPayload::from_pubkey_hash(lock_arg.clone());
+                    match HdScanCache::load(self.ckb_cli_dir)?.get(network_type, &lock_arg) {
+                        Some(state) => self.derived_lock_scripts(
+                            &payload,
+                            state.next_receiving_index().max(1),
+                            state.next_change_index(),
+                        )?,
+                        None => vec![Script::from(&payload)],
+                    }
+                } else {
+                    let address: Address = AddressParser::default()
+                        .set_network(network_type)
+                        .from_matches(m, "address")
+                        .map_err(|_| String::from("<address> or <account> is required"))?;
+                    vec![Script::from(address.payload())]
+                };
+                let limit: u32 = FromStrParser::<u32>::default().from_matches(m, "limit")?;
+                let order = match m.value_of("order") {
+                    Some("asc") => Order::Asc,
+                    _ => Order::Desc,
+                };
+                let after: Option<HistoryCursor> =
+                    FromStrParser::<HistoryCursor>::new().from_matches_opt(m, "after")?;
+
+                let entries = self.history(lock_scripts, order, limit, after)?;
+                let next_cursor = if entries.len() == limit as usize {
+                    entries.last().map(|entry| entry.position.to_string())
+                } else {
+                    None
+                };
+                if let Some(path) = m.value_of("csv") {
+                    let rows = entries
+                        .iter()
+                        .map(|entry| entry.csv_row(network_type))
+                        .collect::<Vec<_>>();
+                    write_csv(path, HistoryEntry::CSV_HEADER, &rows)?;
+                }
+                let resp = serde_json::json!({
+                    "transactions": entries
+                        .iter()
+                        .map(|entry| entry.to_json(network_type))
+                        .collect::<Vec<_>>(),
+                    "next_cursor": next_cursor,
+                });
+                Ok(Output::new_output(resp))
+            }
             ("get-capacity", Some(m)) => {
                 let network_type = get_network_type(self.rpc_client)?;
 
@@ -587,23 +2171,11 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                 };
                 let mut lock_scripts = vec![Script::from(&address_payload)];
                 if m.is_present("derived") {
-                    let lock_arg = H160::from_slice(address_payload.args().as_ref()).unwrap();
-
-                    let key_set = self
-                        .plugin_mgr
-                        .keystore_handler()
-                        .derived_key_set_by_index(
-                            lock_arg,
-                            0,
-                            receiving_address_length,
-                            0,
-                            change_address_length,
-                            None,
-                        )?;
-                    for (_, hash160) in key_set.external.iter().chain(key_set.change.iter()) {
-                        let payload = AddressPayload::from_pubkey_hash(hash160.clone());
-                        lock_scripts.push(Script::from(&payload));
-                    }
+                    lock_scripts.extend(self.derived_lock_scripts(
+                        &address_payload,
+                        receiving_address_length,
+                        change_address_length,
+                    )?);
                 }
 
                 let (total, immature, dao) = self.get_capacity(lock_scripts)?;
@@ -627,31 +2199,110 @@ impl<'a> CliSubCommand for WalletSubCommand<'a> {
                     FromStrParser::<u64>::default().from_matches_opt(m, "from")?;
                 let to_number_opt: Option<u64> =
                     FromStrParser::<u64>::default().from_matches_opt(m, "to")?;
+                let type_script: Option<Script> =
+                    ScriptParser.from_matches_opt(m, "type-script")?;
+                let data_hash: Option<H256> =
+                    FixedHashParser::<H256>::default().from_matches_opt(m, "data-hash")?;
+                let min_capacity: Option<u64> = CapacityParser
+                    .from_matches_opt::<HumanCapacity>(m, "min-capacity")?
+                    .map(Into::into);
+                let max_capacity: Option<u64> = CapacityParser
+                    .from_matches_opt::<HumanCapacity>(m, "max-capacity")?
+                    .map(Into::into);
+                let after: Option<LiveCellsCursor> =
+                    FromStrParser::<LiveCellsCursor>::new().from_matches_opt(m, "after")?;
+                let filter = LiveCellFilter {
+                    type_script: if m.is_present("no-type") {
+                        Some(None)
+                    } else {
+                        type_script.map(Some)
+                    },
+                    has_data: if m.is_present("with-data") || data_hash.is_some() {
+                        Some(true)
+                    } else if m.is_present("without-data") {
+                        Some(false)
+                    } else {
+                        None
+                    },
+                    data_hash,
+                    capacity_range: if min_capacity.is_some() || max_capacity.is_some() {
+                        Some((
+                            min_capacity.unwrap_or(0),
+                            max_capacity
+                                .map(|capacity| capacity.saturating_add(1))
+                                .unwrap_or(u64::max_value()),
+                        ))
+                    } else {
+                        None
+                    },
+                    block_range: (
+                        from_number_opt.unwrap_or(0),
+                        to_number_opt.unwrap_or(u64::max_value()),
+                    ),
+                };
 
                 let network_type = get_network_type(self.rpc_client)?;
-                let address: Address = AddressParser::default()
+                let address_opt: Option<Address> = AddressParser::default()
                     .set_network(network_type)
-                    .from_matches(m, "address")?;
-                let lock_script = Script::from(address.payload());
-                let live_cells = self.get_live_cells(
-                    lock_script,
-                    PrimaryScriptType::Lock,
-                    from_number_opt.unwrap_or(0),
-                    to_number_opt.unwrap_or(u64::max_value()),
-                    limit,
-                )?;
-
-                let resp = serde_json::json!({
-                    "live_cells": live_cells.into_iter().map(|live_cell| {
-                        let LiveCell{ info, mature } = live_cell;
-                        let mut value = serde_json::to_value(&info).unwrap();
-                        let mature = serde_json::Value::Bool(mature);
+                    .from_matches_opt(m, "address")?;
+                let address_payload = if let Some(address) = address_opt {
+                    address.payload().clone()
+                } else {
+                    get_address(Some(network_type), m)?
+                };
+                let mut lock_scripts = vec![Script::from(&address_payload)];
+                if m.is_present("derived") {
+                    let receiving_address_length: u32 = FromStrParser::<u32>::default()
+                        .from_matches(m, "derive-receiving-address-length")?;
+                    let change_address_length: u32 = FromStrParser::<u32>::default()
+                        .from_matches(m, "derive-change-address-length")?;
+                    lock_scripts.extend(self.derived_lock_scripts(
+                        &address_payload,
+                        receiving_address_length,
+                        change_address_length,
+                    )?);
+                }
+                let (live_cells, next_cursor) =
+                    self.get_live_cells(&lock_scripts, &filter, limit, after)?;
+
+                if let Some(path) = m.value_of("csv") {
+                    let rows = live_cells
+                        .iter()
+                        .map(|live_cell| {
+                            let info = &live_cell.info;
+                            let (type_code_hash, type_hash) = info
+                                .type_hashes
+                                .as_ref()
+                                .map(|(code_hash, hash)| {
+                                    (format!("{:#x}", code_hash), format!("{:#x}", hash))
+                                })
+                                .unwrap_or_default();
+                            vec![
+                                format!("{:#x}", info.tx_hash),
+                                info.output_index.to_string(),
+                                HumanCapacity::from(info.capacity).to_string(),
+                                type_code_hash,
+                                type_hash,
+                                info.data_bytes.to_string(),
+                                info.number.to_string(),
+                                live_cell.mature.to_string(),
+                            ]
+                        })
+                        .collect::<Vec<_>>();
+                    write_csv(path, LiveCell::CSV_HEADER, &rows)?;
+                }
+                let resp = serde_json::json!({
+                    "live_cells": live_cells.into_iter().map(|live_cell| {
+                        let LiveCell{ info, mature } = live_cell;
+                        let mut value = serde_json::to_value(&info).unwrap();
+                        let mature = serde_json::Value::Bool(mature);
                         let capacity_string = serde_json::Value::String(format!("{:#}", HumanCapacity::from(info.capacity)));
                         let map = value.as_object_mut().unwrap();
                         map.insert("capacity".to_string(), capacity_string);
                         map.insert("mature".to_string(), mature);
                         value
                     }).collect::<Vec<_>>(),
+                    "next_cursor": next_cursor.map(|cursor| cursor.to_string()),
                 });
 
                 Ok(Output::new_output(resp))
@@ -669,13 +2320,682 @@ pub struct TransferArgs {
     pub password: Option<String>,
     pub derive_receiving_address_length: Option<String>,
     pub derive_change_address: Option<String>,
-    pub capacity: String,
+    pub capacity: Option<String>,
+    /// Fixed fee rate or `auto[:target_blocks]`, see `FeeRateSpec`
     pub fee_rate: String,
+    /// The ceiling of the automatic fee rate, default is `DEFAULT_MAX_FEE_RATE`
+    pub max_fee_rate: Option<String>,
     pub force_small_change_as_fee: Option<String>,
-    pub to_address: String,
+    pub to_address: Option<String>,
     pub to_data: Option<Bytes>,
+    /// Batch transfer targets file, conflicts with `to_address`/`capacity`/`to_data`
+    pub to_file: Option<String>,
     pub is_type_id: bool,
     pub skip_check_to_address: bool,
+    /// Send the transaction without the lint checks, see `lint_tx`
+    pub skip_lint: bool,
+    /// Transfer all capacity to `to_address` without change cell, conflicts with `capacity`
+    pub transfer_all: bool,
+    /// Coin selection strategy name, see `CoinSelection`
+    pub coin_selection: Option<String>,
+    /// Send to the time-locked address of `to_address`, see `SinceParser` for the format
+    pub lock_until: Option<String>,
+    /// Write the unsigned transaction (`ReprTxHelper` JSON) to this file instead of sending it
+    pub unsigned_out: Option<String>,
+    /// Transfer from this omnilock address, requires `cell_deps`
+    pub from_omnilock_address: Option<String>,
+    /// The cell deps file path (contains the `omnilock` item)
+    pub cell_deps: Option<String>,
+    /// The multisig config of the omnilock address with multisig identity
+    pub sighash_addresses: Vec<String>,
+    pub require_first_n: Option<String>,
+    pub threshold: Option<String>,
+}
+
+/// The multisig config of a time-locked address, the single-key config of the sender by
+/// default. The given multi-key config must include the sender.
+fn timelocked_multisig_config(
+    from_lock_arg: &H160,
+    multisig_config: Option<MultisigConfig>,
+) -> Result<MultisigConfig, String> {
+    match multisig_config {
+        Some(config) => {
+            if !config.sighash_addresses().contains(from_lock_arg) {
+                return Err(String::from(
+                    "The sender is not one of the <sighash-address> of the multisig config",
+                ));
+            }
+            Ok(config)
+        }
+        None => MultisigConfig::new_with(vec![from_lock_arg.clone()], 0, 1)
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Extract the absolute since value from a time-locked (multisig with since) address
+fn timelocked_address_since(address: &Address) -> Result<Since, String> {
+    let payload = address.payload();
+    let args = payload.args();
+    if payload.code_hash(Some(address.network())).as_slice() != MULTISIG_TYPE_HASH.as_bytes()
+        || payload.hash_type() != ScriptHashType::Type
+    {
+        return Err(String::from("not a multisig address"));
+    }
+    if args.len() != 28 {
+        return Err(format!("invalid {}", args.len()));
+    }
+    let mut since_bytes = [0u8; 8];
+    since_bytes.copy_from_slice(&args[20..]);
+    let since = Since::from_raw_value(u64::from_le_bytes(since_bytes));
+    if !since.flags_is_valid() {
+        return Err(String::from("invalid since flags"));
+    }
+    if !since.is_absolute() {
+        return Err(String::from("only support absolute since value"));
+    }
+    Ok(since)
+}
+
+/// Build the time-locked (multisig with since) address of a sighash address or a multisig
+/// address, the cells of the new address can only be unlocked after `since`.
+fn build_timelocked_address(address: &Address, since: Since) -> Result<Address, String> {
+    let payload = address.payload();
+    let code_hash: H256 = payload.code_hash(Some(address.network())).unpack();
+    let args = payload.args();
+    let multisig_hash160 = match (code_hash, payload.hash_type(), args.len()) {
+        (code_hash, ScriptHashType::Type, 20) if code_hash == SIGHASH_TYPE_HASH => {
+            let lock_arg = H160::from_slice(args.as_ref()).unwrap();
+            MultisigConfig::new_with(vec![lock_arg], 0, 1)
+                .map_err(|err| err.to_string())?
+                .hash160()
+        }
+        (code_hash, ScriptHashType::Type, 20) if code_hash == MULTISIG_TYPE_HASH => {
+            H160::from_slice(args.as_ref()).unwrap()
+        }
+        _ => {
+            return Err(format!(
+                "Can not build time-locked address from {}, only sighash or multisig (without since) address is supported",
+                address
+            ))
+        }
+    };
+    let mut lock_args = multisig_hash160.as_bytes().to_vec();
+    lock_args.extend_from_slice(&since.value().to_le_bytes());
+    let payload = AddressPayload::new_full(
+        ScriptHashType::Type,
+        MULTISIG_TYPE_HASH.pack(),
+        Bytes::from(lock_args),
+    );
+    Ok(Address::new(address.network(), payload, true))
+}
+
+/// Parse `--from-account` value, the lock arg or the sighash address
+fn parse_from_account(input: &str, network_type: NetworkType) -> Result<H160, String> {
+    FixedHashParser::<H160>::default()
+        .parse(input)
+        .or_else(|err| {
+            let result: Result<Address, String> = AddressParser::new_sighash()
+                .set_network(network_type)
+                .parse(input);
+            result
+                .map(|address| H160::from_slice(&address.payload().args()).unwrap())
+                .map_err(|_| err)
+        })
+}
+
+/// The result of replacing a pending transaction, fee rates are in shannons/KB
+#[derive(Clone, Debug)]
+pub struct BumpFeeResult {
+    pub old_tx_hash: H256,
+    pub old_fee: u64,
+    pub old_fee_rate: u64,
+    pub new_fee: u64,
+    pub new_fee_rate: u64,
+    /// The signed replacement transaction
+    pub tx: TransactionView,
+}
+
+/// Find the change output of a transaction to replace: the output of `change_index`, or the only
+/// output locked by the sender without type script and data. Returns `None` if there is no such
+/// output (a new change output is required).
+fn find_change_output(
+    tx: &TransactionView,
+    owned_locks: &HashSet<Script>,
+    change_index: Option<usize>,
+) -> Result<Option<usize>, String> {
+    let is_change = |(output, data): &(CellOutput, Bytes)| {
+        owned_locks.contains(&output.lock()) && output.type_().is_none() && data.is_empty()
+    };
+    if let Some(idx) = change_index {
+        let output = tx
+            .output_with_data(idx)
+            .ok_or_else(|| format!("Change output #{} not exists", idx))?;
+        if !is_change(&output) {
+            return Err(format!(
+                "Output #{} can not be the change output, it must be locked by the sender without type script and data",
+                idx
+            ));
+        }
+        return Ok(Some(idx));
+    }
+    let indexes = tx
+        .outputs_with_data_iter()
+        .enumerate()
+        .filter(|(_, output)| is_change(output))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if indexes.len() > 1 {
+        return Err(format!(
+            "Outputs {:?} are all locked by the sender, use --change-index to pick the change output",
+            indexes
+        ));
+    }
+    Ok(indexes.first().cloned())
+}
+
+/// Check the fee of the replacement transaction against the RBF rule of the tx-pool: it must
+/// pay the fee of the replaced transaction plus the fee of `min_rbf_rate` (shannons/KB).
+fn check_rbf_fee(
+    old_fee: u64,
+    new_fee: u64,
+    new_size: u64,
+    min_rbf_rate: u64,
+) -> Result<(), String> {
+    let min_fee = old_fee + FeeRate::from_u64(min_rbf_rate).fee(new_size).as_u64();
+    if new_fee < min_fee {
+        return Err(format!(
+            "The fee of the replacement transaction ({}) must be at least {} (the old fee plus the fee of min_rbf_rate {} shannons/KB), try a higher fee rate",
+            HumanCapacity(new_fee),
+            HumanCapacity(min_fee),
+            min_rbf_rate
+        ));
+    }
+    Ok(())
+}
+
+/// The max serialized size added by an extra input of the replacement transaction: the cell
+/// input (44 bytes), the witness offset and length (8 bytes) and the placeholder witness of a
+/// new lock script group (85 bytes).
+const MAX_EXTRA_INPUT_SIZE: u64 = 44 + 8 + 85;
+
+/// The replacement transaction of `wallet bump-fee`, the change output pays the new fee.
+struct ReplacementTx {
+    /// The replaced transaction with placeholder witnesses (and the new change output)
+    tx: TransactionView,
+    input_locks: Vec<Script>,
+    input_total: u64,
+    change_idx: usize,
+}
+
+impl ReplacementTx {
+    fn new(
+        old_tx: &TransactionView,
+        input_locks: Vec<Script>,
+        input_total: u64,
+        change_idx: Option<usize>,
+        change_lock: Script,
+    ) -> Result<ReplacementTx, String> {
+        let mut outputs = old_tx.outputs().into_iter().collect::<Vec<_>>();
+        let mut outputs_data = old_tx.outputs_data().into_iter().collect::<Vec<_>>();
+        let change_idx = match change_idx {
+            Some(idx) => idx,
+            None => {
+                outputs.push(CellOutput::new_builder().lock(change_lock).build());
+                outputs_data.push(Default::default());
+                outputs.len() - 1
+            }
+        };
+
+        // Only the first witness of each lock script group has the signature
+        let mut witnesses = old_tx.witnesses().into_iter().collect::<Vec<_>>();
+        while witnesses.len() < input_locks.len() {
+            witnesses.push(Default::default());
+        }
+        for (idx, lock) in input_locks.iter().enumerate() {
+            if input_locks[..idx].contains(lock) {
+                continue;
+            }
+            let init_witness = if witnesses[idx].raw_data().is_empty() {
+                WitnessArgs::default()
+            } else {
+                WitnessArgs::from_slice(witnesses[idx].raw_data().as_ref())
+                    .map_err(|err| err.to_string())?
+            };
+            witnesses[idx] = init_witness
+                .as_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .build()
+                .as_bytes()
+                .pack();
+        }
+        let tx = old_tx
+            .as_advanced_builder()
+            .set_outputs(outputs)
+            .set_outputs_data(outputs_data)
+            .set_witnesses(witnesses)
+            .build();
+        Ok(ReplacementTx {
+            tx,
+            input_locks,
+            input_total,
+            change_idx,
+        })
+    }
+
+    /// The total capacity of the outputs except the change output
+    fn other_outputs_total(&self) -> u64 {
+        self.tx
+            .outputs()
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.change_idx)
+            .map(|(_, output)| Unpack::<u64>::unpack(&output.capacity()))
+            .sum()
+    }
+
+    /// Select the extra input cells from `candidates` (in order) until they can pay the fee of
+    /// `fee_rate` (estimated by `MAX_EXTRA_INPUT_SIZE`), then build the replacement transaction
+    /// once. Returns `None` if all candidates are not enough.
+    fn build_with_candidates(
+        &self,
+        candidates: Vec<ckb_sdk::traits::LiveCell>,
+        fee_rate: u64,
+    ) -> Result<Option<(TransactionView, u64)>, String> {
+        let base_size = self.tx.data().as_reader().serialized_size_in_block() as u64;
+        let occupied_capacity = self
+            .tx
+            .output(self.change_idx)
+            .expect("change output")
+            .occupied_capacity(Capacity::zero())
+            .map_err(|err| err.to_string())?
+            .as_u64();
+        let required = self.other_outputs_total() + occupied_capacity;
+        let mut extra_capacity: u64 = 0;
+        for (idx, cell) in candidates.iter().enumerate() {
+            let capacity: u64 = cell.output.capacity().unpack();
+            extra_capacity += capacity;
+            let max_size = base_size + (idx as u64 + 1) * MAX_EXTRA_INPUT_SIZE;
+            let max_fee = FeeRate::from_u64(fee_rate).fee(max_size).as_u64();
+            if self.input_total + extra_capacity >= required + max_fee {
+                return self.build(&candidates[..=idx], fee_rate);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Build the replacement transaction with extra inputs, the change output pays the fee of
+    /// `fee_rate`. Returns the transaction and the fee, or `None` if the capacity is not enough.
+    fn build(
+        &self,
+        extra_cells: &[ckb_sdk::traits::LiveCell],
+        fee_rate: u64,
+    ) -> Result<Option<(TransactionView, u64)>, String> {
+        let mut locks = self.input_locks.clone();
+        let mut extra_witnesses = Vec::new();
+        let mut extra_capacity: u64 = 0;
+        for cell in extra_cells {
+            let lock = cell.output.lock();
+            extra_witnesses.push(if locks.contains(&lock) {
+                packed::Bytes::default()
+            } else {
+                WitnessArgs::new_builder()
+                    .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                    .build()
+                    .as_bytes()
+                    .pack()
+            });
+            locks.push(lock);
+            let capacity: u64 = cell.output.capacity().unpack();
+            extra_capacity += capacity;
+        }
+        let input_len = self.input_locks.len();
+        let mut witnesses = self.tx.witnesses().into_iter().collect::<Vec<_>>();
+        witnesses.splice(input_len..input_len, extra_witnesses);
+        let tx = self
+            .tx
+            .as_advanced_builder()
+            .inputs(
+                extra_cells
+                    .iter()
+                    .map(|cell| CellInput::new(cell.out_point.clone(), 0)),
+            )
+            .set_witnesses(witnesses)
+            .build();
+
+        let other_outputs_total = self.other_outputs_total();
+        // The size of the transaction not changed by the change capacity
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        let fee = FeeRate::from_u64(fee_rate).fee(tx_size).as_u64();
+        let change_capacity =
+            match (self.input_total + extra_capacity).checked_sub(other_outputs_total + fee) {
+                Some(capacity) => capacity,
+                None => return Ok(None),
+            };
+        let change_output = tx
+            .output(self.change_idx)
+            .expect("change output")
+            .as_builder()
+            .capacity(change_capacity.pack())
+            .build();
+        let occupied_capacity = change_output
+            .occupied_capacity(Capacity::zero())
+            .map_err(|err| err.to_string())?
+            .as_u64();
+        if change_capacity < occupied_capacity {
+            return Ok(None);
+        }
+        let mut outputs = tx.outputs().into_iter().collect::<Vec<_>>();
+        outputs[self.change_idx] = change_output;
+        Ok(Some((
+            tx.as_advanced_builder().set_outputs(outputs).build(),
+            fee,
+        )))
+    }
+}
+
+/// One consolidate transaction
+#[derive(Clone, Debug)]
+pub struct ConsolidateRound {
+    pub tx_hash: H256,
+    /// Number of merged cells
+    pub cells: usize,
+    /// Capacity of the merged cell
+    pub capacity: u64,
+    pub fee: u64,
+}
+
+/// Live cell collected for sweep/consolidate transaction, with the `since` value and the
+/// placeholder witness of its lock script.
+struct SweepCell {
+    cell: ckb_sdk::traits::LiveCell,
+    since: u64,
+    placeholder_witness: WitnessArgs,
+}
+
+/// Collect the mature live cells (without type script and data) of given lock scripts by
+/// indexer from `cursor`, at most `limit` cells. `get_cells` is the `get_cells` RPC of indexer
+/// (with search key, limit and after cursor).
+///
+/// Return the cells and the cursor of the next page (`None` if all cells are collected).
+fn collect_sweep_cells_page(
+    lock_scripts: &[(Script, WitnessArgs, SinceSource)],
+    get_cells: &mut dyn FnMut(
+        SearchKey,
+        u32,
+        Option<json_types::JsonBytes>,
+    ) -> Result<Pagination<Cell>, String>,
+    max_mature_number: u64,
+    limit: u32,
+    mut cursor: LiveCellsCursor,
+) -> Result<(Vec<SweepCell>, Option<LiveCellsCursor>), String> {
+    let filter = LiveCellFilter {
+        type_script: Some(None),
+        has_data: Some(false),
+        block_range: (0, u64::max_value()),
+        ..Default::default()
+    };
+    let mut sweep_cells = Vec::new();
+    while cursor.script_index < lock_scripts.len() {
+        let rest_limit = limit.saturating_sub(sweep_cells.len() as u32);
+        if rest_limit == 0 {
+            return Ok((sweep_cells, Some(cursor)));
+        }
+        let (lock_script, placeholder_witness, since_source) = &lock_scripts[cursor.script_index];
+        let next_script = LiveCellsCursor {
+            script_index: cursor.script_index + 1,
+            after: None,
+        };
+        if lock_scripts[..cursor.script_index]
+            .iter()
+            .any(|(visited_script, _, _)| visited_script == lock_script)
+        {
+            cursor = next_script;
+            continue;
+        }
+        let since = match since_source {
+            SinceSource::LockArgs(offset) => {
+                let lock_arg = lock_script.args().raw_data();
+                if lock_arg.len() < offset + 8 {
+                    return Err(format!(
+                        "Invalid since offset {} for lock args length {}",
+                        offset,
+                        lock_arg.len()
+                    ));
+                }
+                let mut since_bytes = [0u8; 8];
+                since_bytes.copy_from_slice(&lock_arg[*offset..*offset + 8]);
+                u64::from_le_bytes(since_bytes)
+            }
+            SinceSource::Value(since_value) => *since_value,
+        };
+        let page = get_cells(
+            filter.search_key(lock_script),
+            rest_limit,
+            cursor.after.take(),
+        )?;
+        let page_len = page.objects.len();
+        for cell in page.objects {
+            let cell = ckb_sdk::traits::LiveCell::from(cell);
+            if filter.matches(&cell) && is_mature(&cell, max_mature_number) {
+                sweep_cells.push(SweepCell {
+                    cell,
+                    since,
+                    placeholder_witness: placeholder_witness.clone(),
+                });
+            }
+        }
+        if page_len < rest_limit as usize {
+            cursor = next_script;
+        } else {
+            cursor.after = Some(page.last_cursor);
+        }
+    }
+    Ok((sweep_cells, None))
+}
+
+/// Collect all sweep cells of given lock scripts by indexer, page by page (`page_size` cells
+/// per page), see `collect_sweep_cells_page`.
+fn collect_sweep_cells(
+    lock_scripts: &[(Script, WitnessArgs, SinceSource)],
+    get_cells: &mut dyn FnMut(
+        SearchKey,
+        u32,
+        Option<json_types::JsonBytes>,
+    ) -> Result<Pagination<Cell>, String>,
+    max_mature_number: u64,
+    page_size: u32,
+) -> Result<Vec<SweepCell>, String> {
+    let mut sweep_cells = Vec::new();
+    let mut cursor = Some(LiveCellsCursor::default());
+    while let Some(current) = cursor {
+        let (cells, next) = collect_sweep_cells_page(
+            lock_scripts,
+            get_cells,
+            max_mature_number,
+            page_size,
+            current,
+        )?;
+        sweep_cells.extend(cells);
+        cursor = next;
+    }
+    Ok(sweep_cells)
+}
+
+/// Build the consolidate transaction of the first cells of `sweep_cells`, at most `max_inputs`
+/// cells and the transaction size is kept below `MAX_CONSOLIDATE_TX_SIZE`.
+///
+/// Return the transaction (with placeholder witnesses), the transaction fee and the number of
+/// merged cells.
+fn build_consolidate_tx(
+    sweep_cells: &[SweepCell],
+    max_inputs: usize,
+    to_lock: Script,
+    cell_dep_resolver: &dyn CellDepResolver,
+    fee_rate: u64,
+) -> Result<(TransactionView, u64, usize), String> {
+    let mut chunk_len = std::cmp::min(max_inputs, sweep_cells.len());
+    loop {
+        let (tx, fee) = build_sweep_tx(
+            &sweep_cells[..chunk_len],
+            to_lock.clone(),
+            cell_dep_resolver,
+            fee_rate,
+        )?;
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        if tx_size <= MAX_CONSOLIDATE_TX_SIZE || chunk_len <= 2 {
+            return Ok((tx, fee, chunk_len));
+        }
+        chunk_len = std::cmp::max(
+            2,
+            (chunk_len as u64 * MAX_CONSOLIDATE_TX_SIZE / tx_size) as usize,
+        );
+    }
+}
+
+/// Build a transaction which spends all given cells to one output cell, the transaction fee
+/// is subtracted from the output capacity, so no change cell is needed.
+///
+/// Return the transaction (with placeholder witnesses) and the transaction fee.
+fn build_sweep_tx(
+    sweep_cells: &[SweepCell],
+    to_lock: Script,
+    cell_dep_resolver: &dyn CellDepResolver,
+    fee_rate: u64,
+) -> Result<(TransactionView, u64), String> {
+    if sweep_cells.is_empty() {
+        return Err(String::from("No live cell found to transfer"));
+    }
+    let mut visited_scripts: Vec<Script> = Vec::new();
+    let mut cell_deps: Vec<CellDep> = Vec::new();
+    let mut inputs = Vec::new();
+    let mut witnesses = Vec::new();
+    let mut total_capacity: u64 = 0;
+    for SweepCell {
+        cell,
+        since,
+        placeholder_witness,
+    } in sweep_cells
+    {
+        let lock_script = cell.output.lock();
+        // The placeholder witness has the same size as the signed witness
+        let witness = if visited_scripts.contains(&lock_script) {
+            Bytes::new()
+        } else {
+            let cell_dep = cell_dep_resolver.resolve(&lock_script).ok_or_else(|| {
+                format!(
+                    "Can not resolve cell dep for lock script: {}",
+                    lock_script.code_hash()
+                )
+            })?;
+            if !cell_deps.contains(&cell_dep) {
+                cell_deps.push(cell_dep);
+            }
+            visited_scripts.push(lock_script);
+            placeholder_witness.as_bytes()
+        };
+        inputs.push(CellInput::new(cell.out_point.clone(), *since));
+        witnesses.push(witness.pack());
+        let capacity: u64 = cell.output.capacity().unpack();
+        total_capacity += capacity;
+    }
+
+    let output = CellOutput::new_builder().lock(to_lock).build();
+    let tx = TransactionBuilder::default()
+        .cell_deps(cell_deps)
+        .inputs(inputs)
+        .output(output.clone())
+        .output_data(Bytes::new().pack())
+        .witnesses(witnesses)
+        .build();
+    let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+    let fee = FeeRate::from_u64(fee_rate).fee(tx_size).as_u64();
+    let occupied_capacity = output
+        .occupied_capacity(Capacity::zero())
+        .expect("occupied capacity")
+        .as_u64();
+    if total_capacity < fee + occupied_capacity {
+        return Err(format!(
+            "Total capacity {} is not enough to pay transaction fee {} and create the target cell",
+            HumanCapacity(total_capacity),
+            HumanCapacity(fee),
+        ));
+    }
+    let output = output
+        .as_builder()
+        .capacity(Capacity::shannons(total_capacity - fee).pack())
+        .build();
+    let tx = tx.as_advanced_builder().set_outputs(vec![output]).build();
+    Ok((tx, fee))
+}
+
+/// One target of batch transfer (a row in `--to-file`)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferTarget {
+    pub address: String,
+    /// The capacity (unit: CKB, format: 123.335)
+    pub capacity: String,
+    /// Hex data store in target cell
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+impl TransferTarget {
+    fn resolve(&self, network_type: NetworkType) -> Result<(Address, u64, Bytes), String> {
+        let address: Address = AddressParser::default()
+            .set_network(network_type)
+            .parse(&self.address)?;
+        let capacity: u64 = CapacityParser.parse(&self.capacity)?.into();
+        let data = self
+            .data
+            .as_ref()
+            .map(|data| HexParser.parse(data))
+            .transpose()?
+            .map(Bytes::from)
+            .unwrap_or_default();
+        Ok((address, capacity, data))
+    }
+}
+
+/// Parse batch transfer targets, the content can be:
+///   * JSON array of `TransferTarget`
+///   * CSV lines of `address,capacity[,data]` (header line and `#` comments are ignored)
+pub fn parse_transfer_targets(content: &str) -> Result<Vec<TransferTarget>, String> {
+    let targets = if content.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<TransferTarget>>(content)
+            .map_err(|err| format!("Parse transfer targets json error: {}", err))?
+    } else {
+        let mut targets = Vec::new();
+        for (line_idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+            if targets.is_empty() && fields[0].eq_ignore_ascii_case("address") {
+                continue;
+            }
+            if fields.len() < 2 || fields.len() > 3 {
+                return Err(format!(
+                    "Invalid transfer target at line {}: expected `address,capacity[,data]`",
+                    line_idx + 1
+                ));
+            }
+            targets.push(TransferTarget {
+                address: fields[0].to_string(),
+                capacity: fields[1].to_string(),
+                data: fields
+                    .get(2)
+                    .filter(|data| !data.is_empty())
+                    .map(|data| data.to_string()),
+            });
+        }
+        targets
+    };
+    if targets.is_empty() {
+        return Err(String::from("No transfer target found"));
+    }
+    Ok(targets)
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -683,3 +3003,743 @@ pub struct LiveCell {
     pub info: LiveCellInfo,
     pub mature: bool,
 }
+
+impl LiveCell {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "tx_hash",
+        "output_index",
+        "capacity",
+        "type_code_hash",
+        "type_hash",
+        "data_length",
+        "block_number",
+        "mature",
+    ];
+}
+
+/// The filters of `wallet get-live-cells`, the ranges are `[start, end)`
+#[derive(Clone, Debug, Default)]
+pub struct LiveCellFilter {
+    /// `Some(None)` means the cells without type script
+    pub type_script: Option<Option<Script>>,
+    pub has_data: Option<bool>,
+    pub data_hash: Option<H256>,
+    pub capacity_range: Option<(u64, u64)>,
+    pub block_range: (u64, u64),
+}
+
+impl LiveCellFilter {
+    fn search_key(&self, lock_script: &Script) -> SearchKey {
+        let range = |start: u64, end: u64| [start.into(), end.into()];
+        let mut filter = SearchKeyFilter {
+            block_range: Some(range(self.block_range.0, self.block_range.1)),
+            output_capacity_range: self.capacity_range.map(|(start, end)| range(start, end)),
+            output_data_len_range: self.has_data.map(|has_data| {
+                if has_data {
+                    range(1, u64::max_value())
+                } else {
+                    range(0, 1)
+                }
+            }),
+            ..Default::default()
+        };
+        // The type script in filter is searched by prefix of args, exact match is checked by
+        // `matches()`
+        match self.type_script.as_ref() {
+            Some(Some(type_script)) => filter.script = Some(type_script.clone().into()),
+            Some(None) => filter.script_len_range = Some(range(0, 1)),
+            None => {}
+        }
+        SearchKey {
+            script: lock_script.clone().into(),
+            script_type: ScriptType::Lock,
+            script_search_mode: None,
+            filter: Some(filter),
+            with_data: Some(true),
+            group_by_transaction: None,
+        }
+    }
+
+    fn matches(&self, cell: &ckb_sdk::traits::LiveCell) -> bool {
+        if let Some(type_script) = self.type_script.as_ref() {
+            if &cell.output.type_().to_opt() != type_script {
+                return false;
+            }
+        }
+        if let Some(has_data) = self.has_data {
+            if cell.output_data.is_empty() == has_data {
+                return false;
+            }
+        }
+        if let Some(data_hash) = self.data_hash.as_ref() {
+            if blake2b_256(&cell.output_data) != data_hash.0 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The page cursor of `wallet get-live-cells`: the index of the lock script and the indexer
+/// cursor of that lock script (format: `{script_index}-{indexer_cursor}`)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LiveCellsCursor {
+    pub script_index: usize,
+    pub after: Option<json_types::JsonBytes>,
+}
+
+impl FromStr for LiveCellsCursor {
+    type Err = String;
+    fn from_str(input: &str) -> Result<LiveCellsCursor, String> {
+        let err = || {
+            format!(
+                "Invalid cursor: {}, format: {{script_index}}-{{indexer_cursor}}",
+                input
+            )
+        };
+        let (script_index, after) = input.split_once('-').ok_or_else(err)?;
+        let after = if after.is_empty() {
+            None
+        } else {
+            Some(json_types::JsonBytes::from_vec(
+                HexParser.parse(after).map_err(|_| err())?,
+            ))
+        };
+        Ok(LiveCellsCursor {
+            script_index: script_index.parse().map_err(|_| err())?,
+            after,
+        })
+    }
+}
+
+impl fmt::Display for LiveCellsCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let after = self
+            .after
+            .as_ref()
+            .map(|after| format!("0x{}", faster_hex::hex_string(after.as_bytes())))
+            .unwrap_or_default();
+        write!(f, "{}-{}", self.script_index, after)
+    }
+}
+
+/// The position of a transaction in chain, used as the page cursor of `wallet history`
+/// (format: `{block_number}-{tx_index}`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HistoryCursor {
+    pub block_number: u64,
+    pub tx_index: u32,
+}
+
+impl FromStr for HistoryCursor {
+    type Err = String;
+    fn from_str(input: &str) -> Result<HistoryCursor, String> {
+        let err = || {
+            format!(
+                "Invalid cursor: {}, format: {{block_number}}-{{tx_index}}",
+                input
+            )
+        };
+        let (block_number, tx_index) = input.split_once('-').ok_or_else(err)?;
+        Ok(HistoryCursor {
+            block_number: block_number.parse().map_err(|_| err())?,
+            tx_index: tx_index.parse().map_err(|_| err())?,
+        })
+    }
+}
+
+impl fmt::Display for HistoryCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.block_number, self.tx_index)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryDirection {
+    /// No capacity spent from the addresses
+    In,
+    Out,
+    /// All outputs are sent to the addresses
+    SelfTransfer,
+}
+
+impl HistoryDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HistoryDirection::In => "in",
+            HistoryDirection::Out => "out",
+            HistoryDirection::SelfTransfer => "self",
+        }
+    }
+}
+
+pub struct HistoryEntry {
+    pub tx_hash: H256,
+    pub position: HistoryCursor,
+    /// Block timestamp in milliseconds
+    pub timestamp: u64,
+    pub direction: HistoryDirection,
+    /// Total capacity of the outputs to the addresses
+    pub income: u64,
+    /// Total capacity of the inputs from the addresses
+    pub expense: u64,
+    /// Senders of incoming transaction or recipients of outgoing transaction
+    pub counterparties: Vec<Script>,
+}
+
+impl HistoryEntry {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "tx_hash",
+        "block_number",
+        "tx_index",
+        "timestamp",
+        "time",
+        "direction",
+        "income",
+        "expense",
+        "net_change",
+        "counterparties",
+    ];
+
+    fn net_change(&self) -> String {
+        if self.income >= self.expense {
+            format!("+{}", HumanCapacity::from(self.income - self.expense))
+        } else {
+            format!("-{}", HumanCapacity::from(self.expense - self.income))
+        }
+    }
+
+    fn counterparty_addresses(&self, network_type: NetworkType) -> Vec<String> {
+        self.counterparties
+            .iter()
+            .map(|script| {
+                Address::new(network_type, AddressPayload::from(script.clone()), true).to_string()
+            })
+            .collect()
+    }
+
+    fn to_json(&self, network_type: NetworkType) -> serde_json::Value {
+        serde_json::json!({
+            "tx_hash": self.tx_hash,
+            "block_number": self.position.block_number,
+            "tx_index": self.position.tx_index,
+            "timestamp": Timestamp(self.timestamp),
+            "direction": self.direction.as_str(),
+            "income": format!("{:#}", HumanCapacity::from(self.income)),
+            "expense": format!("{:#}", HumanCapacity::from(self.expense)),
+            "net_change": self.net_change(),
+            "counterparties": self.counterparty_addresses(network_type),
+        })
+    }
+
+    fn csv_row(&self, network_type: NetworkType) -> Vec<String> {
+        let time = Utc
+            .timestamp_millis_opt(self.timestamp as i64)
+            .single()
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_default();
+        vec![
+            format!("{:#x}", self.tx_hash),
+            self.position.block_number.to_string(),
+            self.position.tx_index.to_string(),
+            self.timestamp.to_string(),
+            time,
+            self.direction.as_str().to_string(),
+            HumanCapacity::from(self.income).to_string(),
+            HumanCapacity::from(self.expense).to_string(),
+            self.net_change(),
+            self.counterparty_addresses(network_type).join(" "),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::{genesis_info, sighash_lock};
+    use ckb_sdk::constants::ONE_CKB;
+
+    #[test]
+    fn test_parse_transfer_targets() {
+        let csv = "address,capacity,data\n\
+                   # comment line\n\
+                   ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v, 100.5\n\
+                   ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v,61,0x1234\n";
+        let targets = parse_transfer_targets(csv).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].capacity, "100.5");
+        assert_eq!(targets[0].data, None);
+        assert_eq!(targets[1].data, Some("0x1234".to_string()));
+
+        let json = r#"[
+            {"address": "ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v", "capacity": "100.5"},
+            {"address": "ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v", "capacity": "61", "data": "0x1234"}
+        ]"#;
+        assert_eq!(parse_transfer_targets(json).unwrap(), targets);
+
+        assert!(parse_transfer_targets("").is_err());
+        assert!(parse_transfer_targets("address,capacity\n").is_err());
+        assert!(parse_transfer_targets("ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v").is_err());
+        assert!(parse_transfer_targets(r#"[{"address": "ckt1", "amount": "1"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_history_cursor() {
+        let cursor = HistoryCursor::from_str("1024-3").unwrap();
+        assert_eq!(
+            cursor,
+            HistoryCursor {
+                block_number: 1024,
+                tx_index: 3
+            }
+        );
+        assert_eq!(cursor.to_string(), "1024-3");
+        assert!(cursor < HistoryCursor::from_str("1024-4").unwrap());
+        assert!(cursor > HistoryCursor::from_str("1023-9").unwrap());
+        assert!(HistoryCursor::from_str("1024").is_err());
+        assert!(HistoryCursor::from_str("a-1").is_err());
+    }
+
+    #[test]
+    fn test_live_cells_cursor() {
+        let cursor = LiveCellsCursor::from_str("2-0x0a0b").unwrap();
+        assert_eq!(cursor.script_index, 2);
+        assert_eq!(
+            cursor.after,
+            Some(json_types::JsonBytes::from_vec(vec![10, 11]))
+        );
+        assert_eq!(cursor.to_string(), "2-0x0a0b");
+        let cursor = LiveCellsCursor::from_str("1-").unwrap();
+        assert_eq!(cursor.after, None);
+        assert_eq!(cursor.to_string(), "1-");
+        assert!(LiveCellsCursor::from_str("0x0a0b").is_err());
+        assert!(LiveCellsCursor::from_str("1-0xzz").is_err());
+    }
+
+    #[test]
+    fn test_timelocked_address() {
+        let lock_arg = H160([7u8; 20]);
+        let address = Address::new(
+            NetworkType::Testnet,
+            AddressPayload::from_pubkey_hash(lock_arg.clone()),
+            true,
+        );
+        let config = MultisigConfig::new_with(vec![lock_arg], 0, 1).unwrap();
+
+        let since = Since::new_absolute_epoch(200);
+        let locked_address = build_timelocked_address(&address, since).unwrap();
+        assert_eq!(
+            locked_address.payload(),
+            &config.to_address_payload(Some(200))
+        );
+        assert_eq!(timelocked_address_since(&locked_address), Ok(since));
+
+        // multisig address (without since) is also supported
+        let multisig_address =
+            Address::new(address.network(), config.to_address_payload(None), true);
+        let since = SinceParser.parse("block:1000").unwrap();
+        let locked_address = build_timelocked_address(&multisig_address, since).unwrap();
+        assert_eq!(
+            &locked_address.payload().args()[0..20],
+            config.hash160().as_bytes()
+        );
+        assert_eq!(timelocked_address_since(&locked_address), Ok(since));
+        assert!(build_timelocked_address(&locked_address, since).is_err());
+        assert!(timelocked_address_since(&address).is_err());
+
+        // The multi-key config must include the sender
+        assert_eq!(
+            timelocked_multisig_config(&H160([7u8; 20]), None)
+                .unwrap()
+                .hash160(),
+            config.hash160()
+        );
+        let multi_config =
+            MultisigConfig::new_with(vec![H160([7u8; 20]), H160([8u8; 20])], 0, 2).unwrap();
+        assert_eq!(
+            timelocked_multisig_config(&H160([8u8; 20]), Some(multi_config.clone()))
+                .unwrap()
+                .hash160(),
+            multi_config.hash160()
+        );
+        assert!(timelocked_multisig_config(&H160([9u8; 20]), Some(multi_config)).is_err());
+    }
+
+    #[test]
+    fn test_bump_fee_replacement() {
+        let output = |lock: Script, capacity: u64| {
+            CellOutput::new_builder()
+                .lock(lock)
+                .capacity(capacity.pack())
+                .build()
+        };
+        let (root_lock, derived_lock, other_lock) =
+            (sighash_lock(1), sighash_lock(2), sighash_lock(3));
+        let owned_locks: HashSet<Script> = vec![root_lock.clone(), derived_lock.clone()]
+            .into_iter()
+            .collect();
+        let input_locks = vec![root_lock.clone(), derived_lock.clone()];
+        let input_total = 200 * ONE_CKB + 100_000;
+        let old_tx = TransactionBuilder::default()
+            .inputs(
+                (0..2).map(|idx| CellInput::new(packed::OutPoint::new(Default::default(), idx), 0)),
+            )
+            .output(output(other_lock.clone(), 100 * ONE_CKB))
+            .output(output(root_lock.clone(), 100 * ONE_CKB))
+            .outputs_data(vec![Bytes::new().pack(); 2])
+            .build();
+
+        // change output
+        assert_eq!(find_change_output(&old_tx, &owned_locks, None), Ok(Some(1)));
+        assert_eq!(
+            find_change_output(&old_tx, &owned_locks, Some(1)),
+            Ok(Some(1))
+        );
+        assert!(find_change_output(&old_tx, &owned_locks, Some(0)).is_err());
+        assert!(find_change_output(&old_tx, &owned_locks, Some(2)).is_err());
+        let ambiguous_tx = old_tx
+            .as_advanced_builder()
+            .output(output(derived_lock.clone(), 100 * ONE_CKB))
+            .output_data(Bytes::new().pack())
+            .build();
+        assert!(find_change_output(&ambiguous_tx, &owned_locks, None).is_err());
+        assert_eq!(
+            find_change_output(&ambiguous_tx, &owned_locks, Some(2)),
+            Ok(Some(2))
+        );
+        let no_change_tx = old_tx
+            .as_advanced_builder()
+            .set_outputs(vec![output(other_lock, 200 * ONE_CKB)])
+            .set_outputs_data(vec![Bytes::new().pack()])
+            .build();
+        assert_eq!(
+            find_change_output(&no_change_tx, &owned_locks, None),
+            Ok(None)
+        );
+        let replacement = ReplacementTx::new(
+            &no_change_tx,
+            input_locks.clone(),
+            input_total,
+            None,
+            root_lock.clone(),
+        )
+        .unwrap();
+        assert_eq!(replacement.change_idx, 1);
+        assert_eq!(replacement.tx.output(1).unwrap().lock(), root_lock);
+
+        // the change output pays the fee, each lock script group has a placeholder witness
+        let replacement = ReplacementTx::new(
+            &old_tx,
+            input_locks,
+            input_total,
+            Some(1),
+            root_lock.clone(),
+        )
+        .unwrap();
+        let (tx, fee) = replacement.build(&[], 2000).unwrap().unwrap();
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        assert_eq!(fee, FeeRate::from_u64(2000).fee(tx_size).as_u64());
+        let change_capacity: u64 = tx.output(1).unwrap().capacity().unpack();
+        assert_eq!(change_capacity, input_total - 100 * ONE_CKB - fee);
+        assert_eq!(tx.witnesses().len(), 2);
+        for witness in tx.witnesses().into_iter() {
+            let witness_args = WitnessArgs::from_slice(&witness.raw_data()).unwrap();
+            assert_eq!(witness_args.lock().to_opt().unwrap().len(), 65);
+        }
+
+        // add extra inputs when the change capacity is not enough
+        let fee_rate = 100 * ONE_CKB;
+        assert_eq!(replacement.build(&[], fee_rate), Ok(None));
+        let extra_cell = |lock: Script, idx: u32| ckb_sdk::traits::LiveCell {
+            output: output(lock, 1000 * ONE_CKB),
+            output_data: Bytes::new(),
+            out_point: packed::OutPoint::new(Default::default(), idx),
+            block_number: 0,
+            tx_index: 0,
+        };
+        let extra_cells = vec![
+            extra_cell(root_lock.clone(), 2),
+            extra_cell(sighash_lock(4), 3),
+        ];
+        let (tx, fee) = replacement.build(&extra_cells, fee_rate).unwrap().unwrap();
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        assert_eq!(fee, FeeRate::from_u64(fee_rate).fee(tx_size).as_u64());
+        let change_capacity: u64 = tx.output(1).unwrap().capacity().unpack();
+        assert_eq!(
+            change_capacity,
+            input_total + 2000 * ONE_CKB - 100 * ONE_CKB - fee
+        );
+        assert_eq!(tx.inputs().len(), 4);
+        assert!(tx.witnesses().get(2).unwrap().raw_data().is_empty());
+        assert!(!tx.witnesses().get(3).unwrap().raw_data().is_empty());
+
+        // the extra inputs are selected from the candidates, then built once
+        let candidates = (2..6)
+            .map(|idx| extra_cell(sighash_lock(4 + idx as u8), idx))
+            .collect::<Vec<_>>();
+        let (tx, fee) = replacement
+            .build_with_candidates(candidates.clone(), 3000 * ONE_CKB)
+            .unwrap()
+            .unwrap();
+        let extra_len = tx.inputs().len() - 2;
+        assert!(extra_len > 1 && extra_len < candidates.len());
+        assert_eq!(
+            replacement.build(&candidates[..extra_len - 1], 3000 * ONE_CKB),
+            Ok(None)
+        );
+        let change_capacity: u64 = tx.output(1).unwrap().capacity().unpack();
+        assert_eq!(
+            change_capacity,
+            input_total + 1000 * ONE_CKB * extra_len as u64 - 100 * ONE_CKB - fee
+        );
+        assert_eq!(
+            replacement.build_with_candidates(candidates, 100_000 * ONE_CKB),
+            Ok(None)
+        );
+
+        // the change to a derived change address is reduced, even if all inputs are locked
+        // by the root key
+        let derived_change_tx = old_tx
+            .as_advanced_builder()
+            .set_outputs(vec![
+                output(sighash_lock(3), 100 * ONE_CKB),
+                output(derived_lock.clone(), 100 * ONE_CKB),
+            ])
+            .build();
+        let change_idx = find_change_output(&derived_change_tx, &owned_locks, None).unwrap();
+        assert_eq!(change_idx, Some(1));
+        let replacement = ReplacementTx::new(
+            &derived_change_tx,
+            vec![root_lock.clone(), root_lock.clone()],
+            input_total,
+            change_idx,
+            root_lock.clone(),
+        )
+        .unwrap();
+        let (tx, fee) = replacement.build(&[], 2000).unwrap().unwrap();
+        assert_eq!(tx.outputs().len(), 2);
+        assert_eq!(tx.output(1).unwrap().lock(), derived_lock);
+        let change_capacity: u64 = tx.output(1).unwrap().capacity().unpack();
+        assert_eq!(change_capacity, input_total - 100 * ONE_CKB - fee);
+
+        // RBF rule of tx-pool
+        assert!(check_rbf_fee(1000, 1999, 1000, 1000).is_err());
+        assert!(check_rbf_fee(1000, 2000, 1000, 1000).is_ok());
+    }
+
+    fn sweep_cell(lock: Script, capacity: u64, index: u32) -> SweepCell {
+        SweepCell {
+            cell: ckb_sdk::traits::LiveCell {
+                output: CellOutput::new_builder()
+                    .lock(lock)
+                    .capacity(capacity.pack())
+                    .build(),
+                output_data: Bytes::new(),
+                out_point: packed::OutPoint::new(Default::default(), index),
+                block_number: 1,
+                tx_index: 1,
+            },
+            since: 0,
+            placeholder_witness: WitnessArgs::new_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .build(),
+        }
+    }
+
+    /// The indexer `get_cells` of the cells of one lock script, the cursor is the position of
+    /// the last returned cell
+    fn indexer_page(
+        lock_cells: &[ckb_sdk::traits::LiveCell],
+        limit: u32,
+        after: Option<json_types::JsonBytes>,
+    ) -> Pagination<Cell> {
+        let start = after
+            .map(|after| after.as_bytes()[0] as usize + 1)
+            .unwrap_or(0);
+        let end = std::cmp::min(start + limit as usize, lock_cells.len());
+        let objects = lock_cells[start..end]
+            .iter()
+            .map(|cell| Cell {
+                output: cell.output.clone().into(),
+                output_data: Some(json_types::JsonBytes::from_bytes(cell.output_data.clone())),
+                out_point: cell.out_point.clone().into(),
+                block_number: cell.block_number.into(),
+                tx_index: cell.tx_index.into(),
+            })
+            .collect();
+        Pagination {
+            objects,
+            last_cursor: json_types::JsonBytes::from_vec(vec![end as u8 - 1]),
+        }
+    }
+
+    #[test]
+    fn test_collect_sweep_cells_page() {
+        let (lock_a, lock_b) = (sighash_lock(1), sighash_lock(2));
+        let mut cells: HashMap<Script, Vec<ckb_sdk::traits::LiveCell>> = HashMap::new();
+        for index in 0..5 {
+            cells
+                .entry(lock_a.clone())
+                .or_default()
+                .push(sweep_cell(lock_a.clone(), 100, index).cell);
+        }
+        let mut cell_with_data = sweep_cell(lock_b.clone(), 100, 5).cell;
+        cell_with_data.output_data = Bytes::from(vec![1]);
+        let mut immature_cellbase = sweep_cell(lock_b.clone(), 100, 6).cell;
+        immature_cellbase.tx_index = 0;
+        immature_cellbase.block_number = 100;
+        cells.insert(
+            lock_b.clone(),
+            vec![
+                sweep_cell(lock_b.clone(), 100, 7).cell,
+                cell_with_data,
+                immature_cellbase,
+            ],
+        );
+        let mut get_cells = |search_key: SearchKey, limit: u32, after| {
+            Ok(indexer_page(
+                &cells[&Script::from(search_key.script)],
+                limit,
+                after,
+            ))
+        };
+        let placeholder_witness = sweep_cell(lock_a.clone(), 0, 0).placeholder_witness;
+        let lock_scripts = [&lock_a, &lock_b, &lock_a]
+            .iter()
+            .map(|lock| {
+                (
+                    (*lock).clone(),
+                    placeholder_witness.clone(),
+                    SinceSource::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut pages = Vec::new();
+        let mut cursor = Some(LiveCellsCursor::default());
+        while let Some(current) = cursor {
+            let (page, next) =
+                collect_sweep_cells_page(&lock_scripts, &mut get_cells, 50, 3, current).unwrap();
+            assert!(page.len() <= 3);
+            pages.push(page);
+            cursor = next;
+        }
+        let out_points = pages
+            .iter()
+            .flatten()
+            .map(|sweep_cell| Unpack::<u32>::unpack(&sweep_cell.cell.out_point.index()))
+            .collect::<Vec<_>>();
+        // The cells with data, the immature cellbase and the duplicated lock script are skipped
+        assert_eq!(out_points, vec![0, 1, 2, 3, 4, 7]);
+        assert_eq!(pages[0].len(), 3);
+    }
+
+    #[test]
+    fn test_consolidate_tx() {
+        let genesis_info = genesis_info();
+        let resolver = &genesis_info.cell_dep_resolver;
+        let (from_lock, to_lock) = (sighash_lock(1), sighash_lock(2));
+        let cells = (0..5)
+            .map(|index| sweep_cell(from_lock.clone(), 100 * ONE_CKB, index))
+            .collect::<Vec<_>>();
+
+        // The fee is paid by the only output, only the first witness of the lock group is set
+        let (tx, fee) = build_sweep_tx(&cells[..2], to_lock.clone(), resolver, 1000).unwrap();
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        assert_eq!(fee, FeeRate::from_u64(1000).fee(tx_size).as_u64());
+        assert_eq!(tx.cell_deps().len(), 1);
+        assert_eq!(tx.outputs().len(), 1);
+        assert_eq!(tx.output(0).unwrap().lock(), to_lock);
+        let capacity: u64 = tx.output(0).unwrap().capacity().unpack();
+        assert_eq!(capacity, 200 * ONE_CKB - fee);
+        assert_eq!(tx.witnesses().get(0).unwrap().raw_data().len(), 85);
+        assert!(tx.witnesses().get(1).unwrap().raw_data().is_empty());
+        assert!(build_sweep_tx(&[], to_lock.clone(), resolver, 1000).is_err());
+        assert!(build_sweep_tx(&cells[..1], to_lock.clone(), resolver, 1000 * ONE_CKB).is_err());
+
+        // Chunked by <max-inputs>
+        let (tx, _, chunk_len) =
+            build_consolidate_tx(&cells, 3, to_lock.clone(), resolver, 1000).unwrap();
+        assert_eq!(chunk_len, 3);
+        assert_eq!(tx.inputs().len(), 3);
+        let (_, _, chunk_len) =
+            build_consolidate_tx(&cells[3..], 3, to_lock.clone(), resolver, 1000).unwrap();
+        assert_eq!(chunk_len, 2);
+
+        // Chunked by the transaction size
+        let cells = (0..12000)
+            .map(|index| sweep_cell(from_lock.clone(), 100 * ONE_CKB, index))
+            .collect::<Vec<_>>();
+        let (tx, _, chunk_len) =
+            build_consolidate_tx(&cells, cells.len(), to_lock, resolver, 1000).unwrap();
+        assert!(chunk_len < cells.len());
+        assert_eq!(tx.inputs().len(), chunk_len);
+        assert!(tx.data().as_reader().serialized_size_in_block() as u64 <= MAX_CONSOLIDATE_TX_SIZE);
+    }
+
+    #[test]
+    fn test_transfer_all() {
+        let genesis_info = genesis_info();
+        let resolver = &genesis_info.cell_dep_resolver;
+        // The root lock, a derived receiving lock and a derived change lock
+        let locks = vec![sighash_lock(1), sighash_lock(2), sighash_lock(3)];
+        let to_lock = sighash_lock(4);
+        let mut cells: HashMap<Script, Vec<ckb_sdk::traits::LiveCell>> = HashMap::new();
+        let mut index = 0;
+        for (lock, count) in locks.iter().zip([1, 3, 2].iter()) {
+            for _ in 0..*count {
+                cells
+                    .entry(lock.clone())
+                    .or_default()
+                    .push(sweep_cell(lock.clone(), 100 * ONE_CKB, index).cell);
+                index += 1;
+            }
+        }
+        let mut get_cells = |search_key: SearchKey, limit: u32, after| {
+            Ok(indexer_page(
+                &cells[&Script::from(search_key.script)],
+                limit,
+                after,
+            ))
+        };
+        let placeholder_witness = sweep_cell(to_lock.clone(), 0, 0).placeholder_witness;
+        let lock_scripts = locks
+            .iter()
+            .map(|lock| {
+                (
+                    lock.clone(),
+                    placeholder_witness.clone(),
+                    SinceSource::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // All cells of the derived locks are collected across the pages
+        let sweep_cells = collect_sweep_cells(&lock_scripts, &mut get_cells, 50, 2).unwrap();
+        let out_points = sweep_cells
+            .iter()
+            .map(|sweep_cell| Unpack::<u32>::unpack(&sweep_cell.cell.out_point.index()))
+            .collect::<Vec<_>>();
+        assert_eq!(out_points, (0..6).collect::<Vec<_>>());
+
+        // All capacity minus the fee goes to the only output, no change output
+        let (tx, fee) = build_sweep_tx(&sweep_cells, to_lock.clone(), resolver, 1000).unwrap();
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        assert_eq!(fee, FeeRate::from_u64(1000).fee(tx_size).as_u64());
+        assert_eq!(tx.inputs().len(), 6);
+        assert_eq!(tx.outputs().len(), 1);
+        assert_eq!(tx.output(0).unwrap().lock(), to_lock);
+        let capacity: u64 = tx.output(0).unwrap().capacity().unpack();
+        assert_eq!(capacity, 600 * ONE_CKB - fee);
+        // One placeholder witness for each lock group, sharing the sighash cell dep
+        assert_eq!(tx.cell_deps().len(), 1);
+        let signed_witnesses = tx
+            .witnesses()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, witness)| !witness.raw_data().is_empty())
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        assert_eq!(signed_witnesses, vec![0, 1, 4]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ckb_sdk::NetworkType;
+use serde::{Deserialize, Serialize};
+
+use super::cli_file::CliJsonFile;
+
+pub const ADDRESS_BOOK_FILENAME: &str = "address-book.json";
+/// The prefix to reference an address book entry in address arguments (`@name`)
+pub const ALIAS_PREFIX: char = '@';
+
+// The address arguments are resolved by the argument parsers, which have no other way to find
+// the `$CKB_CLI_HOME` directory
+static ADDRESS_BOOK_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the address book file location, must be called before any address argument is parsed
+pub fn init(ckb_cli_dir: &Path) {
+    let _ = ADDRESS_BOOK_DIR.set(ckb_cli_dir.to_path_buf());
+}
+
+fn address_book_file() -> Option<CliJsonFile> {
+    ADDRESS_BOOK_DIR
+        .get()
+        .map(|dir| CliJsonFile::new(dir, ADDRESS_BOOK_FILENAME, "address book"))
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AddressBookEntry {
+    pub name: String,
+    pub address: String,
+    /// The network type string (ckb, ckb_testnet, ckb_staging, ckb_dev) the alias is pinned to
+    pub network: String,
+}
+
+/// Named addresses stored in `$CKB_CLI_HOME/address-book.json`
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AddressBook {
+    entries: BTreeMap<String, AddressBookEntry>,
+}
+
+impl AddressBook {
+    /// Load the address book, an empty book is returned if the file not exists
+    pub fn load() -> Result<AddressBook, String> {
+        address_book_file()
+            .map(|file| file.load())
+            .unwrap_or_else(|| Ok(AddressBook::default()))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        address_book_file()
+            .ok_or_else(|| "The address book location is not initialized".to_string())?
+            .save(self)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AddressBookEntry> {
+        self.entries.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AddressBookEntry> {
+        self.entries.get(name)
+    }
+
+    pub fn add(&mut self, name: &str, address: String, network: NetworkType) -> Result<(), String> {
+        check_name(name)?;
+        if self.entries.contains_key(name) {
+            return Err(format!("Address book entry already exists: {}", name));
+        }
+        self.entries.insert(
+            name.to_string(),
+            AddressBookEntry {
+                name: name.to_string(),
+                address,
+                network: network.to_str().to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<AddressBookEntry, String> {
+        self.entries
+            .remove(name)
+            .ok_or_else(|| format!("Address book entry not found: {}", name))
+    }
+
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        check_name(new_name)?;
+        if self.entries.contains_key(new_name) {
+            return Err(format!("Address book entry already exists: {}", new_name));
+        }
+        let mut entry = self.remove(name)?;
+        entry.name = new_name.to_string();
+        self.entries.insert(new_name.to_string(), entry);
+        Ok(())
+    }
+
+    /// Get the address string of an alias, the alias can only be used on the pinned network
+    pub fn resolve(&self, name: &str, network: Option<NetworkType>) -> Result<&str, String> {
+        let entry = self
+            .get(name)
+            .ok_or_else(|| format!("Address book entry not found: {}", name))?;
+        if let Some(network) = network {
+            if entry.network != network.to_str() {
+                return Err(format!(
+                    "Address book entry {}{} is pinned to network {}, current network: {}",
+                    ALIAS_PREFIX, name, entry.network, network
+                ));
+            }
+        }
+        if entry.address.starts_with(ALIAS_PREFIX) {
+            return Err(format!(
+                "Invalid address of address book entry {}: {}",
+                name, entry.address
+            ));
+        }
+        Ok(entry.address.as_str())
+    }
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(format!(
+            "Invalid address book entry name: {:?}, only letters, digits, `_`, `-` and `.` are allowed",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_book() {
+        let address = "ckt1qyqt8xaupvm8837nv3gtc9x0ekkj64vud3jqfwyw5v".to_string();
+        let mut book = AddressBook::default();
+        book.add("alice", address.clone(), NetworkType::Testnet)
+            .unwrap();
+        assert!(book
+            .add("alice", address.clone(), NetworkType::Testnet)
+            .is_err());
+        assert!(book
+            .add("bad name", address.clone(), NetworkType::Dev)
+            .is_err());
+        assert!(book.add("", address.clone(), NetworkType::Dev).is_err());
+
+        assert_eq!(
+            book.resolve("alice", Some(NetworkType::Testnet)),
+            Ok(address.as_str())
+        );
+        assert_eq!(book.resolve("alice", None), Ok(address.as_str()));
+        // pinned to testnet, can not be used on other networks with same prefix
+        assert!(book.resolve("alice", Some(NetworkType::Dev)).is_err());
+        assert!(book.resolve("bob", None).is_err());
+
+        book.rename("alice", "bob").unwrap();
+        assert!(book.get("alice").is_none());
+        assert_eq!(book.get("bob").unwrap().name, "bob");
+        let content = serde_json::to_string(&book).unwrap();
+        assert_eq!(serde_json::from_str::<AddressBook>(&content).unwrap(), book);
+
+        book.remove("bob").unwrap();
+        assert!(book.remove("bob").is_err());
+        assert_eq!(book.entries().count(), 0);
+    }
+}
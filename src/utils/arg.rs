@@ -2,8 +2,11 @@ use crate::utils::arg_parser::{
     AddressParser, ArgParser, CapacityParser, FilePathParser, FixedHashParser, FromStrParser,
     HexParser, OutPointParser, PrivkeyPathParser, PubkeyHexParser,
 };
+use crate::utils::coin_selection::CoinSelection;
+use crate::utils::fee_rate::{FeeRateSpec, DEFAULT_MAX_FEE_RATE};
 use ckb_types::H160;
 use clap::Arg;
+use std::sync::OnceLock;
 
 pub fn privkey_path<'a>() -> Arg<'a> {
     Arg::with_name("privkey-path")
@@ -88,6 +91,23 @@ pub fn from_account<'a>() -> Arg<'a> {
         .about("The account's lock-arg or sighash address (transfer from this account)")
 }
 
+pub fn account<'a>() -> Arg<'a> {
+    Arg::with_name("account")
+        .long("account")
+        .takes_value(true)
+        .validator(|input| {
+            FixedHashParser::<H160>::default()
+                .validate(input)
+                .or_else(|err| {
+                    AddressParser::default()
+                        .validate(input)
+                        .and_then(|()| AddressParser::new_sighash().validate(input))
+                        .map_err(|_| err)
+                })
+        })
+        .about("The account's lock-arg or sighash address")
+}
+
 pub fn from_locked_address<'a>() -> Arg<'a> {
     Arg::with_name("from-locked-address")
         .long("from-locked-address")
@@ -128,13 +148,32 @@ pub fn capacity<'a>() -> Arg<'a> {
         .about("The capacity (unit: CKB, format: 123.335)")
 }
 
+pub fn coin_selection<'a>() -> Arg<'a> {
+    Arg::with_name("coin-selection")
+        .long("coin-selection")
+        .takes_value(true)
+        .default_value("default")
+        .possible_values(CoinSelection::NAMES)
+        .about("The strategy to select input cells: default (indexer order), largest-first, smallest-first, oldest-first, branch-and-bound (exact match, fallback to largest-first)")
+}
+
 pub fn fee_rate<'a>() -> Arg<'a> {
     Arg::with_name("fee-rate")
         .long("fee-rate")
         .takes_value(true)
-        .validator(|input| FromStrParser::<u64>::default().validate(input))
+        .validator(|input| FromStrParser::<FeeRateSpec>::new().validate(input))
         .default_value("1000")
-        .about("The transaction fee rate (unit: shannons/KB)")
+        .about("The transaction fee rate (unit: shannons/KB), or `auto[:target_blocks]` to pick the median fee rate of recent blocks (target_blocks: 1 ~ 101), not lower than the minimal fee rate of tx-pool and not higher than --max-fee-rate")
+}
+
+pub fn max_fee_rate<'a>() -> Arg<'a> {
+    static DEFAULT_VALUE: OnceLock<String> = OnceLock::new();
+    Arg::with_name("max-fee-rate")
+        .long("max-fee-rate")
+        .takes_value(true)
+        .validator(|input| FromStrParser::<u64>::default().validate(input))
+        .default_value(DEFAULT_VALUE.get_or_init(|| DEFAULT_MAX_FEE_RATE.to_string()))
+        .about("The ceiling of the fee rate picked by `--fee-rate auto` (unit: shannons/KB)")
 }
 
 /// create an Arg object to receive value of force_small_change_as_fee for CapacityBalancer
@@ -147,6 +186,16 @@ pub fn max_tx_fee<'a>() -> Arg<'a> {
         .about("When there is no more inputs for create a change cell to balance the transaction capacity, force the addition capacity as fee, the value is actual maximum transaction fee(unit CKB, example:0.001)")
 }
 
+/// The max transaction fee checked before sending a transaction
+pub fn max_send_tx_fee<'a>() -> Arg<'a> {
+    Arg::with_name("max-tx-fee")
+        .long("max-tx-fee")
+        .takes_value(true)
+        .default_value("1.0")
+        .validator(|input| CapacityParser.validate(input))
+        .about("Max transaction fee (unit: CKB)")
+}
+
 pub fn live_cells_limit<'a>() -> Arg<'a> {
     Arg::with_name("limit")
         .long("limit")
@@ -179,3 +228,9 @@ pub fn out_point<'a>() -> Arg<'a> {
         .validator(|input| { OutPointParser.validate(input) })
         .about("out-point to specify a cell. Example: 0xd56ed5d4e8984701714de9744a533413f79604b3b91461e2265614829d2005d1-1")
 }
+
+pub fn skip_lint<'a>() -> Arg<'a> {
+    Arg::with_name("skip-lint")
+        .long("skip-lint")
+        .about("Send the transaction without the pre-flight lint checks (see `tx lint`)")
+}
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
+use chrono::DateTime;
 use clap::ArgMatches;
 use faster_hex::hex_decode;
 use url::Url;
@@ -14,12 +15,21 @@ use url::Url;
 use ckb_sdk::{
     constants::{MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH},
     util::zeroize_privkey,
-    Address, AddressPayload, HumanCapacity, NetworkType, OldAddress, ScriptId,
+    Address, AddressPayload, HumanCapacity, NetworkType, OldAddress, ScriptId, Since, SinceType,
 };
 use ckb_signer::MasterPrivKey;
-use ckb_types::{core::ScriptHashType, packed::OutPoint, prelude::*, H160, H256};
+use ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, ScriptHashType},
+    packed::{OutPoint, Script},
+    prelude::*,
+    H160, H256,
+};
 
-use crate::utils::cell_dep::CellDeps;
+use crate::utils::{
+    address_book::{AddressBook, ALIAS_PREFIX},
+    cell_dep::CellDeps,
+};
 
 #[allow(clippy::wrong_self_convention)]
 pub trait ArgParser<T> {
@@ -122,6 +132,42 @@ where
     }
 }
 
+/// Format: `{a}:{b}`, split at the first `:`
+pub struct PairParser<TA, TB, A, B> {
+    a: A,
+    b: B,
+    _ta: PhantomData<TA>,
+    _tb: PhantomData<TB>,
+}
+
+impl<TA, TB, A, B> PairParser<TA, TB, A, B>
+where
+    A: ArgParser<TA>,
+    B: ArgParser<TB>,
+{
+    pub fn new(a: A, b: B) -> Self {
+        PairParser {
+            a,
+            b,
+            _ta: PhantomData,
+            _tb: PhantomData,
+        }
+    }
+}
+
+impl<TA, TB, A, B> ArgParser<(TA, TB)> for PairParser<TA, TB, A, B>
+where
+    A: ArgParser<TA>,
+    B: ArgParser<TB>,
+{
+    fn parse(&self, input: &str) -> Result<(TA, TB), String> {
+        let (a, b) = input
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid pair: {}, format: {{a}}:{{b}}", input))?;
+        Ok((self.a.parse(a)?, self.b.parse(b)?))
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct FromStrParser<T: FromStr> {
     _t: PhantomData<T>,
@@ -404,6 +450,11 @@ impl AddressParser {
 
 impl ArgParser<Address> for AddressParser {
     fn parse(&self, input: &str) -> Result<Address, String> {
+        if let Some(name) = input.strip_prefix(ALIAS_PREFIX) {
+            let address_book = AddressBook::load()?;
+            let address = address_book.resolve(name, self.network)?;
+            return self.parse(address);
+        }
         if let Ok(address) = Address::from_str(input) {
             if matches!(address.network(), NetworkType::Staging | NetworkType::Dev)
                 && address.payload().is_short_acp()
@@ -525,6 +576,32 @@ impl ArgParser<ScriptId> for ScriptIdParser {
     }
 }
 
+/// Format: `{code_hash}-{hash_type}[-{args}]`, the args is empty if omitted
+pub struct ScriptParser;
+
+impl ArgParser<Script> for ScriptParser {
+    fn parse(&self, input: &str) -> Result<Script, String> {
+        let parts = input.splitn(3, '-').collect::<Vec<_>>();
+        if parts.len() < 2 {
+            return Err(format!(
+                "Invalid script: {}, format: {{code_hash}}-{{hash_type}}[-{{args}}], `hash_type` can be: [type, data, data1]",
+                input
+            ));
+        }
+        let script_id = ScriptIdParser.parse(&format!("{}-{}", parts[0], parts[1]))?;
+        let args = parts
+            .get(2)
+            .map(|args| HexParser.parse(args))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Script::new_builder()
+            .code_hash(script_id.code_hash.pack())
+            .hash_type(script_id.hash_type.into())
+            .args(Bytes::from(args).pack())
+            .build())
+    }
+}
+
 pub struct DurationParser;
 
 impl ArgParser<Duration> for DurationParser {
@@ -551,6 +628,78 @@ impl ArgParser<Duration> for DurationParser {
     }
 }
 
+// The block number and timestamp of a since value are stored in the lower 56 bits
+const SINCE_VALUE_MAX: u64 = (1 << 56) - 1;
+
+/// Parse absolute since value, format: `epoch:{number}[.{index}/{length}]`,
+/// `timestamp:{unix seconds or RFC3339 datetime}` or `block:{number}`
+pub struct SinceParser;
+
+impl ArgParser<Since> for SinceParser {
+    fn parse(&self, input: &str) -> Result<Since, String> {
+        let (metric, value) = input.split_once(':').ok_or_else(|| {
+            format!(
+                "Invalid since value: {}, format: epoch:{{number}}[.{{index}}/{{length}}], timestamp:{{unix seconds or RFC3339 datetime}} or block:{{number}}",
+                input
+            )
+        })?;
+        match metric {
+            "epoch" => {
+                let (number, index, length) =
+                    if let Some((number, fraction)) = value.split_once('.') {
+                        let (index, length) = fraction
+                            .split_once('/')
+                            .ok_or_else(|| format!("Invalid epoch fraction: {}", fraction))?;
+                        (
+                            FromStrParser::<u64>::default().parse(number)?,
+                            FromStrParser::<u64>::default().parse(index)?,
+                            FromStrParser::<u64>::default().parse(length)?,
+                        )
+                    } else {
+                        (FromStrParser::<u64>::default().parse(value)?, 0, 1)
+                    };
+                if length == 0 || index >= length {
+                    return Err(format!("Invalid epoch fraction: {}/{}", index, length));
+                }
+                if number > 0xFF_FFFF || length > 0xFFFF {
+                    return Err(format!("Epoch value out of range: {}", value));
+                }
+                let epoch = EpochNumberWithFraction::new(number, index, length);
+                Ok(Since::new(
+                    SinceType::EpochNumberWithFraction,
+                    epoch.full_value(),
+                    false,
+                ))
+            }
+            "timestamp" => {
+                let seconds = if let Ok(seconds) = value.parse::<u64>() {
+                    seconds
+                } else {
+                    let datetime = DateTime::parse_from_rfc3339(value)
+                        .map_err(|err| format!("Invalid timestamp {}: {}", value, err))?;
+                    u64::try_from(datetime.timestamp())
+                        .map_err(|_| format!("Invalid timestamp: {}", value))?
+                };
+                if seconds > SINCE_VALUE_MAX {
+                    return Err(format!("Timestamp value out of range: {}", value));
+                }
+                Ok(Since::new(SinceType::Timestamp, seconds, false))
+            }
+            "block" => {
+                let number = FromStrParser::<u64>::default().parse(value)?;
+                if number > SINCE_VALUE_MAX {
+                    return Err(format!("Block number out of range: {}", value));
+                }
+                Ok(Since::new(SinceType::BlockNumber, number, false))
+            }
+            _ => Err(format!(
+                "Invalid since type: {}, expected one of: epoch, timestamp, block",
+                metric
+            )),
+        }
+    }
+}
+
 pub struct SocketParser;
 
 impl ArgParser<::std::net::SocketAddr> for SocketParser {
@@ -583,6 +732,67 @@ mod tests {
         assert!(FromStrParser::<u64>::default().parse("3x").is_err());
     }
 
+    #[test]
+    fn test_since() {
+        let since = SinceParser.parse("epoch:100").unwrap();
+        assert_eq!(since, Since::new_absolute_epoch(100));
+        let since = SinceParser.parse("epoch:100.5/1800").unwrap();
+        assert_eq!(
+            since.extract_metric(),
+            Some((
+                SinceType::EpochNumberWithFraction,
+                EpochNumberWithFraction::new(100, 5, 1800).full_value()
+            ))
+        );
+        let since = SinceParser.parse("block:12345").unwrap();
+        assert_eq!(
+            since.extract_metric(),
+            Some((SinceType::BlockNumber, 12345))
+        );
+        let since = SinceParser.parse("timestamp:1700000000").unwrap();
+        assert!(since.is_absolute());
+        assert_eq!(
+            since.extract_metric(),
+            Some((SinceType::Timestamp, 1700000000))
+        );
+        assert_eq!(
+            SinceParser.parse("timestamp:2023-11-14T22:13:20Z"),
+            Ok(since)
+        );
+
+        assert!(SinceParser.parse("100").is_err());
+        assert!(SinceParser.parse("epoch:100.5").is_err());
+        assert!(SinceParser.parse("epoch:100.1800/1800").is_err());
+        assert!(SinceParser.parse("height:100").is_err());
+        assert!(SinceParser.parse("timestamp:yesterday").is_err());
+
+        // The value is limited to 56 bits
+        let max_value = (1u64 << 56) - 1;
+        assert_eq!(
+            SinceParser
+                .parse(&format!("block:{}", max_value))
+                .unwrap()
+                .extract_metric(),
+            Some((SinceType::BlockNumber, max_value))
+        );
+        assert!(SinceParser
+            .parse(&format!("block:{}", max_value + 1))
+            .is_err());
+        assert!(SinceParser
+            .parse(&format!("timestamp:{}", max_value + 1))
+            .is_err());
+        assert!(SinceParser.parse("epoch:16777216").is_err());
+    }
+
+    #[test]
+    fn test_pair() {
+        let parser = PairParser::new(FromStrParser::<usize>::default(), HexParser);
+        assert_eq!(parser.parse("2:0x3a66"), Ok((2, vec![0x3a, 0x66])));
+        assert!(parser.parse("2").is_err());
+        assert!(parser.parse("x:0x3a").is_err());
+        assert!(parser.parse("2:0x3a:66").is_err());
+    }
+
     #[test]
     fn test_hex() {
         assert_eq!(HexParser.parse("0x3a"), Ok(vec![0x3a]));
@@ -592,6 +802,26 @@ mod tests {
         assert!(HexParser.parse("abcdefghi").is_err());
     }
 
+    #[test]
+    fn test_script() {
+        let code_hash = h256!("0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8");
+        let script = ScriptParser
+            .parse(&format!("{:#x}-type-0x0102", code_hash))
+            .unwrap();
+        assert_eq!(script.code_hash(), code_hash.pack());
+        assert_eq!(script.hash_type(), ScriptHashType::Type.into());
+        assert_eq!(script.args().raw_data(), Bytes::from(vec![1u8, 2]));
+        let script = ScriptParser
+            .parse(&format!("{:#x}-data1", code_hash))
+            .unwrap();
+        assert_eq!(script.hash_type(), ScriptHashType::Data1.into());
+        assert!(script.args().raw_data().is_empty());
+        assert!(ScriptParser.parse(&format!("{:#x}", code_hash)).is_err());
+        assert!(ScriptParser
+            .parse(&format!("{:#x}-type-0x010", code_hash))
+            .is_err());
+    }
+
     #[test]
     fn test_fixed_hash() {
         assert_eq!(
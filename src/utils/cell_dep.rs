@@ -28,6 +28,11 @@ pub enum CellDepName {
     Cheque,
     /// Simple UDT
     Sudt,
+    /// Omnilock
+    Omnilock,
+    /// The new multisig script (data1)
+    #[serde(rename = "multisig_v2")]
+    MultisigV2,
 }
 impl fmt::Display for CellDepName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -35,6 +40,8 @@ impl fmt::Display for CellDepName {
             CellDepName::Acp => "acp",
             CellDepName::Cheque => "cheque",
             CellDepName::Sudt => "sudt",
+            CellDepName::Omnilock => "omnilock",
+            CellDepName::MultisigV2 => "multisig_v2",
         };
         write!(f, "{}", output)
     }
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A JSON file in `$CKB_CLI_HOME`
+pub struct CliJsonFile {
+    path: PathBuf,
+    /// Used in error messages
+    name: &'static str,
+}
+
+impl CliJsonFile {
+    pub fn new(ckb_cli_dir: &Path, filename: &str, name: &'static str) -> CliJsonFile {
+        CliJsonFile {
+            path: ckb_cli_dir.join(filename),
+            name,
+        }
+    }
+
+    /// Load the content, the default value is returned if the file not exists
+    pub fn load<T: DeserializeOwned + Default>(&self) -> Result<T, String> {
+        if !self.path.exists() {
+            return Ok(T::default());
+        }
+        let content = fs::read_to_string(&self.path)
+            .map_err(|err| format!("Read {} {} failed: {}", self.name, self.path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| {
+            format!(
+                "Parse {} {} failed: {}",
+                self.name,
+                self.path.display(),
+                err
+            )
+        })
+    }
+
+    pub fn save<T: Serialize>(&self, value: &T) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+        fs::write(&self.path, content).map_err(|err| {
+            format!(
+                "Write {} {} failed: {}",
+                self.name,
+                self.path.display(),
+                err
+            )
+        })
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ckb_sdk::{
+    traits::{CellCollector, CellCollectorError, CellQueryOptions, DefaultCellCollector, LiveCell},
+    CkbRpcClient,
+};
+use ckb_types::{
+    packed::{OutPoint, Transaction},
+    prelude::*,
+};
+
+// Max search steps of branch-and-bound before fallback to largest-first
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// The strategy to select input cells for balancing a transaction
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum CoinSelection {
+    /// Use cells in the order returned by the indexer
+    #[default]
+    Default,
+    LargestFirst,
+    SmallestFirst,
+    /// Use cells with smaller block number first
+    OldestFirst,
+    /// Search cells which total capacity exactly match the target (within a tolerance),
+    /// fallback to largest-first when not found.
+    BranchAndBound,
+}
+
+impl CoinSelection {
+    pub const NAMES: &'static [&'static str] = &[
+        "default",
+        "largest-first",
+        "smallest-first",
+        "oldest-first",
+        "branch-and-bound",
+    ];
+}
+
+impl FromStr for CoinSelection {
+    type Err = String;
+    fn from_str(input: &str) -> Result<CoinSelection, String> {
+        match input {
+            "default" => Ok(CoinSelection::Default),
+            "largest-first" => Ok(CoinSelection::LargestFirst),
+            "smallest-first" => Ok(CoinSelection::SmallestFirst),
+            "oldest-first" => Ok(CoinSelection::OldestFirst),
+            "branch-and-bound" => Ok(CoinSelection::BranchAndBound),
+            _ => Err(format!(
+                "Invalid coin selection strategy: {}, expected one of: {}",
+                input,
+                CoinSelection::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CoinSelection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CoinSelection::Default => "default",
+            CoinSelection::LargestFirst => "largest-first",
+            CoinSelection::SmallestFirst => "smallest-first",
+            CoinSelection::OldestFirst => "oldest-first",
+            CoinSelection::BranchAndBound => "branch-and-bound",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn cell_capacity(cell: &LiveCell) -> u64 {
+    cell.output.capacity().unpack()
+}
+
+/// Select cells from `cells` until the total capacity reach `target`. If all cells are not
+/// enough, all cells are returned.
+///
+/// The `tolerance` is only used by branch-and-bound: a selection with total capacity in range
+/// `[target, target + tolerance]` is treated as an exact match.
+pub fn select_cells(
+    mut cells: Vec<LiveCell>,
+    target: u64,
+    strategy: CoinSelection,
+    tolerance: u64,
+) -> Vec<LiveCell> {
+    match strategy {
+        CoinSelection::Default => {}
+        CoinSelection::LargestFirst => {
+            cells.sort_by_key(|cell| std::cmp::Reverse(cell_capacity(cell)));
+        }
+        CoinSelection::SmallestFirst => {
+            cells.sort_by_key(cell_capacity);
+        }
+        CoinSelection::OldestFirst => {
+            cells.sort_by_key(|cell| {
+                let index: u32 = cell.out_point.index().unpack();
+                (cell.block_number, cell.tx_index, index)
+            });
+        }
+        CoinSelection::BranchAndBound => {
+            cells.sort_by_key(|cell| std::cmp::Reverse(cell_capacity(cell)));
+            let capacities = cells.iter().map(cell_capacity).collect::<Vec<_>>();
+            if let Some(selected) = branch_and_bound(&capacities, target, tolerance) {
+                return cells
+                    .into_iter()
+                    .zip(selected)
+                    .filter_map(|(cell, selected)| if selected { Some(cell) } else { None })
+                    .collect();
+            }
+        }
+    }
+    let mut total: u64 = 0;
+    cells
+        .into_iter()
+        .take_while(|cell| {
+            let reached = total >= target;
+            total = total.saturating_add(cell_capacity(cell));
+            !reached
+        })
+        .collect()
+}
+
+/// Depth first search for a subset of `capacities` (sorted in descending order) with total in
+/// range `[target, target + tolerance]`, the subset with least waste is returned.
+///
+/// The search is iterative (the inclusion branch first), so the depth is not limited by the
+/// stack size.
+fn branch_and_bound(capacities: &[u64], target: u64, tolerance: u64) -> Option<Vec<bool>> {
+    let (lower, upper) = (target, target.saturating_add(tolerance));
+    // rest_totals[i] = sum(capacities[i..])
+    let mut rest_totals = vec![0u64; capacities.len() + 1];
+    for idx in (0..capacities.len()).rev() {
+        rest_totals[idx] = rest_totals[idx + 1].saturating_add(capacities[idx]);
+    }
+    let mut current = vec![false; capacities.len()];
+    let mut best: Option<(u64, Vec<bool>)> = None;
+    let mut total: u64 = 0;
+    let mut idx = 0;
+    for _ in 0..BNB_MAX_TRIES {
+        let backtrack = if total > upper {
+            true
+        } else if total >= lower {
+            let waste = total - lower;
+            if best
+                .as_ref()
+                .map(|(best_waste, _)| waste < *best_waste)
+                .unwrap_or(true)
+            {
+                best = Some((waste, current.clone()));
+            }
+            if waste == 0 {
+                break;
+            }
+            true
+        } else {
+            idx >= capacities.len() || total.saturating_add(rest_totals[idx]) < lower
+        };
+        if backtrack {
+            // Exclude the last included cell, then search the rest cells after it
+            while idx > 0 && !current[idx - 1] {
+                idx -= 1;
+            }
+            if idx == 0 {
+                break;
+            }
+            idx -= 1;
+            current[idx] = false;
+            total -= capacities[idx];
+        } else {
+            current[idx] = true;
+            total += capacities[idx];
+        }
+        idx += 1;
+    }
+    best.map(|(_, selected)| selected)
+}
+
+/// The candidate cells of each query (without the capacity limit), fetched once and updated
+/// by the locked cells.
+#[derive(Clone, Default)]
+struct CandidateCache {
+    queries: HashMap<CellQueryOptions, Vec<LiveCell>>,
+}
+
+impl CandidateCache {
+    fn candidates<F>(
+        &mut self,
+        query: &CellQueryOptions,
+        fetch: F,
+    ) -> Result<Vec<LiveCell>, CellCollectorError>
+    where
+        F: FnOnce(&CellQueryOptions) -> Result<Vec<LiveCell>, CellCollectorError>,
+    {
+        let mut all_query = query.clone();
+        all_query.min_total_capacity = u64::max_value();
+        if let Some(cells) = self.queries.get(&all_query) {
+            return Ok(cells.clone());
+        }
+        let cells = fetch(&all_query)?;
+        self.queries.insert(all_query, cells.clone());
+        Ok(cells)
+    }
+
+    fn remove(&mut self, out_point: &OutPoint) {
+        for cells in self.queries.values_mut() {
+            cells.retain(|cell| &cell.out_point != out_point);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queries.clear();
+    }
+}
+
+/// A cell collector apply the coin selection strategy on the cells collected by
+/// `DefaultCellCollector`.
+#[derive(Clone)]
+pub struct CoinSelectionCellCollector {
+    inner: DefaultCellCollector,
+    ckb_client: CkbRpcClient,
+    strategy: CoinSelection,
+    tolerance: u64,
+    cache: CandidateCache,
+}
+
+impl CoinSelectionCellCollector {
+    pub fn new(
+        inner: DefaultCellCollector,
+        ckb_rpc: &str,
+        strategy: CoinSelection,
+    ) -> CoinSelectionCellCollector {
+        CoinSelectionCellCollector {
+            inner,
+            ckb_client: CkbRpcClient::new(ckb_rpc),
+            strategy,
+            tolerance: 0,
+            cache: CandidateCache::default(),
+        }
+    }
+
+    /// Set the tolerance of branch-and-bound strategy
+    pub fn set_tolerance(&mut self, tolerance: u64) {
+        self.tolerance = tolerance;
+    }
+}
+
+impl CellCollector for CoinSelectionCellCollector {
+    fn collect_live_cells(
+        &mut self,
+        query: &CellQueryOptions,
+        apply_changes: bool,
+    ) -> Result<(Vec<LiveCell>, u64), CellCollectorError> {
+        if self.strategy == CoinSelection::Default {
+            return self.inner.collect_live_cells(query, apply_changes);
+        }
+        let inner = &mut self.inner;
+        let cells = self.cache.candidates(query, |all_query| {
+            inner
+                .collect_live_cells(all_query, false)
+                .map(|(cells, _)| cells)
+        })?;
+        let selected = select_cells(
+            cells,
+            query.min_total_capacity,
+            self.strategy,
+            self.tolerance,
+        );
+        if apply_changes && !selected.is_empty() {
+            let tip_block_number = self
+                .ckb_client
+                .get_tip_block_number()
+                .map_err(|err| CellCollectorError::Internal(anyhow!(err)))?
+                .value();
+            for cell in &selected {
+                self.lock_cell(cell.out_point.clone(), tip_block_number)?;
+            }
+        }
+        let total_capacity = selected.iter().map(cell_capacity).sum();
+        Ok((selected, total_capacity))
+    }
+
+    fn lock_cell(
+        &mut self,
+        out_point: OutPoint,
+        tip_block_number: u64,
+    ) -> Result<(), CellCollectorError> {
+        self.cache.remove(&out_point);
+        self.inner.lock_cell(out_point, tip_block_number)
+    }
+
+    fn apply_tx(
+        &mut self,
+        tx: Transaction,
+        tip_block_number: u64,
+    ) -> Result<(), CellCollectorError> {
+        // The inputs are dead and the outputs are new live cells
+        self.cache.clear();
+        self.inner.apply_tx(tx, tip_block_number)
+    }
+
+    fn reset(&mut self) {
+        self.cache.clear();
+        self.inner.reset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{
+        bytes::Bytes,
+        core::Capacity,
+        packed::{Byte32, CellOutput},
+    };
+
+    fn fake_cells(cells: &[(u64, u64)]) -> Vec<LiveCell> {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(idx, (capacity, block_number))| LiveCell {
+                output: CellOutput::new_builder()
+                    .capacity(Capacity::shannons(*capacity).pack())
+                    .build(),
+                output_data: Bytes::new(),
+                out_point: OutPoint::new(Byte32::default(), idx as u32),
+                block_number: *block_number,
+                tx_index: 0,
+            })
+            .collect()
+    }
+
+    fn capacities(cells: &[LiveCell]) -> Vec<u64> {
+        cells.iter().map(cell_capacity).collect()
+    }
+
+    #[test]
+    fn test_parse_coin_selection() {
+        for name in CoinSelection::NAMES {
+            let strategy = CoinSelection::from_str(name).unwrap();
+            assert_eq!(&strategy.to_string(), name);
+        }
+        assert!(CoinSelection::from_str("random").is_err());
+    }
+
+    #[test]
+    fn test_select_cells() {
+        let cells = fake_cells(&[(300, 5), (100, 1), (500, 3), (200, 2), (400, 4)]);
+
+        let selected = select_cells(cells.clone(), 450, CoinSelection::Default, 0);
+        assert_eq!(capacities(&selected), vec![300, 100, 500]);
+        let selected = select_cells(cells.clone(), 450, CoinSelection::LargestFirst, 0);
+        assert_eq!(capacities(&selected), vec![500]);
+        let selected = select_cells(cells.clone(), 450, CoinSelection::SmallestFirst, 0);
+        assert_eq!(capacities(&selected), vec![100, 200, 300]);
+        let selected = select_cells(cells.clone(), 450, CoinSelection::OldestFirst, 0);
+        assert_eq!(capacities(&selected), vec![100, 200, 500]);
+
+        // exact match
+        let selected = select_cells(cells.clone(), 700, CoinSelection::BranchAndBound, 0);
+        assert_eq!(capacities(&selected).iter().sum::<u64>(), 700);
+        // match within tolerance
+        let selected = select_cells(cells.clone(), 1050, CoinSelection::BranchAndBound, 60);
+        assert_eq!(capacities(&selected), vec![500, 400, 200]);
+        // fallback to largest-first
+        let selected = select_cells(cells.clone(), 1050, CoinSelection::BranchAndBound, 0);
+        assert_eq!(capacities(&selected), vec![500, 400, 300]);
+
+        // not enough capacity, return all cells
+        for strategy in [
+            CoinSelection::Default,
+            CoinSelection::LargestFirst,
+            CoinSelection::SmallestFirst,
+            CoinSelection::OldestFirst,
+            CoinSelection::BranchAndBound,
+        ] {
+            let selected = select_cells(cells.clone(), 10_000, strategy, 0);
+            assert_eq!(selected.len(), cells.len());
+        }
+        assert!(select_cells(Vec::new(), 1, CoinSelection::LargestFirst, 0).is_empty());
+    }
+
+    #[test]
+    fn test_branch_and_bound_depth() {
+        // The search goes as deep as the number of cells before backtracking
+        let capacities = vec![2u64; 200_000];
+        assert_eq!(branch_and_bound(&capacities, 399_999, 0), None);
+        let selected = branch_and_bound(&capacities, 180_000, 0).unwrap();
+        assert_eq!(
+            selected.iter().filter(|selected| **selected).count(),
+            90_000
+        );
+    }
+
+    #[test]
+    fn test_candidate_cache() {
+        let cells = fake_cells(&[(300, 1), (100, 2), (500, 3)]);
+        let query = CellQueryOptions::new_lock(Default::default());
+        let mut cache = CandidateCache::default();
+        let mut fetched = 0;
+        let mut fetch = |all_query: &CellQueryOptions| {
+            assert_eq!(all_query.min_total_capacity, u64::max_value());
+            fetched += 1;
+            Ok(cells.clone())
+        };
+        assert_eq!(cache.candidates(&query, &mut fetch).unwrap().len(), 3);
+        // Fetched once for the same query with different capacity
+        let mut larger_query = query.clone();
+        larger_query.min_total_capacity = 800;
+        assert_eq!(
+            cache.candidates(&larger_query, &mut fetch).unwrap().len(),
+            3
+        );
+        // The locked cell is removed
+        cache.remove(&cells[1].out_point);
+        assert_eq!(
+            capacities(&cache.candidates(&query, &mut fetch).unwrap()),
+            vec![300, 500]
+        );
+        cache.clear();
+        assert_eq!(cache.candidates(&query, &mut fetch).unwrap().len(), 3);
+        assert_eq!(fetched, 2);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::str::FromStr;
+
+use clap::ArgMatches;
+
+use super::arg_parser::{ArgParser, FeeRateStatisticsTargetParser, FromStrParser};
+use super::rpc::HttpRpcClient;
+
+/// The default ceiling of the automatic fee rate (unit: shannons/KB)
+pub const DEFAULT_MAX_FEE_RATE: u64 = 10_000;
+
+/// The value of `--fee-rate` argument: a fixed fee rate or `auto[:target_blocks]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeRateSpec {
+    /// Fixed fee rate (unit: shannons/KB)
+    Fixed(u64),
+    /// Pick the fee rate from the fee rate statistics of the last `target` blocks (default is
+    /// decided by the node) and the minimal fee rate of the tx-pool
+    Auto { target: Option<u64> },
+}
+
+impl FeeRateSpec {
+    /// Resolve the fee rate, the rpc client is only used by `auto`
+    pub fn resolve(self, rpc_client: &mut HttpRpcClient, max_fee_rate: u64) -> Result<u64, String> {
+        match self {
+            FeeRateSpec::Fixed(fee_rate) => Ok(fee_rate),
+            FeeRateSpec::Auto { target } => {
+                let median = rpc_client
+                    .get_fee_rate_statistics(target)?
+                    .map(|statistics| statistics.median);
+                let min_fee_rate = rpc_client.tx_pool_info()?.min_fee_rate;
+                let fee_rate = select_fee_rate(median, min_fee_rate, max_fee_rate)?;
+                eprintln!(
+                    "[fee-rate] auto: {} shannons/KB (median: {}, tx-pool min: {}, max: {})",
+                    fee_rate,
+                    median
+                        .map(|value| value.to_string())
+                        .unwrap_or_else(|| String::from("none")),
+                    min_fee_rate,
+                    max_fee_rate
+                );
+                Ok(fee_rate)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FeeRateSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FeeRateSpec::Fixed(fee_rate) => write!(f, "{}", fee_rate),
+            FeeRateSpec::Auto { target: None } => write!(f, "auto"),
+            FeeRateSpec::Auto {
+                target: Some(target),
+            } => write!(f, "auto:{}", target),
+        }
+    }
+}
+
+impl FromStr for FeeRateSpec {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input == "auto" {
+            return Ok(FeeRateSpec::Auto { target: None });
+        }
+        if let Some(target) = input.strip_prefix("auto:") {
+            let target = FeeRateStatisticsTargetParser.parse(target)?;
+            return Ok(FeeRateSpec::Auto {
+                target: Some(target),
+            });
+        }
+        FromStrParser::<u64>::default()
+            .parse(input)
+            .map(FeeRateSpec::Fixed)
+            .map_err(|err| format!("Invalid fee rate: {}, {}", input, err))
+    }
+}
+
+/// Use the median of the recent fee rates, but not lower than the minimal fee rate of the
+/// tx-pool and not higher than the ceiling.
+pub fn select_fee_rate(
+    median: Option<u64>,
+    min_fee_rate: u64,
+    max_fee_rate: u64,
+) -> Result<u64, String> {
+    if min_fee_rate > max_fee_rate {
+        return Err(format!(
+            "The minimal fee rate of tx-pool ({} shannons/KB) is higher than the max fee rate ({} shannons/KB)",
+            min_fee_rate, max_fee_rate
+        ));
+    }
+    Ok(median
+        .unwrap_or(min_fee_rate)
+        .max(min_fee_rate)
+        .min(max_fee_rate))
+}
+
+/// Get the fee rate from `--fee-rate` and `--max-fee-rate` arguments
+pub fn get_fee_rate(rpc_client: &mut HttpRpcClient, m: &ArgMatches) -> Result<u64, String> {
+    let spec: FeeRateSpec = FromStrParser::<FeeRateSpec>::new().from_matches(m, "fee-rate")?;
+    let max_fee_rate: u64 = FromStrParser::<u64>::default()
+        .from_matches_opt(m, "max-fee-rate")?
+        .unwrap_or(DEFAULT_MAX_FEE_RATE);
+    spec.resolve(rpc_client, max_fee_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rate_spec() {
+        for input in ["1000", "auto", "auto:21"] {
+            let spec: FeeRateSpec = input.parse().unwrap();
+            assert_eq!(spec.to_string(), input);
+        }
+        assert_eq!("auto".parse(), Ok(FeeRateSpec::Auto { target: None }));
+        assert!("auto:0".parse::<FeeRateSpec>().is_err());
+        assert!("auto:102".parse::<FeeRateSpec>().is_err());
+        assert!("fast".parse::<FeeRateSpec>().is_err());
+
+        assert_eq!(select_fee_rate(Some(3000), 1000, 10_000), Ok(3000));
+        assert_eq!(select_fee_rate(Some(500), 1000, 10_000), Ok(1000));
+        assert_eq!(select_fee_rate(None, 1000, 10_000), Ok(1000));
+        assert_eq!(select_fee_rate(Some(50_000), 1000, 10_000), Ok(10_000));
+        assert!(select_fee_rate(Some(3000), 20_000, 10_000).is_err());
+    }
+}
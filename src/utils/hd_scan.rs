@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use ckb_jsonrpc_types::Uint32;
+use ckb_sdk::{
+    rpc::ckb_indexer::{Order, ScriptType, SearchKey},
+    AddressPayload, CkbRpcClient, NetworkType,
+};
+use ckb_types::{packed::Script, H160};
+use serde::{Deserialize, Serialize};
+
+use super::cli_file::CliJsonFile;
+
+pub const HD_SCAN_CACHE_FILENAME: &str = "hd-scan-cache.json";
+/// The default number of consecutive unused addresses to stop the scan (BIP44)
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// The last used indexes of the receiving and change chains of an account
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct HdScanState {
+    pub receiving_last_used: Option<u32>,
+    pub change_last_used: Option<u32>,
+}
+
+impl HdScanState {
+    pub fn next_receiving_index(&self) -> u32 {
+        self.receiving_last_used.map(|idx| idx + 1).unwrap_or(0)
+    }
+
+    pub fn next_change_index(&self) -> u32 {
+        self.change_last_used.map(|idx| idx + 1).unwrap_or(0)
+    }
+
+    /// Mark the change address at `index` as used
+    pub fn use_change_index(&mut self, index: u32) {
+        if self.change_last_used.map(|idx| idx < index).unwrap_or(true) {
+            self.change_last_used = Some(index);
+        }
+    }
+}
+
+/// Scan results stored in `$CKB_CLI_HOME/hd-scan-cache.json`, keyed by network and account
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct HdScanCache {
+    accounts: BTreeMap<String, HdScanState>,
+}
+
+impl HdScanCache {
+    /// Load the cache, an empty cache is returned if the file not exists
+    pub fn load(ckb_cli_dir: &Path) -> Result<HdScanCache, String> {
+        cache_file(ckb_cli_dir).load()
+    }
+
+    pub fn save(&self, ckb_cli_dir: &Path) -> Result<(), String> {
+        cache_file(ckb_cli_dir).save(self)
+    }
+
+    pub fn get(&self, network: NetworkType, lock_arg: &H160) -> Option<HdScanState> {
+        self.accounts.get(&cache_key(network, lock_arg)).cloned()
+    }
+
+    pub fn set(&mut self, network: NetworkType, lock_arg: &H160, state: HdScanState) {
+        self.accounts.insert(cache_key(network, lock_arg), state);
+    }
+}
+
+fn cache_file(ckb_cli_dir: &Path) -> CliJsonFile {
+    CliJsonFile::new(ckb_cli_dir, HD_SCAN_CACHE_FILENAME, "HD scan cache")
+}
+
+fn cache_key(network: NetworkType, lock_arg: &H160) -> String {
+    format!("{}:{:#x}", network, lock_arg)
+}
+
+/// Walk a key chain until `gap_limit` consecutive unused keys are found, return the last used
+/// index. The scan starts after `last_used` (the keys before it are treated as used).
+///
+/// * `derive(start, length)` returns the lock args of the keys in range `[start, start+length)`
+/// * `is_used(lock_arg)` tells if the address of the key ever appeared in any transaction
+pub fn scan_key_chain<D, U>(
+    mut last_used: Option<u32>,
+    gap_limit: u32,
+    mut derive: D,
+    mut is_used: U,
+) -> Result<Option<u32>, String>
+where
+    D: FnMut(u32, u32) -> Result<Vec<H160>, String>,
+    U: FnMut(&H160) -> Result<bool, String>,
+{
+    if gap_limit == 0 {
+        return Err(String::from("Gap limit must be greater than 0"));
+    }
+    let mut index = last_used.map(|idx| idx + 1).unwrap_or(0);
+    loop {
+        let unused_count = index - last_used.map(|idx| idx + 1).unwrap_or(0);
+        if unused_count >= gap_limit {
+            return Ok(last_used);
+        }
+        let length = gap_limit - unused_count;
+        let lock_args = derive(index, length)?;
+        if lock_args.len() != length as usize {
+            return Err(format!(
+                "Derive keys failed, expected {} keys, got {}",
+                length,
+                lock_args.len()
+            ));
+        }
+        for (offset, lock_arg) in lock_args.iter().enumerate() {
+            if is_used(lock_arg)? {
+                last_used = Some(index + offset as u32);
+            }
+        }
+        index += length;
+    }
+}
+
+/// Check if the sighash address of `lock_arg` ever appeared in any transaction (by indexer)
+pub fn is_address_used(client: &mut CkbRpcClient, lock_arg: &H160) -> Result<bool, String> {
+    let script = Script::from(&AddressPayload::from_pubkey_hash(lock_arg.clone()));
+    let search_key = SearchKey {
+        script: script.into(),
+        script_type: ScriptType::Lock,
+        script_search_mode: None,
+        filter: None,
+        with_data: Some(false),
+        group_by_transaction: Some(true),
+    };
+    let page = client
+        .get_transactions(search_key, Order::Asc, Uint32::from(1), None)
+        .map_err(|err| err.to_string())?;
+    Ok(!page.objects.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_key_chain() {
+        let derive = |start: u32, length: u32| -> Result<Vec<H160>, String> {
+            Ok((start..start + length)
+                .map(|idx| {
+                    let mut bytes = [0u8; 20];
+                    bytes[..4].copy_from_slice(&idx.to_le_bytes());
+                    H160(bytes)
+                })
+                .collect())
+        };
+        let key_index = |lock_arg: &H160| {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(&lock_arg.0[..4]);
+            u32::from_le_bytes(bytes)
+        };
+        let used_set = [0u32, 1, 4, 9, 30];
+        let mut checked = 0;
+        let last_used = scan_key_chain(None, 5, derive, |lock_arg| {
+            checked += 1;
+            Ok(used_set.contains(&key_index(lock_arg)))
+        })
+        .unwrap();
+        // index 30 is out of the gap (10..=14 are unused)
+        assert_eq!(last_used, Some(9));
+        assert_eq!(checked, 15);
+
+        // nothing used
+        let last_used = scan_key_chain(None, 3, derive, |_| Ok(false)).unwrap();
+        assert_eq!(last_used, None);
+        // continue from cached last used index
+        let last_used = scan_key_chain(Some(9), 25, derive, |lock_arg| {
+            assert!(key_index(lock_arg) > 9);
+            Ok(used_set.contains(&key_index(lock_arg)))
+        })
+        .unwrap();
+        assert_eq!(last_used, Some(30));
+        assert!(scan_key_chain(None, 0, derive, |_| Ok(false)).is_err());
+
+        let mut state = HdScanState {
+            receiving_last_used: Some(30),
+            change_last_used: None,
+        };
+        assert_eq!(state.next_receiving_index(), 31);
+        assert_eq!(state.next_change_index(), 0);
+        state.use_change_index(2);
+        state.use_change_index(1);
+        assert_eq!(state.next_change_index(), 3);
+    }
+}
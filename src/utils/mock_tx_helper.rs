@@ -7,13 +7,19 @@ use ckb_error::OtherError;
 use ckb_hash::new_blake2b;
 use ckb_jsonrpc_types as rpc_types;
 use ckb_mock_tx_types::{MockResourceLoader, MockTransaction, Resource};
-use ckb_script::{TransactionScriptsVerifier, TxVerifyEnv};
-use ckb_sdk::constants::{MIN_SECP_CELL_CAPACITY, SIGHASH_TYPE_HASH};
+use ckb_script::{ScriptGroupType, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_sdk::{
+    constants::{MIN_SECP_CELL_CAPACITY, SIGHASH_TYPE_HASH},
+    HumanCapacity, ScriptId,
+};
 use ckb_types::core::hardfork::{HardForks, CKB2021, CKB2023};
 use ckb_types::core::HeaderBuilder;
 use ckb_types::{
     bytes::Bytes,
-    core::{cell::resolve_transaction, Capacity, Cycle, ScriptHashType},
+    core::{
+        cell::{resolve_transaction, ResolvedTransaction},
+        Capacity, Cycle, ScriptHashType, TransactionView,
+    },
     packed::{Byte32, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
     prelude::*,
     H160, H256,
@@ -309,40 +315,296 @@ impl<'a> MockTransactionHelper<'a> {
         self.fill_witnesses(signer, &mut live_cell_getter)
     }
 
-    /// Verify the transaction by local ScriptVerifier
-    pub fn verify<L: MockResourceLoader>(
+    /// Replace the data of a cell dep (or a cell of dep group) in mock info, return the data
+    /// hash of the new data
+    pub fn replace_cell_dep_data(
+        &mut self,
+        out_point: &OutPoint,
+        data: Bytes,
+    ) -> Result<Byte32, String> {
+        let mut found = false;
+        for mock_cell_dep in self.mock_tx.mock_info.cell_deps.iter_mut() {
+            if &mock_cell_dep.cell_dep.out_point() == out_point {
+                mock_cell_dep.data = data.clone();
+                found = true;
+            }
+        }
+        if !found {
+            return Err(format!("Cell dep not found in mock info: {}", out_point));
+        }
+        Ok(CellOutput::calc_data_hash(&data))
+    }
+
+    /// Replace the code hash and hash type of the lock/type scripts of the inputs (in mock info)
+    /// and the outputs, the args are kept. Return the number of replaced scripts.
+    ///
+    /// NOTE: the transaction hash is changed if any output is patched.
+    pub fn replace_script_id(&mut self, from: &ScriptId, to: &ScriptId) -> usize {
+        let mut count = 0;
+        let mut replace = |script: Script| {
+            if &ScriptId::from(&script) == from {
+                count += 1;
+                script
+                    .as_builder()
+                    .code_hash(to.code_hash.pack())
+                    .hash_type(to.hash_type.into())
+                    .build()
+            } else {
+                script
+            }
+        };
+        let mut replace_output = |output: CellOutput| {
+            let lock = replace(output.lock());
+            let type_ = output.type_().to_opt().map(&mut replace);
+            output.as_builder().lock(lock).type_(type_.pack()).build()
+        };
+        for mock_input in self.mock_tx.mock_info.inputs.iter_mut() {
+            mock_input.output = replace_output(mock_input.output.clone());
+        }
+        let tx = self.mock_tx.core_transaction();
+        let outputs = tx.outputs().into_iter().map(replace_output).collect();
+        self.mock_tx.tx = tx.as_advanced_builder().set_outputs(outputs).build().data();
+        count
+    }
+
+    /// Set the witness at `index`, the missing witnesses before it are filled with empty witness
+    pub fn set_witness(&mut self, index: usize, witness: Bytes) {
+        let tx = self.mock_tx.core_transaction();
+        let mut witnesses: Vec<_> = tx.witnesses().into_iter().collect();
+        if witnesses.len() <= index {
+            witnesses.resize(index + 1, Bytes::new().pack());
+        }
+        witnesses[index] = witness.pack();
+        self.mock_tx.tx = tx
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build()
+            .data();
+    }
+
+    /// Set the lock field of the witness at `index`, the missing or empty witness is treated as
+    /// an empty WitnessArgs
+    pub fn set_witness_lock(&mut self, index: usize, lock: Bytes) -> Result<(), String> {
+        let witness = self
+            .mock_tx
+            .core_transaction()
+            .witnesses()
+            .get(index)
+            .map(|witness| witness.raw_data())
+            .unwrap_or_default();
+        let witness_args = if witness.is_empty() {
+            WitnessArgs::default()
+        } else {
+            WitnessArgs::from_slice(witness.as_ref())
+                .map_err(|err| format!("Witness #{} is not a WitnessArgs: {}", index, err))?
+        };
+        let witness_args = witness_args.as_builder().lock(Some(lock).pack()).build();
+        self.set_witness(index, witness_args.as_bytes());
+        Ok(())
+    }
+
+    /// Verify the transaction by local ScriptVerifier, the script groups are verified one by
+    /// one and the failed groups are kept in the report
+    pub fn verify_groups<L: MockResourceLoader>(
         &mut self,
         max_cycle: Cycle,
         loader: L,
-    ) -> Result<Cycle, String> {
+    ) -> Result<CycleReport, String> {
+        let (rtx, resource) = self.resolve(loader)?;
+        let input_capacity = rtx
+            .resolved_inputs
+            .iter()
+            .map(|cell| Unpack::<u64>::unpack(&cell.cell_output.capacity()))
+            .sum();
+        let dep_cells = rtx
+            .resolved_cell_deps
+            .iter()
+            .map(|cell| {
+                let data_hash = cell
+                    .mem_cell_data_hash
+                    .clone()
+                    .or_else(|| {
+                        cell.mem_cell_data
+                            .as_ref()
+                            .map(|data| CellOutput::calc_data_hash(data))
+                    })
+                    .unwrap_or_default();
+                let type_hash = cell
+                    .cell_output
+                    .type_()
+                    .to_opt()
+                    .map(|script| script.calc_script_hash());
+                (cell.out_point.clone(), data_hash, type_hash)
+            })
+            .collect::<Vec<_>>();
+
+        let verifier = build_verifier(rtx, resource);
+        let mut remaining_cycle = max_cycle;
+        let mut groups = Vec::new();
+        for (group_type, script_hash, group) in verifier.groups_with_type() {
+            let code_hash = group.script.code_hash();
+            let by_type = group.script.hash_type() == ScriptHashType::Type.into();
+            let code_cell = dep_cells
+                .iter()
+                .find(|(_, data_hash, type_hash)| {
+                    if by_type {
+                        type_hash.as_ref() == Some(&code_hash)
+                    } else {
+                        data_hash == &code_hash
+                    }
+                })
+                .map(|(out_point, _, _)| out_point.clone());
+            let cycles = verifier
+                .verify_single(group_type, script_hash, remaining_cycle)
+                .map_err(|err| err.to_string());
+            if let Ok(cycles) = cycles.as_ref() {
+                remaining_cycle = remaining_cycle.saturating_sub(*cycles);
+            }
+            groups.push(ScriptGroupCycles {
+                group_type,
+                script: group.script.clone(),
+                input_indices: group.input_indices.clone(),
+                output_indices: group.output_indices.clone(),
+                code_cell,
+                cycles,
+            });
+        }
+        Ok(CycleReport {
+            groups,
+            input_capacity,
+        })
+    }
+
+    fn resolve<L: MockResourceLoader>(
+        &self,
+        loader: L,
+    ) -> Result<(ResolvedTransaction, Resource), String> {
         let resource = Resource::from_both(self.mock_tx, loader)?;
         let tx = self.mock_tx.core_transaction();
-        let rtx = {
-            resolve_transaction(tx, &mut HashSet::new(), &resource, &resource)
-                .map_err(|err| format!("Resolve transaction error: {:?}", err))?
-        };
+        let rtx = resolve_transaction(tx, &mut HashSet::new(), &resource, &resource)
+            .map_err(|err| format!("Resolve transaction error: {:?}", err))?;
+        Ok((rtx, resource))
+    }
+}
+
+fn build_verifier(
+    rtx: ResolvedTransaction,
+    resource: Resource,
+) -> TransactionScriptsVerifier<Resource> {
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(HardForks {
+            ckb2021: CKB2021::new_dev_default(),
+            ckb2023: CKB2023::new_dev_default(),
+        })
+        .build();
+    let tip = HeaderBuilder::default().number(0.pack()).build();
+    let tx_verify_env = TxVerifyEnv::new_submit(&tip);
+
+    let mut verifier = TransactionScriptsVerifier::new(
+        Arc::new(rtx),
+        resource,
+        Arc::new(consensus),
+        Arc::new(tx_verify_env),
+    );
+    verifier.set_debug_printer(|script_hash, message| {
+        println!("script: {:x}, debug: {}", script_hash, message);
+    });
+    verifier
+}
+
+/// The cycles consumed by a lock/type script group
+pub struct ScriptGroupCycles {
+    pub group_type: ScriptGroupType,
+    pub script: Script,
+    pub input_indices: Vec<usize>,
+    pub output_indices: Vec<usize>,
+    /// The cell dep provides the script code (`None` for type id script)
+    pub code_cell: Option<OutPoint>,
+    /// The consumed cycles or the verification error
+    pub cycles: Result<Cycle, String>,
+}
 
-        let consensus = ConsensusBuilder::default()
-            .hardfork_switch(HardForks {
-                ckb2021: CKB2021::new_dev_default(),
-                ckb2023: CKB2023::new_dev_default(),
+pub struct CycleReport {
+    pub groups: Vec<ScriptGroupCycles>,
+    /// The total capacity of the resolved inputs
+    pub input_capacity: u64,
+}
+
+impl CycleReport {
+    /// The transaction fee, `None` if the outputs capacity is more than the inputs capacity
+    pub fn tx_fee(&self, tx: &TransactionView) -> Option<u64> {
+        let output_capacity: u64 = tx
+            .outputs()
+            .into_iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+            .sum();
+        self.input_capacity.checked_sub(output_capacity)
+    }
+
+    /// The report with serialized size, fee and effective fee rate (unit: shannons/KB) of the
+    /// transaction, the scripts are named by `names`
+    pub fn to_json(
+        &self,
+        tx: &TransactionView,
+        names: &HashMap<ScriptId, String>,
+    ) -> serde_json::Value {
+        let tx_size = tx.data().as_reader().serialized_size_in_block() as u64;
+        let tx_fee = self.tx_fee(tx);
+        serde_json::json!({
+            "total_cycles": self.total_cycles().ok(),
+            "tx_size": tx_size,
+            "tx_fee": tx_fee.map(|fee| format!("{:#}", HumanCapacity(fee))),
+            "fee_rate": tx_fee.map(|fee| fee * 1000 / tx_size),
+            "script_groups": self.groups_json(names),
+        })
+    }
+
+    /// The script groups with the names of known scripts
+    pub fn groups_json(&self, names: &HashMap<ScriptId, String>) -> serde_json::Value {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                let script_hash: H256 = group.script.calc_script_hash().unpack();
+                let (cycles, error) = match group.cycles.as_ref() {
+                    Ok(cycles) => (Some(*cycles), None),
+                    Err(err) => (None, Some(err.clone())),
+                };
+                serde_json::json!({
+                    "type": match group.group_type {
+                        ScriptGroupType::Lock => "lock",
+                        ScriptGroupType::Type => "type",
+                    },
+                    "name": names.get(&ScriptId::from(&group.script)),
+                    "script_hash": script_hash,
+                    "script": rpc_types::Script::from(group.script.clone()),
+                    "code_cell": group.code_cell.clone().map(rpc_types::OutPoint::from),
+                    "input_indexes": group.input_indices,
+                    "output_indexes": group.output_indices,
+                    "cycles": cycles,
+                    "error": error,
+                })
             })
-            .build();
-        let tip = HeaderBuilder::default().number(0.pack()).build();
-        let tx_verify_env = TxVerifyEnv::new_submit(&tip);
-
-        let mut verifier = TransactionScriptsVerifier::new(
-            Arc::new(rtx),
-            resource,
-            Arc::new(consensus),
-            Arc::new(tx_verify_env),
-        );
-        verifier.set_debug_printer(|script_hash, message| {
-            println!("script: {:x}, debug: {}", script_hash, message);
-        });
-        verifier
-            .verify(max_cycle)
-            .map_err(|err| format!("Verify script error: {:?}", err))
+            .collect::<Vec<_>>();
+        serde_json::Value::Array(groups)
+    }
+
+    /// The total cycles, fails if any script group failed
+    pub fn total_cycles(&self) -> Result<Cycle, String> {
+        self.groups.iter().try_fold(0, |total: Cycle, group| {
+            group
+                .cycles
+                .as_ref()
+                .map(|cycles| total + cycles)
+                .map_err(|err| {
+                    format!(
+                        "Verify script error: {:?} script {:#x}, {}",
+                        group.group_type,
+                        group.script.calc_script_hash(),
+                        err
+                    )
+                })
+        })
     }
 }
 
@@ -498,8 +760,106 @@ mod test {
             tx.witnesses().len(),
             "Witnesses not match inputs"
         );
-        helper
-            .verify(u64::max_value(), Loader)
+        let report = helper
+            .verify_groups(u64::max_value(), Loader)
             .expect("Verify mock tx failed");
+        assert_eq!(report.groups.len(), 1, "Only the sighash lock group");
+        assert_eq!(report.groups[0].group_type, ScriptGroupType::Lock);
+        assert_eq!(report.groups[0].input_indices, vec![0]);
+        assert!(report.groups[0].code_cell.is_some(), "Code cell not found");
+        assert!(report.total_cycles().expect("Verify mock tx failed") > 0);
+
+        // The wrong signature is reported in the failed group
+        let witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+            .build();
+        helper.mock_tx.tx = tx
+            .as_advanced_builder()
+            .set_witnesses(vec![witness.as_bytes().pack()])
+            .build()
+            .data();
+        let report = helper
+            .verify_groups(u64::max_value(), Loader)
+            .expect("Verify mock tx failed");
+        assert!(report.groups[0].cycles.is_err());
+        assert!(report.total_cycles().is_err());
+    }
+
+    #[test]
+    fn test_patch() {
+        let code_hash = h256!("0xff00");
+        let lock_script = Script::new_builder()
+            .code_hash(code_hash.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![1, 2, 3]).pack())
+            .build();
+        let dep_out_point = OutPoint::new(h256!("0xff01").pack(), 0);
+        let input = CellInput::new(OutPoint::new(h256!("0xff02").pack(), 0), 0);
+        let output = CellOutput::new_builder().lock(lock_script).build();
+        let mut mock_tx = MockTransaction::default();
+        mock_tx.mock_info.cell_deps.push(MockCellDep {
+            cell_dep: CellDep::new_builder()
+                .out_point(dep_out_point.clone())
+                .build(),
+            output: CellOutput::default(),
+            data: Bytes::from("old code"),
+            header: None,
+        });
+        mock_tx.mock_info.inputs.push(MockInput {
+            input: input.clone(),
+            output: output.clone(),
+            data: Bytes::default(),
+            header: None,
+        });
+        mock_tx.tx = mock_tx
+            .tx
+            .as_advanced_builder()
+            .input(input)
+            .output(output)
+            .output_data(Default::default())
+            .build()
+            .data();
+
+        let mut helper = MockTransactionHelper::new(&mut mock_tx);
+        let new_code = Bytes::from("new code");
+        let data_hash = helper
+            .replace_cell_dep_data(&dep_out_point, new_code.clone())
+            .unwrap();
+        assert_eq!(data_hash, CellOutput::calc_data_hash(&new_code));
+        assert!(helper
+            .replace_cell_dep_data(&OutPoint::new(h256!("0xff03").pack(), 0), new_code.clone())
+            .is_err());
+
+        let new_id = ScriptId::new_data1(data_hash.unpack());
+        assert_eq!(
+            helper.replace_script_id(&ScriptId::new_type(code_hash), &new_id),
+            2
+        );
+        assert_eq!(
+            helper.replace_script_id(&ScriptId::new_data(h256!("0xff04")), &new_id),
+            0
+        );
+
+        helper.set_witness_lock(1, Bytes::from(vec![9; 4])).unwrap();
+        helper.set_witness(3, Bytes::from(vec![7]));
+
+        let tx = mock_tx.core_transaction();
+        assert_eq!(mock_tx.mock_info.cell_deps[0].data, new_code);
+        for lock in [
+            mock_tx.mock_info.inputs[0].output.lock(),
+            tx.outputs().get(0).unwrap().lock(),
+        ] {
+            assert_eq!(ScriptId::from(&lock), new_id);
+            assert_eq!(lock.args().raw_data(), Bytes::from(vec![1, 2, 3]));
+        }
+        let witnesses = tx.witnesses();
+        assert_eq!(witnesses.len(), 4);
+        assert!(witnesses.get(0).unwrap().raw_data().is_empty());
+        let witness_args = WitnessArgs::from_slice(&witnesses.get(1).unwrap().raw_data()).unwrap();
+        assert_eq!(
+            witness_args.lock().to_opt().unwrap().raw_data(),
+            Bytes::from(vec![9; 4])
+        );
+        assert_eq!(witnesses.get(3).unwrap().raw_data(), Bytes::from(vec![7]));
     }
 }
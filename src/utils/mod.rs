@@ -1,16 +1,27 @@
+pub mod address_book;
 pub mod arg;
 pub mod arg_parser;
 pub mod cell_dep;
+pub mod cli_file;
+pub mod coin_selection;
 pub mod completer;
 pub mod config;
+pub mod fee_rate;
 pub mod genesis_info;
+pub mod hd_scan;
 pub mod json_color;
 pub mod mock_tx_helper;
+pub mod multisig;
+pub mod omnilock;
 pub mod other;
 pub mod printer;
 pub mod rpc;
 pub mod signer;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod tx_frames;
 pub mod tx_helper;
+pub mod tx_lint;
 
 #[allow(clippy::cast_lossless)]
 pub mod yaml_ser;
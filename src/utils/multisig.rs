@@ -0,0 +1,152 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ckb_sdk::{
+    constants::MULTISIG_TYPE_HASH, unlock::MultisigConfig, AddressPayload, ScriptId, Since,
+};
+use ckb_types::{
+    bytes::BytesMut,
+    core::ScriptHashType,
+    h256,
+    packed::{Byte32, Script},
+    prelude::*,
+    H256,
+};
+use serde::{Deserialize, Serialize};
+
+/// The code hash (hash_type: data1) of the new multisig script, it checks the since of every
+/// input in the script group against the lock args.
+pub const MULTISIG_V2_CODE_HASH: H256 =
+    h256!("0x36c971b8d41fbd94aabca77dc75e826729ac98447b46f91e00796155dddb0d29");
+
+/// The variant of secp256k1_blake160_multisig_all script, the lock args, witness and signing
+/// message are the same.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultisigScript {
+    /// The multisig script deployed in genesis block (hash_type: type)
+    #[default]
+    Legacy,
+    /// The new multisig script (hash_type: data1), the cell dep is not in genesis block, it
+    /// must be given by the `multisig_v2` item of cell deps file or added manually
+    V2,
+}
+
+impl MultisigScript {
+    pub const NAMES: [&'static str; 2] = ["legacy", "v2"];
+
+    pub fn code_hash(self) -> H256 {
+        match self {
+            MultisigScript::Legacy => MULTISIG_TYPE_HASH,
+            MultisigScript::V2 => MULTISIG_V2_CODE_HASH,
+        }
+    }
+
+    pub fn hash_type(self) -> ScriptHashType {
+        match self {
+            MultisigScript::Legacy => ScriptHashType::Type,
+            MultisigScript::V2 => ScriptHashType::Data1,
+        }
+    }
+
+    pub fn script_id(self) -> ScriptId {
+        ScriptId::new(self.code_hash(), self.hash_type())
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        *self == MultisigScript::Legacy
+    }
+
+    /// Match by code hash only, the hash type must be checked separately
+    pub fn from_code_hash(code_hash: &Byte32) -> Option<MultisigScript> {
+        let code_hash: H256 = code_hash.unpack();
+        if code_hash == MULTISIG_TYPE_HASH {
+            Some(MultisigScript::Legacy)
+        } else if code_hash == MULTISIG_V2_CODE_HASH {
+            Some(MultisigScript::V2)
+        } else {
+            None
+        }
+    }
+
+    pub fn from_lock(lock: &Script) -> Option<MultisigScript> {
+        MultisigScript::from_code_hash(&lock.code_hash()).filter(|variant| {
+            ScriptHashType::try_from(lock.hash_type()).ok() == Some(variant.hash_type())
+        })
+    }
+
+    /// The address payload of a multisig config, the legacy address without since is in short
+    /// format.
+    pub fn address_payload(
+        self,
+        config: &MultisigConfig,
+        since_absolute_epoch: Option<u64>,
+    ) -> AddressPayload {
+        match self {
+            MultisigScript::Legacy => config.to_address_payload(since_absolute_epoch),
+            MultisigScript::V2 => {
+                let mut args = BytesMut::from(config.hash160().as_bytes());
+                if let Some(epoch_number) = since_absolute_epoch {
+                    let since_value = Since::new_absolute_epoch(epoch_number).value();
+                    args.extend_from_slice(&since_value.to_le_bytes()[..]);
+                }
+                AddressPayload::new_full(
+                    ScriptHashType::Data1,
+                    MULTISIG_V2_CODE_HASH.pack(),
+                    args.freeze(),
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for MultisigScript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let output = match self {
+            MultisigScript::Legacy => "legacy",
+            MultisigScript::V2 => "v2",
+        };
+        write!(f, "{}", output)
+    }
+}
+
+impl FromStr for MultisigScript {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "legacy" => Ok(MultisigScript::Legacy),
+            "v2" => Ok(MultisigScript::V2),
+            _ => Err(format!("Invalid multisig script: {}", input)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::h160;
+
+    #[test]
+    fn test_multisig_script() {
+        let config = MultisigConfig::new_with(vec![h160!("0x1"), h160!("0x2")], 0, 2).unwrap();
+        for variant in [MultisigScript::Legacy, MultisigScript::V2] {
+            assert_eq!(variant.to_string().parse(), Ok(variant));
+            for since in [None, Some(100)] {
+                let lock = Script::from(&variant.address_payload(&config, since));
+                assert_eq!(MultisigScript::from_lock(&lock), Some(variant));
+                assert_eq!(&lock.args().raw_data()[..20], config.hash160().as_bytes());
+                assert_eq!(
+                    lock.args().raw_data().len(),
+                    if since.is_some() { 28 } else { 20 }
+                );
+            }
+        }
+        // The v2 code hash with type hash type is not a multisig lock
+        let lock = Script::new_builder()
+            .code_hash(MULTISIG_V2_CODE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        assert_eq!(MultisigScript::from_lock(&lock), None);
+        assert!("v3".parse::<MultisigScript>().is_err());
+    }
+}
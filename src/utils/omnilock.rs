@@ -0,0 +1,299 @@
+use ckb_sdk::{
+    types::omni_lock::OmniLockWitnessLock,
+    unlock::{IdentityFlag, MultisigConfig, OmniLockAcpConfig, OmniLockConfig, OmniUnlockMode},
+    Address, AddressPayload, NetworkType,
+};
+use ckb_types::{
+    bytes::{BufMut, Bytes, BytesMut},
+    core::ScriptHashType,
+    h256,
+    packed::Script,
+    prelude::*,
+    H160, H256,
+};
+
+/// The Omnilock type script hash deployed on mainnet (see RFC 0042)
+pub const OMNILOCK_TYPE_HASH_MAINNET: H256 =
+    h256!("0x9b819793a64463aed77c615d6cb226eea5487ccfc0783043a587254cda2b6f26");
+/// The Omnilock type script hash deployed on testnet (see RFC 0042)
+pub const OMNILOCK_TYPE_HASH_TESTNET: H256 =
+    h256!("0xf329effd1c475a2978453c8600e1eaf0bc2087ee093c3ee64cc96ec6847752cb");
+
+const FLAG_ADMIN: u8 = 1;
+const FLAG_ACP: u8 = 1 << 1;
+const FLAG_TIME_LOCK: u8 = 1 << 2;
+const FLAG_SUPPLY: u8 = 1 << 3;
+
+/// The Omnilock code hash of mainnet/testnet, the code hash of other networks must be given by
+/// the `omnilock` item of cell deps file.
+pub fn omnilock_type_hash(network: NetworkType) -> Option<H256> {
+    match network {
+        NetworkType::Mainnet => Some(OMNILOCK_TYPE_HASH_MAINNET),
+        NetworkType::Testnet => Some(OMNILOCK_TYPE_HASH_TESTNET),
+        _ => None,
+    }
+}
+
+pub fn is_omnilock_type_hash(code_hash: &H256) -> bool {
+    code_hash == &OMNILOCK_TYPE_HASH_MAINNET || code_hash == &OMNILOCK_TYPE_HASH_TESTNET
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OmnilockIdentity {
+    /// The blake160 hash of a secp256k1 public key
+    PubkeyHash(H160),
+    /// The hash160 of a multisig config
+    Multisig(H160),
+}
+
+/// Omnilock lock script args, only secp256k1 and multisig identities are supported (the
+/// administrator and supply modes are not supported).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OmnilockArgs {
+    pub identity: OmnilockIdentity,
+    /// Anyone-can-pay mode: minimum CKB and UDT (power of 10)
+    pub acp: Option<(u8, u8)>,
+    /// Time-lock mode: the since value
+    pub time_lock: Option<u64>,
+}
+
+impl OmnilockArgs {
+    pub fn new_pubkey_hash(lock_arg: H160) -> OmnilockArgs {
+        OmnilockArgs {
+            identity: OmnilockIdentity::PubkeyHash(lock_arg),
+            acp: None,
+            time_lock: None,
+        }
+    }
+
+    pub fn from_slice(args: &[u8]) -> Result<OmnilockArgs, String> {
+        if args.len() < 22 {
+            return Err(format!("Invalid omnilock args length: {}", args.len()));
+        }
+        let auth_content = H160::from_slice(&args[1..21]).unwrap();
+        let identity = match args[0] {
+            flag if flag == IdentityFlag::PubkeyHash as u8 => {
+                OmnilockIdentity::PubkeyHash(auth_content)
+            }
+            flag if flag == IdentityFlag::Multisig as u8 => {
+                OmnilockIdentity::Multisig(auth_content)
+            }
+            flag => return Err(format!("Unsupported omnilock identity flag: {}", flag)),
+        };
+        let flags = args[21];
+        if flags & (FLAG_ADMIN | FLAG_SUPPLY) != 0 {
+            return Err(String::from(
+                "Omnilock administrator mode and supply mode are not supported",
+            ));
+        }
+        if flags & !(FLAG_ACP | FLAG_TIME_LOCK) != 0 {
+            return Err(format!("Invalid omnilock flags: {:#04x}", flags));
+        }
+        let mut rest = &args[22..];
+        let acp = if flags & FLAG_ACP != 0 {
+            if rest.len() < 2 {
+                return Err(String::from(
+                    "Omnilock args too short for anyone-can-pay mode",
+                ));
+            }
+            let acp = (rest[0], rest[1]);
+            rest = &rest[2..];
+            Some(acp)
+        } else {
+            None
+        };
+        let time_lock = if flags & FLAG_TIME_LOCK != 0 {
+            if rest.len() < 8 {
+                return Err(String::from("Omnilock args too short for time-lock mode"));
+            }
+            let mut since_bytes = [0u8; 8];
+            since_bytes.copy_from_slice(&rest[..8]);
+            rest = &rest[8..];
+            Some(u64::from_le_bytes(since_bytes))
+        } else {
+            None
+        };
+        if !rest.is_empty() {
+            return Err(format!("Invalid omnilock args length: {}", args.len()));
+        }
+        Ok(OmnilockArgs {
+            identity,
+            acp,
+            time_lock,
+        })
+    }
+
+    pub fn auth_content(&self) -> &H160 {
+        match &self.identity {
+            OmnilockIdentity::PubkeyHash(hash160) | OmnilockIdentity::Multisig(hash160) => hash160,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let (identity_flag, auth_content) = match &self.identity {
+            OmnilockIdentity::PubkeyHash(hash160) => (IdentityFlag::PubkeyHash, hash160),
+            OmnilockIdentity::Multisig(hash160) => (IdentityFlag::Multisig, hash160),
+        };
+        let mut flags = 0;
+        if self.acp.is_some() {
+            flags |= FLAG_ACP;
+        }
+        if self.time_lock.is_some() {
+            flags |= FLAG_TIME_LOCK;
+        }
+        let mut bytes = BytesMut::with_capacity(32);
+        bytes.put_u8(identity_flag as u8);
+        bytes.put(auth_content.as_bytes());
+        bytes.put_u8(flags);
+        if let Some((ckb_minimum, udt_minimum)) = self.acp {
+            bytes.put_u8(ckb_minimum);
+            bytes.put_u8(udt_minimum);
+        }
+        if let Some(since) = self.time_lock {
+            bytes.put(&since.to_le_bytes()[..]);
+        }
+        bytes.freeze()
+    }
+
+    /// Human readable mode, like: `secp256k1`, `multisig+acp`
+    pub fn mode(&self) -> String {
+        let mut mode = match self.identity {
+            OmnilockIdentity::PubkeyHash(_) => String::from("secp256k1"),
+            OmnilockIdentity::Multisig(_) => String::from("multisig"),
+        };
+        if self.acp.is_some() {
+            mode.push_str("+acp");
+        }
+        if self.time_lock.is_some() {
+            mode.push_str("+time-lock");
+        }
+        mode
+    }
+
+    /// Build the sdk config, the multisig config is required by multisig identity
+    pub fn to_config(
+        &self,
+        multisig_config: Option<&MultisigConfig>,
+    ) -> Result<OmniLockConfig, String> {
+        let mut config = match &self.identity {
+            OmnilockIdentity::PubkeyHash(hash160) => {
+                OmniLockConfig::new_pubkey_hash(hash160.clone())
+            }
+            OmnilockIdentity::Multisig(hash160) => {
+                let multisig_config = multisig_config.ok_or_else(|| {
+                    format!("No multisig config found for omnilock auth: {:#x}", hash160)
+                })?;
+                if &multisig_config.hash160() != hash160 {
+                    return Err(format!(
+                        "Multisig config hash {:#x} not match omnilock auth: {:#x}",
+                        multisig_config.hash160(),
+                        hash160
+                    ));
+                }
+                OmniLockConfig::new_multisig(multisig_config.clone())
+            }
+        };
+        if let Some((ckb_minimum, udt_minimum)) = self.acp {
+            config.set_acp_config(OmniLockAcpConfig::new(ckb_minimum, udt_minimum));
+        }
+        if let Some(since) = self.time_lock {
+            config.set_time_lock_config(since);
+        }
+        Ok(config)
+    }
+}
+
+pub fn omnilock_script(code_hash: &H256, args: &OmnilockArgs) -> Script {
+    Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.to_bytes().pack())
+        .build()
+}
+
+/// The mainnet/testnet omnilock (secp256k1 mode) addresses of a key
+pub fn pubkey_hash_address_json(lock_arg: &H160) -> serde_json::Value {
+    let args = OmnilockArgs::new_pubkey_hash(lock_arg.clone());
+    let address = |network: NetworkType| {
+        let code_hash = omnilock_type_hash(network).expect("omnilock code hash");
+        let payload = AddressPayload::from(omnilock_script(&code_hash, &args));
+        Address::new(network, payload, true).to_string()
+    };
+    serde_json::json!({
+        "mainnet": address(NetworkType::Mainnet),
+        "testnet": address(NetworkType::Testnet),
+    })
+}
+
+/// The witness lock used to compute the signing message (all zeros, placeholder length)
+pub fn zero_lock(config: &OmniLockConfig) -> Result<Bytes, String> {
+    config
+        .zero_lock(OmniUnlockMode::Normal)
+        .map_err(|err| err.to_string())
+}
+
+/// The witness lock with signature (for multisig identity the signature is the multisig
+/// witness data with signatures)
+pub fn witness_lock(signature: Bytes) -> Bytes {
+    OmniLockWitnessLock::new_builder()
+        .signature(Some(signature).pack())
+        .build()
+        .as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::h160;
+
+    #[test]
+    fn test_omnilock_args() {
+        let lock_arg = h160!("0x6c8f2a0c7d6b1a3e4f5a6b7c8d9e0f1a2b3c4d5e");
+        let args = OmnilockArgs::new_pubkey_hash(lock_arg.clone());
+        let config = args.to_config(None).unwrap();
+        assert_eq!(args.to_bytes(), config.build_args());
+        assert_eq!(OmnilockArgs::from_slice(&args.to_bytes()), Ok(args.clone()));
+        assert_eq!(args.mode(), "secp256k1");
+
+        let acp_args = OmnilockArgs {
+            acp: Some((1, 2)),
+            time_lock: Some(0x2000_0000_0000_0064),
+            ..args
+        };
+        let config = acp_args.to_config(None).unwrap();
+        assert_eq!(acp_args.to_bytes(), config.build_args());
+        assert_eq!(acp_args.to_bytes().len(), config.get_args_len());
+        assert_eq!(
+            OmnilockArgs::from_slice(&acp_args.to_bytes()),
+            Ok(acp_args.clone())
+        );
+        assert_eq!(acp_args.mode(), "secp256k1+acp+time-lock");
+
+        let multisig_config = MultisigConfig::new_with(vec![lock_arg.clone()], 0, 1).unwrap();
+        let multisig_args = OmnilockArgs {
+            identity: OmnilockIdentity::Multisig(multisig_config.hash160()),
+            acp: None,
+            time_lock: None,
+        };
+        assert!(multisig_args.to_config(None).is_err());
+        let other_config =
+            MultisigConfig::new_with(vec![lock_arg.clone(), h160!("0x1")], 0, 1).unwrap();
+        assert!(multisig_args.to_config(Some(&other_config)).is_err());
+        let config = multisig_args.to_config(Some(&multisig_config)).unwrap();
+        assert_eq!(multisig_args.to_bytes(), config.build_args());
+
+        let mut bad_args = args_bytes(&acp_args);
+        bad_args.pop();
+        assert!(OmnilockArgs::from_slice(&bad_args).is_err());
+        let mut admin_args = args_bytes(&multisig_args);
+        admin_args[21] |= FLAG_ADMIN;
+        assert!(OmnilockArgs::from_slice(&admin_args).is_err());
+        let mut eth_args = args_bytes(&multisig_args);
+        eth_args[0] = IdentityFlag::Ethereum as u8;
+        assert!(OmnilockArgs::from_slice(&eth_args).is_err());
+    }
+
+    fn args_bytes(args: &OmnilockArgs) -> Vec<u8> {
+        args.to_bytes().to_vec()
+    }
+}
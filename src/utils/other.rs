@@ -331,6 +331,26 @@ pub fn address_json(payload: AddressPayload, is_new: bool) -> serde_json::Value
     })
 }
 
+/// Write rows to a CSV file, fields contain `,`, `"` or newlines are quoted
+pub fn write_csv(path: &str, header: &[&str], rows: &[Vec<String>]) -> Result<(), String> {
+    let mut content = header.join(",");
+    content.push('\n');
+    for row in rows {
+        let fields = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+        content.push_str(&fields.join(","));
+        content.push('\n');
+    }
+    fs::write(path, content).map_err(|err| format!("Write CSV file {} failed: {}", path, err))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
 pub fn calculate_type_id(first_cell_input: &CellInput, output_index: u64) -> [u8; 32] {
     let mut blake2b = new_blake2b();
     blake2b.update(first_cell_input.as_slice());
@@ -352,3 +372,16 @@ pub(crate) fn map_tx_builder_error_2_str(no_max_tx_fee: bool, err: TxBuilderErro
     }
     err.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("0x1234"), "0x1234");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field(""), "");
+    }
+}
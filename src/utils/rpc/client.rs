@@ -1,8 +1,9 @@
 use std::convert::TryInto;
 
 use ckb_jsonrpc_types::{
-    Alert, BlockNumber, CellWithStatus, EpochNumber, JsonBytes, OutputsValidator, Script,
+    Alert, BlockNumber, CellWithStatus, EpochNumber, JsonBytes, OutputsValidator, Script, Uint32,
 };
+use ckb_sdk::rpc::ckb_indexer::{Cell, Order, Pagination, SearchKey, Tx};
 
 use super::primitive;
 use super::types;
@@ -365,6 +366,20 @@ impl HttpRpcClient {
             .map(Into::into)
             .map_err(|err| err.to_string())
     }
+    /// The `min_rbf_rate` field of `tx_pool_info` (absent if the node does not support RBF)
+    pub fn min_rbf_rate(&mut self) -> Result<Option<u64>, String> {
+        let info: serde_json::Value = self
+            .client
+            .post("tx_pool_info", serde_json::Value::Null)
+            .map_err(|err| err.to_string())?;
+        info.get("min_rbf_rate")
+            .map(|value| {
+                serde_json::from_value::<ckb_jsonrpc_types::Uint64>(value.clone())
+                    .map(|rate| rate.value())
+                    .map_err(|err| err.to_string())
+            })
+            .transpose()
+    }
     pub fn clear_tx_pool(&mut self) -> Result<(), String> {
         self.client.clear_tx_pool().map_err(|err| err.to_string())
     }
@@ -411,4 +426,28 @@ impl HttpRpcClient {
             .notify_transaction(tx.into())
             .map_err(|err| err.to_string())
     }
+
+    // Indexer
+    pub fn get_cells(
+        &mut self,
+        search_key: SearchKey,
+        order: Order,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> Result<Pagination<Cell>, String> {
+        self.client
+            .get_cells(search_key, order, Uint32::from(limit), after)
+            .map_err(|err| err.to_string())
+    }
+    pub fn get_transactions(
+        &mut self,
+        search_key: SearchKey,
+        order: Order,
+        limit: u32,
+        after: Option<JsonBytes>,
+    ) -> Result<Pagination<Tx>, String> {
+        self.client
+            .get_transactions(search_key, order, Uint32::from(limit), after)
+            .map_err(|err| err.to_string())
+    }
 }
@@ -9,7 +9,7 @@ pub use ckb_jsonrpc_types::{
 use ckb_types::{core, packed, prelude::*, H256, U256};
 
 use super::primitive::{Capacity, EpochNumberWithFraction, Since, Timestamp};
-use crate::utils::rpc::json_rpc;
+use crate::utils::{multisig::MULTISIG_V2_CODE_HASH, rpc::json_rpc};
 use ckb_sdk::constants::{DAO_TYPE_HASH, MULTISIG_TYPE_HASH, SIGHASH_TYPE_HASH};
 
 type Version = u32;
@@ -101,6 +101,9 @@ impl Serialize for Script {
             } else {
                 String::new()
             }
+        } else if self.hash_type == ScriptHashType::Data1 && self.code_hash == MULTISIG_V2_CODE_HASH
+        {
+            String::from(" (multisig v2)")
         } else {
             String::new()
         };
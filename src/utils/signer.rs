@@ -201,6 +201,34 @@ impl KeyStoreHandlerSigner {
         Ok(())
     }
 
+    pub fn cache_key_set_by_index(
+        &mut self,
+        account: H160,
+        external_length: u32,
+        change_length: u32,
+    ) -> Result<(), String> {
+        let password = self.passwords.get(&account).cloned();
+        let key_set = self.handler.derived_key_set_by_index(
+            account.clone(),
+            0,
+            external_length,
+            0,
+            change_length,
+            password,
+        )?;
+        for (path, pubkey_hash) in key_set.external {
+            self.ids.insert(
+                pubkey_hash,
+                (path, Some(KeyChain::External), account.clone()),
+            );
+        }
+        for (path, pubkey_hash) in key_set.change {
+            self.ids
+                .insert(pubkey_hash, (path, Some(KeyChain::Change), account.clone()));
+        }
+        Ok(())
+    }
+
     fn get_id_info(&self, id: &[u8]) -> Option<(DerivationPath, Option<KeyChain>, H160)> {
         if id.len() != 20 {
             return None;
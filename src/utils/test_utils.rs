@@ -0,0 +1,23 @@
+//! Fixtures shared by the unit tests
+
+use ckb_jsonrpc_types as rpc_types;
+use ckb_sdk::constants::SIGHASH_TYPE_HASH;
+use ckb_types::{bytes::Bytes, core::ScriptHashType, packed::Script, prelude::*};
+
+use super::genesis_info::GenesisInfo;
+
+/// The sighash lock script of which the args are 20 bytes of `byte`
+pub fn sighash_lock(byte: u8) -> Script {
+    Script::new_builder()
+        .code_hash(SIGHASH_TYPE_HASH.pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(Bytes::from(vec![byte; 20]).pack())
+        .build()
+}
+
+/// The genesis info of the dev chain in `test-data/genesis_block.json`
+pub fn genesis_info() -> GenesisInfo {
+    let genesis_block: rpc_types::BlockView =
+        serde_json::from_str(include_str!("test-data/genesis_block.json")).unwrap();
+    GenesisInfo::from_block(&genesis_block.into()).unwrap()
+}
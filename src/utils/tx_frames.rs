@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use ckb_hash::blake2b_256;
+use data_encoding::{BASE32_NOPAD, HEXUPPER};
+
+/// The prefix of every frame
+pub const FRAME_PREFIX: &str = "CKBTX";
+
+/// The max number of frames of one content, the `total` of untrusted frames is bounded by it
+pub const MAX_FRAMES: usize = 10_000;
+
+/// Split the content into text frames, the format of a frame is:
+///
+///   `CKBTX:{seq}/{total}:{content-id}:{payload}:{checksum}`
+///
+///  * `seq` is 1-based sequence number, `total` is the number of frames
+///  * `content-id` is the first 8 bytes of blake2b hash of the whole content (hex)
+///  * `payload` is the base32 encoded chunk of the content
+///  * `checksum` is the first 4 bytes of blake2b hash of the frame text before it (hex)
+///
+/// Only uppercase letters, digits and `:/` are used, so the frames can also be encoded as
+/// QR codes in alphanumeric mode.
+pub fn encode_frames(content: &[u8], chunk_size: usize) -> Result<Vec<String>, String> {
+    if chunk_size == 0 {
+        return Err(String::from("Chunk size must be greater than 0"));
+    }
+    if content.is_empty() {
+        return Err(String::from("Can not encode empty content"));
+    }
+    let content_id = HEXUPPER.encode(&blake2b_256(content)[..8]);
+    let chunks = content.chunks(chunk_size).collect::<Vec<_>>();
+    let total = chunks.len();
+    if total > MAX_FRAMES {
+        return Err(format!(
+            "Too many frames: {}, the max number of frames is {}, use a larger chunk size",
+            total, MAX_FRAMES
+        ));
+    }
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let body = format!(
+                "{}:{}/{}:{}:{}",
+                FRAME_PREFIX,
+                idx + 1,
+                total,
+                content_id,
+                BASE32_NOPAD.encode(chunk)
+            );
+            let checksum = frame_checksum(&body);
+            format!("{}:{}", body, checksum)
+        })
+        .collect())
+}
+
+/// Reassemble the content from frames (in any order, duplicated frames and blank lines are
+/// ignored), every frame and the whole content are verified.
+pub fn decode_frames<'a, I: IntoIterator<Item = &'a str>>(frames: I) -> Result<Vec<u8>, String> {
+    let mut header: Option<(usize, String)> = None;
+    let mut chunks: BTreeMap<usize, Vec<u8>> = BTreeMap::default();
+    for frame in frames {
+        let frame = frame.trim();
+        if frame.is_empty() {
+            continue;
+        }
+        let parsed = Frame::parse(frame)?;
+        match header.as_ref() {
+            Some((total, content_id)) => {
+                if content_id != &parsed.content_id {
+                    return Err(format!(
+                        "Frame {}/{} belongs to another transaction (content-id: {}, expected: {})",
+                        parsed.seq, parsed.total, parsed.content_id, content_id
+                    ));
+                }
+                if *total != parsed.total {
+                    return Err(format!(
+                        "Frame {}/{} has inconsistent total, expected: {}",
+                        parsed.seq, parsed.total, total
+                    ));
+                }
+            }
+            None => {
+                header = Some((parsed.total, parsed.content_id.clone()));
+            }
+        }
+        if let Some(chunk) = chunks.get(&parsed.seq) {
+            if chunk != &parsed.payload {
+                return Err(format!("Conflicting frame {}/{}", parsed.seq, parsed.total));
+            }
+            continue;
+        }
+        chunks.insert(parsed.seq, parsed.payload);
+    }
+
+    let (total, content_id) = header.ok_or_else(|| String::from("No frame found"))?;
+    let missing = (1..=total)
+        .filter(|seq| !chunks.contains_key(seq))
+        .map(|seq| seq.to_string())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing frames (total {}): {}",
+            total,
+            missing.join(",")
+        ));
+    }
+    let content = chunks.into_values().flatten().collect::<Vec<u8>>();
+    let actual_id = HEXUPPER.encode(&blake2b_256(&content)[..8]);
+    if actual_id != content_id {
+        return Err(format!(
+            "Content verification failed, content-id: {}, expected: {}",
+            actual_id, content_id
+        ));
+    }
+    Ok(content)
+}
+
+struct Frame {
+    seq: usize,
+    total: usize,
+    content_id: String,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    fn parse(frame: &str) -> Result<Frame, String> {
+        let invalid = || format!("Invalid frame: {}", frame);
+        let (body, checksum) = frame.rsplit_once(':').ok_or_else(invalid)?;
+        if frame_checksum(body) != checksum {
+            return Err(format!("Frame checksum mismatch: {}", frame));
+        }
+        let parts = body.split(':').collect::<Vec<_>>();
+        if parts.len() != 4 || parts[0] != FRAME_PREFIX {
+            return Err(invalid());
+        }
+        let (seq, total) = parts[1].split_once('/').ok_or_else(invalid)?;
+        let seq: usize = seq.parse().map_err(|_| invalid())?;
+        let total: usize = total.parse().map_err(|_| invalid())?;
+        if total > MAX_FRAMES {
+            return Err(format!(
+                "Too many frames: {}, the max number of frames is {}",
+                total, MAX_FRAMES
+            ));
+        }
+        if seq == 0 || seq > total {
+            return Err(invalid());
+        }
+        let payload = BASE32_NOPAD
+            .decode(parts[3].as_bytes())
+            .map_err(|err| format!("Invalid frame payload: {}, frame: {}", err, frame))?;
+        Ok(Frame {
+            seq,
+            total,
+            content_id: parts[2].to_string(),
+            payload,
+        })
+    }
+}
+
+fn frame_checksum(body: &str) -> String {
+    HEXUPPER.encode(&blake2b_256(body.as_bytes())[..4])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let content = (0..1000u32)
+            .map(|idx| (idx % 251) as u8)
+            .collect::<Vec<_>>();
+        let frames = encode_frames(&content, 64).unwrap();
+        assert_eq!(frames.len(), 16);
+        assert!(frames[0].starts_with("CKBTX:1/16:"));
+        assert!(frames.iter().all(|frame| frame
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == ':' || c == '/')));
+
+        // any order, with duplicates and blank lines
+        let mut shuffled = frames.iter().rev().map(String::as_str).collect::<Vec<_>>();
+        shuffled.push(frames[3].as_str());
+        shuffled.push("");
+        assert_eq!(decode_frames(shuffled).unwrap(), content);
+
+        // missing frame
+        let err = decode_frames(frames.iter().skip(1).map(String::as_str)).unwrap_err();
+        assert!(err.contains("Missing frames"), "{}", err);
+
+        // corrupted frame
+        let mut corrupted = frames.clone();
+        corrupted[2] = corrupted[2].replacen("CKBTX:3/16:", "CKBTX:4/16:", 1);
+        assert!(decode_frames(corrupted.iter().map(String::as_str)).is_err());
+
+        // frames of other content
+        let other = encode_frames(b"other content", 64).unwrap();
+        let mut mixed = frames.clone();
+        mixed.push(other[0].clone());
+        assert!(decode_frames(mixed.iter().map(String::as_str)).is_err());
+
+        assert!(decode_frames(Vec::new()).is_err());
+        assert!(encode_frames(&content, 0).is_err());
+
+        // the total of a (well-formed) frame is bounded
+        let body = format!(
+            "{}:1/{}:{}:{}",
+            FRAME_PREFIX,
+            usize::max_value(),
+            HEXUPPER.encode(&blake2b_256(&content)[..8]),
+            BASE32_NOPAD.encode(&content)
+        );
+        let huge_frame = format!("{}:{}", body, frame_checksum(&body));
+        let err = decode_frames(vec![huge_frame.as_str()]).unwrap_err();
+        assert!(err.contains("Too many frames"), "{}", err);
+        assert!(encode_frames(&[1u8; MAX_FRAMES + 1], 1).is_err());
+    }
+}
@@ -1,8 +1,8 @@
-use ckb_hash::new_blake2b;
+use ckb_hash::{blake2b_256, new_blake2b};
 use ckb_jsonrpc_types as rpc_types;
 use ckb_types::{
     bytes::{Bytes, BytesMut},
-    core::{ScriptHashType, TransactionBuilder, TransactionView},
+    core::{Capacity, FeeRate, ScriptHashType, TransactionBuilder, TransactionView},
     h256,
     packed::{
         self, Byte32, CellDep, CellInput, CellOutput, OutPoint, Script, Transaction, WitnessArgs,
@@ -10,25 +10,42 @@ use ckb_types::{
     prelude::*,
     H160, H256,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::convert::TryInto;
 
-use ckb_sdk::constants::{MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH};
-use ckb_sdk::{unlock::MultisigConfig, Since};
+use ckb_sdk::constants::{
+    MULTISIG_TYPE_HASH, SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH,
+};
+use ckb_sdk::traits::{CellCollector, CellQueryOptions, ValueRangeOption};
+use ckb_sdk::{unlock::MultisigConfig, HumanCapacity, Since, SECP256K1};
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
 
+use crate::utils::cell_dep::CellDepItem;
 use crate::utils::genesis_info::GenesisInfo;
+use crate::utils::multisig::{MultisigScript, MULTISIG_V2_CODE_HASH};
+use crate::utils::omnilock::{self, is_omnilock_type_hash, OmnilockArgs, OmnilockIdentity};
 
 // TODO: Add dao support
 
-/// A transaction helper handle input/output with secp256k1(sighash/multisg) lock
+/// A transaction helper handle input/output with secp256k1(sighash/multisg) lock and omnilock
+/// (secp256k1/multisig identity)
 ///  1. Sign transaction
 ///  2. Inspect transaction information
 #[derive(Clone)]
 pub struct TxHelper {
     transaction: TransactionView,
     multisig_configs: HashMap<H160, MultisigConfig>,
-    // Only support sighash/multisig signatures
+    // The multisig script variant chosen by the user for the multisig configs, the inputs
+    // locked by the config take precedence
+    multisig_scripts: HashMap<H160, MultisigScript>,
+    // Only support sighash/multisig/omnilock signatures
     signatures: HashMap<Bytes, HashSet<Bytes>>,
+    // The snapshots of input cells (output and data), used to inspect and sign the transaction
+    // without the node
+    input_cells: HashMap<OutPoint, (CellOutput, Bytes)>,
+    // The Omnilock code hash of other networks (like dev chain), from the `omnilock` item of
+    // cell deps file
+    omnilock_code_hash: Option<H256>,
 }
 
 impl Default for TxHelper {
@@ -36,11 +53,35 @@ impl Default for TxHelper {
         TxHelper {
             transaction: TransactionBuilder::default().build(),
             multisig_configs: HashMap::default(),
+            multisig_scripts: HashMap::default(),
             signatures: HashMap::default(),
+            input_cells: HashMap::default(),
+            omnilock_code_hash: None,
         }
     }
 }
 
+/// The signing status of the inputs with same lock script
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningGroup {
+    pub lock_script: Script,
+    pub input_indexes: Vec<usize>,
+    /// The lock args of the keys can sign this group (empty for unknown lock or missing
+    /// multisig config)
+    pub signers: Vec<H160>,
+    pub require_first_n: u8,
+    /// The number of required signatures (0 for unknown lock or missing multisig config)
+    pub threshold: u8,
+    /// The number of collected signatures
+    pub signatures: usize,
+}
+
+impl SigningGroup {
+    pub fn is_complete(&self) -> bool {
+        self.threshold > 0 && self.signatures >= self.threshold as usize
+    }
+}
+
 type SignFn =
     dyn FnMut(&HashSet<H160>, &H256, &rpc_types::Transaction) -> Result<Option<[u8; 65]>, String>;
 
@@ -49,7 +90,10 @@ impl TxHelper {
         TxHelper {
             transaction,
             multisig_configs: HashMap::default(),
+            multisig_scripts: HashMap::default(),
             signatures: HashMap::default(),
+            input_cells: HashMap::default(),
+            omnilock_code_hash: None,
         }
     }
 
@@ -62,6 +106,178 @@ impl TxHelper {
     pub fn signatures(&self) -> &HashMap<Bytes, HashSet<Bytes>> {
         &self.signatures
     }
+    pub fn input_cells(&self) -> &HashMap<OutPoint, (CellOutput, Bytes)> {
+        &self.input_cells
+    }
+    pub fn omnilock_code_hash(&self) -> Option<&H256> {
+        self.omnilock_code_hash.as_ref()
+    }
+
+    /// Recognize the Omnilock deployed on other networks (the mainnet/testnet Omnilock is
+    /// always recognized)
+    pub fn set_omnilock_code_hash(&mut self, code_hash: H256) {
+        if !is_omnilock_type_hash(&code_hash) {
+            self.omnilock_code_hash = Some(code_hash);
+        }
+    }
+
+    fn is_omnilock(&self, code_hash: &H256) -> bool {
+        is_omnilock_type_hash(code_hash) || self.omnilock_code_hash.as_ref() == Some(code_hash)
+    }
+
+    fn check_lock(&self, lock: &Script, skip_check: bool) -> Result<(), String> {
+        check_lock_script_with(lock, skip_check, self.omnilock_code_hash.as_ref())
+    }
+
+    pub fn add_input_cell(&mut self, out_point: OutPoint, output: CellOutput, data: Bytes) {
+        self.input_cells.insert(out_point, (output, data));
+    }
+
+    /// Get the input cell from the snapshots (for offline usage)
+    pub fn get_input_cell(&self, out_point: &OutPoint) -> Result<(CellOutput, Bytes), String> {
+        self.input_cells.get(out_point).cloned().ok_or_else(|| {
+            format!(
+                "Input cell is not embedded in the transaction file: {}",
+                out_point
+            )
+        })
+    }
+
+    /// Check all the input cells are snapshotted (required in offline mode)
+    pub fn check_input_cells(&self) -> Result<(), String> {
+        for (idx, input) in self.transaction.inputs().into_iter().enumerate() {
+            if !self.input_cells.contains_key(&input.previous_output()) {
+                return Err(format!(
+                    "Input(no.{}) cell is not embedded in the transaction file: {}",
+                    idx + 1,
+                    input.previous_output()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the snapshots of input cells not snapshotted yet, and remove the snapshots of
+    /// removed inputs
+    pub fn snapshot_inputs<F: FnMut(OutPoint) -> Result<(CellOutput, Bytes), String>>(
+        &mut self,
+        mut get_live_cell: F,
+    ) -> Result<(), String> {
+        let out_points = self
+            .transaction
+            .inputs()
+            .into_iter()
+            .map(|input| input.previous_output())
+            .collect::<HashSet<_>>();
+        self.input_cells
+            .retain(|out_point, _| out_points.contains(out_point));
+        for out_point in out_points {
+            if let Entry::Vacant(entry) = self.input_cells.entry(out_point) {
+                let cell = get_live_cell(entry.key().clone())?;
+                entry.insert(cell);
+            }
+        }
+        Ok(())
+    }
+
+    /// The multisig script variant of the inputs locked by the multisig config (by the input
+    /// cell snapshots), the variant set by `set_multisig_script` (legacy by default) if no such
+    /// input
+    pub fn multisig_script(&self, hash160: &H160) -> MultisigScript {
+        self.input_cells
+            .values()
+            .find_map(|(output, _)| {
+                let lock = output.lock();
+                MultisigScript::from_lock(&lock)
+                    .filter(|_| lock.args().raw_data().get(..20) == Some(hash160.as_bytes()))
+            })
+            .or_else(|| self.multisig_scripts.get(hash160).copied())
+            .unwrap_or_default()
+    }
+
+    /// Set the multisig script variant of the multisig config, used when no input is locked by
+    /// the config
+    pub fn set_multisig_script(&mut self, hash160: H160, multisig_script: MultisigScript) {
+        self.multisig_scripts.insert(hash160, multisig_script);
+    }
+
+    /// The signing status of all input groups, by the input cell snapshots
+    pub fn signing_groups(&self) -> Result<Vec<SigningGroup>, String> {
+        let mut groups: Vec<SigningGroup> = Vec::new();
+        for (idx, input) in self.transaction.inputs().into_iter().enumerate() {
+            let out_point = input.previous_output();
+            let lock = self
+                .input_cells
+                .get(&out_point)
+                .map(|(output, _)| output.lock())
+                .ok_or_else(|| {
+                    format!("Missing input(no.{}) cell snapshot: {}", idx + 1, out_point)
+                })?;
+            if let Some(group) = groups.iter_mut().find(|group| group.lock_script == lock) {
+                group.input_indexes.push(idx);
+                continue;
+            }
+            let lock_arg = lock.args().raw_data();
+            let code_hash: H256 = lock.code_hash().unpack();
+            let multisig_hash160 = if code_hash == SIGHASH_TYPE_HASH && lock_arg.len() == 20 {
+                None
+            } else if MultisigScript::from_lock(&lock).is_some() && lock_arg.len() >= 20 {
+                Some(H160::from_slice(&lock_arg[..20]).unwrap())
+            } else {
+                match OmnilockArgs::from_slice(&lock_arg) {
+                    Ok(args) if self.is_omnilock(&code_hash) => match args.identity {
+                        OmnilockIdentity::Multisig(hash160) => Some(hash160),
+                        OmnilockIdentity::PubkeyHash(_) => None,
+                    },
+                    _ => {
+                        groups.push(SigningGroup {
+                            lock_script: lock,
+                            input_indexes: vec![idx],
+                            signers: Vec::new(),
+                            require_first_n: 0,
+                            threshold: 0,
+                            signatures: 0,
+                        });
+                        continue;
+                    }
+                }
+            };
+            let (signers, require_first_n, threshold) = match multisig_hash160 {
+                Some(hash160) => self
+                    .multisig_configs
+                    .get(&hash160)
+                    .map(|config| {
+                        (
+                            config.sighash_addresses().clone(),
+                            config.require_first_n(),
+                            config.threshold(),
+                        )
+                    })
+                    .unwrap_or_default(),
+                None if code_hash == SIGHASH_TYPE_HASH => {
+                    (vec![H160::from_slice(&lock_arg).unwrap()], 0, 1)
+                }
+                None => {
+                    let args = OmnilockArgs::from_slice(&lock_arg)?;
+                    (vec![args.auth_content().clone()], 0, 1)
+                }
+            };
+            let signatures = self
+                .signatures
+                .get(&lock_arg)
+                .map(|signatures| signatures.len())
+                .unwrap_or(0);
+            groups.push(SigningGroup {
+                lock_script: lock,
+                input_indexes: vec![idx],
+                signers,
+                require_first_n,
+                threshold,
+                signatures,
+            });
+        }
+        Ok(groups)
+    }
 
     pub fn clear_inputs(&mut self) {
         self.transaction = self
@@ -69,6 +285,7 @@ impl TxHelper {
             .as_advanced_builder()
             .set_inputs(Vec::new())
             .build();
+        self.input_cells.clear();
     }
     pub fn clear_outputs(&mut self) {
         self.transaction = self
@@ -82,25 +299,33 @@ impl TxHelper {
         self.signatures.clear();
     }
 
+    /// Add an input, the cell deps of sighash/multisig (legacy) inputs are set by genesis info,
+    /// the Omnilock cell dep is added when `omnilock` (the item of cell deps file) is given.
     pub fn add_input<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &mut self,
         out_point: OutPoint,
         since_absolute_epoch_opt: Option<u64>,
         mut get_live_cell: F,
         genesis_info: &GenesisInfo,
+        omnilock: Option<&CellDepItem>,
         skip_check: bool,
     ) -> Result<(), String> {
+        if let Some(item) = omnilock {
+            self.set_omnilock_code_hash(item.script_id.code_hash.clone());
+        }
         let lock = get_live_cell(out_point.clone(), false)?.lock();
-        check_lock_script(&lock, skip_check)?;
+        self.check_lock(&lock, skip_check)?;
 
         let since = if let Some(number) = since_absolute_epoch_opt {
             Since::new_absolute_epoch(number).value()
         } else {
             let lock_arg = lock.args().raw_data();
-            if lock.code_hash() == MULTISIG_TYPE_HASH.pack() && lock_arg.len() == 28 {
+            if MultisigScript::from_lock(&lock).is_some() && lock_arg.len() == 28 {
                 let mut since_bytes = [0u8; 8];
                 since_bytes.copy_from_slice(&lock_arg[20..]);
                 u64::from_le_bytes(since_bytes)
+            } else if self.is_omnilock(&lock.code_hash().unpack()) {
+                OmnilockArgs::from_slice(&lock_arg)?.time_lock.unwrap_or(0)
             } else {
                 0
             }
@@ -112,14 +337,25 @@ impl TxHelper {
             .build();
 
         self.transaction = self.transaction.as_advanced_builder().input(input).build();
-        let mut cell_deps: HashSet<CellDep> = HashSet::default();
+        // The cell deps not from genesis (like omnilock and multisig v2) are kept
+        let genesis_deps = [genesis_info.sighash_dep(), genesis_info.multisig_dep()];
+        let mut cell_deps: HashSet<CellDep> = self
+            .transaction
+            .cell_deps()
+            .into_iter()
+            .filter(|cell_dep| !genesis_deps.contains(cell_dep))
+            .collect();
         for ((code_hash, _), _) in self.input_group(get_live_cell, skip_check)?.into_iter() {
             let code_hash: H256 = code_hash.unpack();
             if code_hash == SIGHASH_TYPE_HASH {
                 cell_deps.insert(genesis_info.sighash_dep());
             } else if code_hash == MULTISIG_TYPE_HASH {
                 cell_deps.insert(genesis_info.multisig_dep());
-            } else {
+            } else if self.is_omnilock(&code_hash) {
+                if let Some(item) = omnilock.filter(|item| item.script_id.code_hash == code_hash) {
+                    cell_deps.insert(item.cell_dep.clone().into());
+                }
+            } else if code_hash != MULTISIG_V2_CODE_HASH {
                 panic!("Unexpected input code_hash: {:#x}", code_hash);
             }
         }
@@ -131,6 +367,90 @@ impl TxHelper {
         Ok(())
     }
 
+    /// Collect live cells (without type script and data) of `from_lock` as inputs to pay the
+    /// outputs and the transaction fee of `fee_rate` (shannons/KB), the rest capacity goes to a
+    /// new change output of `change_lock`. The input cells must be snapshotted.
+    ///
+    /// Return the number of added inputs, the change capacity and the transaction fee.
+    pub fn balance<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
+        &mut self,
+        from_lock: &Script,
+        change_lock: &Script,
+        fee_rate: u64,
+        cell_collector: &mut dyn CellCollector,
+        mut get_live_cell: F,
+        genesis_info: &GenesisInfo,
+    ) -> Result<(usize, u64, u64), String> {
+        let mut added_inputs = 0;
+        loop {
+            let tx = self.placeholder_tx()?;
+            let mut input_total: u64 = 0;
+            for out_point in tx.input_pts_iter() {
+                let capacity: u64 = self.get_input_cell(&out_point)?.0.capacity().unpack();
+                input_total += capacity;
+            }
+            let output_total: u64 = tx
+                .outputs()
+                .into_iter()
+                .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+                .sum();
+            let change_output = CellOutput::new_builder()
+                .lock(change_lock.clone())
+                .build_exact_capacity(Capacity::zero())
+                .map_err(|err| err.to_string())?;
+            let min_change: u64 = change_output.capacity().unpack();
+            let tx_with_change = tx
+                .as_advanced_builder()
+                .output(change_output.clone())
+                .output_data(Bytes::new().pack())
+                .build();
+            let fee = FeeRate::from_u64(fee_rate)
+                .fee(tx_with_change.data().as_reader().serialized_size_in_block() as u64)
+                .as_u64();
+            // The change output must hold its occupied capacity, dust is not left as fee
+            let required = output_total + fee + min_change;
+            if input_total >= required {
+                let change_capacity = input_total - output_total - fee;
+                let change_output = change_output
+                    .as_builder()
+                    .capacity(change_capacity.pack())
+                    .build();
+                self.add_output(change_output, Bytes::new());
+                return Ok((added_inputs, change_capacity, fee));
+            }
+
+            let mut query = CellQueryOptions::new_lock(from_lock.clone());
+            query.secondary_script_len_range = Some(ValueRangeOption::new_exact(0));
+            query.data_len_range = Some(ValueRangeOption::new_exact(0));
+            query.min_total_capacity = required - input_total;
+            let (cells, _) = cell_collector
+                .collect_live_cells(&query, true)
+                .map_err(|err| err.to_string())?;
+            let cells = cells
+                .into_iter()
+                .filter(|cell| !self.input_cells.contains_key(&cell.out_point))
+                .collect::<Vec<_>>();
+            if cells.is_empty() {
+                return Err(format!(
+                    "Capacity not enough, need {:#} more",
+                    HumanCapacity(required - input_total)
+                ));
+            }
+            for cell in cells {
+                self.add_input(
+                    cell.out_point.clone(),
+                    None,
+                    &mut get_live_cell,
+                    genesis_info,
+                    None,
+                    false,
+                )?;
+                self.add_input_cell(cell.out_point, cell.output, cell.output_data);
+                added_inputs += 1;
+            }
+        }
+    }
+
     pub fn add_output(&mut self, output: CellOutput, data: Bytes) {
         // TODO: Check output(lock-script/type-script)
         self.transaction = self
@@ -141,8 +461,105 @@ impl TxHelper {
             .build()
     }
 
+    /// Add a cell dep, return false if it already exists
+    pub fn add_cell_dep(&mut self, cell_dep: CellDep) -> bool {
+        if self
+            .transaction
+            .cell_deps()
+            .into_iter()
+            .any(|dep| dep == cell_dep)
+        {
+            return false;
+        }
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .cell_dep(cell_dep)
+            .build();
+        true
+    }
+
+    /// Add a header dep, return false if it already exists
+    pub fn add_header_dep(&mut self, block_hash: Byte32) -> bool {
+        if self
+            .transaction
+            .header_deps()
+            .into_iter()
+            .any(|hash| hash == block_hash)
+        {
+            return false;
+        }
+        self.transaction = self
+            .transaction
+            .as_advanced_builder()
+            .header_dep(block_hash)
+            .build();
+        true
+    }
+
+    /// The type id args of the output at `output_index`, computed from the first input
+    pub fn type_id_args(&self, output_index: u64) -> Result<Bytes, String> {
+        let first_input = self
+            .transaction
+            .inputs()
+            .get(0)
+            .ok_or_else(|| String::from("Type id requires at least one input"))?;
+        let mut blake2b = new_blake2b();
+        blake2b.update(first_input.as_slice());
+        blake2b.update(&output_index.to_le_bytes());
+        let mut ret = [0u8; 32];
+        blake2b.finalize(&mut ret);
+        Ok(Bytes::from(ret.to_vec()))
+    }
+
+    /// Check the args of the new type id outputs (no input has the same type script) are
+    /// computed from the current first input, they must be re-added after the inputs changed
+    pub fn check_type_id_outputs<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
+        &self,
+        mut get_live_cell: F,
+    ) -> Result<(), String> {
+        let type_id_outputs = self
+            .transaction
+            .outputs()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, output)| output.type_().to_opt().map(|script| (idx, script)))
+            .filter(|(_, script)| {
+                script.code_hash() == TYPE_ID_CODE_HASH.pack()
+                    && script.hash_type() == ScriptHashType::Type.into()
+            })
+            .collect::<Vec<_>>();
+        if type_id_outputs.is_empty() {
+            return Ok(());
+        }
+        let mut input_type_scripts = HashSet::new();
+        for input in self.transaction.inputs().into_iter() {
+            if let Some(script) = get_live_cell(input.previous_output(), false)?
+                .type_()
+                .to_opt()
+            {
+                input_type_scripts.insert(script);
+            }
+        }
+        for (idx, script) in type_id_outputs {
+            if input_type_scripts.contains(&script) {
+                continue;
+            }
+            if script.args().raw_data() != self.type_id_args(idx as u64)? {
+                return Err(format!(
+                    "Output(no.{}) type id args is not computed from the first input, the inputs changed after the output is added with `--type-id`",
+                    idx + 1
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn add_signature(&mut self, lock_arg: Bytes, signature: Bytes) -> Result<bool, String> {
-        if lock_arg.len() != 20 && lock_arg.len() != 28 {
+        if lock_arg.len() != 20
+            && lock_arg.len() != 28
+            && OmnilockArgs::from_slice(&lock_arg).is_err()
+        {
             return Err(format!(
                 "Invalid lock_arg(0x{}) length({}) with signature(0x{})",
                 hex_string(lock_arg.as_ref()),
@@ -176,13 +593,22 @@ impl TxHelper {
         let mut input_group: HashMap<(Byte32, Bytes), Vec<usize>> = HashMap::default();
         for (idx, input) in self.transaction.inputs().into_iter().enumerate() {
             let lock = get_live_cell(input.previous_output(), false)?.lock();
-            check_lock_script(&lock, skip_check)
+            self.check_lock(&lock, skip_check)
                 .map_err(|err| format!("Input(no.{}) {}", idx + 1, err))?;
 
             let lock_arg = lock.args().raw_data();
             let code_hash = lock.code_hash();
-            if code_hash == MULTISIG_TYPE_HASH.pack() {
-                let hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
+            let multisig_hash160 = if MultisigScript::from_code_hash(&code_hash).is_some() {
+                Some(H160::from_slice(&lock_arg[..20]).unwrap())
+            } else if self.is_omnilock(&code_hash.unpack()) {
+                match OmnilockArgs::from_slice(&lock_arg)?.identity {
+                    OmnilockIdentity::Multisig(hash160) => Some(hash160),
+                    OmnilockIdentity::PubkeyHash(_) => None,
+                }
+            } else {
+                None
+            };
+            if let Some(hash160) = multisig_hash160 {
                 if !self.multisig_configs.contains_key(&hash160) {
                     return Err(format!(
                         "No mutisig config found for input(no.{}) lock_arg prefix: {:#x}",
@@ -237,20 +663,29 @@ impl TxHelper {
         for ((code_hash, lock_arg), idxs) in
             self.input_group(get_live_cell, skip_check)?.into_iter()
         {
-            if code_hash != SIGHASH_TYPE_HASH.pack() && code_hash != MULTISIG_TYPE_HASH.pack() {
-                continue;
-            }
-
-            let multisig_hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
-            let lock_args = if code_hash == MULTISIG_TYPE_HASH.pack() {
+            let lock_args = if code_hash == SIGHASH_TYPE_HASH.pack() {
+                let mut lock_args = HashSet::default();
+                lock_args.insert(H160::from_slice(lock_arg.as_ref()).unwrap());
+                lock_args
+            } else if MultisigScript::from_code_hash(&code_hash).is_some() {
+                let multisig_hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
                 all_sighash_lock_args
                     .get(&multisig_hash160)
                     .unwrap()
                     .clone()
+            } else if self.is_omnilock(&code_hash.unpack()) {
+                match OmnilockArgs::from_slice(&lock_arg)?.identity {
+                    OmnilockIdentity::PubkeyHash(hash160) => {
+                        let mut lock_args = HashSet::default();
+                        lock_args.insert(hash160);
+                        lock_args
+                    }
+                    OmnilockIdentity::Multisig(hash160) => {
+                        all_sighash_lock_args.get(&hash160).unwrap().clone()
+                    }
+                }
             } else {
-                let mut lock_args = HashSet::default();
-                lock_args.insert(H160::from_slice(lock_arg.as_ref()).unwrap());
-                lock_args
+                continue;
             };
             if signer(&lock_args, &h256!("0x0"), &Transaction::default().into())?.is_some() {
                 let signature = build_signature(
@@ -258,7 +693,7 @@ impl TxHelper {
                     input_size,
                     &idxs,
                     &witnesses,
-                    self.multisig_configs.get(&multisig_hash160),
+                    self.lock_placeholder(&code_hash, &lock_arg)?,
                     |message: &H256, tx: &rpc_types::Transaction| {
                         signer(&lock_args, message, tx).map(|sig| sig.unwrap())
                     },
@@ -271,9 +706,12 @@ impl TxHelper {
 
     pub fn build_tx<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &self,
-        get_live_cell: F,
+        mut get_live_cell: F,
         skip_check: bool,
     ) -> Result<TransactionView, String> {
+        if !skip_check {
+            self.check_type_id_outputs(&mut get_live_cell)?;
+        }
         let mut witnesses = self.init_witnesses();
         for ((code_hash, lock_arg), idxs) in
             self.input_group(get_live_cell, skip_check)?.into_iter()
@@ -282,9 +720,12 @@ impl TxHelper {
                 continue;
             }
             let signatures = self.signatures.get(&lock_arg).ok_or_else(|| {
+                let hash_type = MultisigScript::from_code_hash(&code_hash)
+                    .map(MultisigScript::hash_type)
+                    .unwrap_or(ScriptHashType::Type);
                 let lock_script = rpc_types::Script::from(
                     Script::new_builder()
-                        .hash_type(ScriptHashType::Type.into())
+                        .hash_type(hash_type.into())
                         .code_hash(code_hash.clone())
                         .args(lock_arg.pack())
                         .build(),
@@ -294,32 +735,25 @@ impl TxHelper {
                     serde_json::to_string_pretty(&lock_script).unwrap()
                 )
             })?;
-            let lock_field = if code_hash == MULTISIG_TYPE_HASH.pack() {
+            let lock_field = if MultisigScript::from_code_hash(&code_hash).is_some() {
                 let hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
-                let multisig_config = self.multisig_configs.get(&hash160).unwrap();
-                let threshold = multisig_config.threshold() as usize;
-                let mut data = BytesMut::from(&multisig_config.to_witness_data()[..]);
-                if signatures.len() != threshold {
-                    return Err(format!(
-                        "Invalid multisig signature length for lock_arg: 0x{}, got: {}, expected: {}",
-                        hex_string(&lock_arg),
-                        signatures.len(),
-                        threshold,
-                    ));
-                }
-                for signature in signatures {
-                    data.extend_from_slice(signature.as_ref());
-                }
-                data.freeze()
+                multisig_lock_field(
+                    self.multisig_configs.get(&hash160).unwrap(),
+                    &lock_arg,
+                    signatures,
+                )?
+            } else if self.is_omnilock(&code_hash.unpack()) {
+                let signature = match OmnilockArgs::from_slice(&lock_arg)?.identity {
+                    OmnilockIdentity::PubkeyHash(_) => secp_lock_field(&lock_arg, signatures)?,
+                    OmnilockIdentity::Multisig(hash160) => multisig_lock_field(
+                        self.multisig_configs.get(&hash160).unwrap(),
+                        &lock_arg,
+                        signatures,
+                    )?,
+                };
+                omnilock::witness_lock(signature)
             } else {
-                if signatures.len() != 1 {
-                    return Err(format!(
-                        "Invalid secp signature length for lock_arg: 0x{}, got: {}, expected: 1",
-                        hex_string(&lock_arg),
-                        signatures.len(),
-                    ));
-                }
-                signatures.iter().last().unwrap().clone()
+                secp_lock_field(&lock_arg, signatures)?
             };
 
             let init_witness = if witnesses[idxs[0]].raw_data().is_empty() {
@@ -342,6 +776,198 @@ impl TxHelper {
             .build())
     }
 
+    /// The transaction with placeholder witness lock (all zero signatures) of every input group,
+    /// used to estimate the transaction size before signing
+    pub fn placeholder_tx(&self) -> Result<TransactionView, String> {
+        let mut witnesses = self.init_witnesses();
+        for group in self.signing_groups()? {
+            let lock_arg = group.lock_script.args().raw_data();
+            if group.threshold == 0 {
+                if self.check_lock(&group.lock_script, false).is_ok() {
+                    return Err(format!(
+                        "No multisig config found for lock_arg: 0x{}",
+                        hex_string(&lock_arg)
+                    ));
+                }
+                continue;
+            }
+            let idx = group.input_indexes[0];
+            let init_witness = if witnesses[idx].raw_data().is_empty() {
+                WitnessArgs::default()
+            } else {
+                WitnessArgs::from_slice(witnesses[idx].raw_data().as_ref())
+                    .map_err(|err| err.to_string())?
+            };
+            let lock_placeholder =
+                self.lock_placeholder(&group.lock_script.code_hash(), &lock_arg)?;
+            witnesses[idx] = init_witness
+                .as_builder()
+                .lock(Some(lock_placeholder).pack())
+                .build()
+                .as_bytes()
+                .pack();
+        }
+        Ok(self
+            .transaction
+            .as_advanced_builder()
+            .set_witnesses(witnesses)
+            .build())
+    }
+
+    /// Merge the multisig configs, input cell snapshots and signatures of another helper of the
+    /// same transaction, the signatures are not verified (see `check_signatures`)
+    pub fn merge(&mut self, other: TxHelper) -> Result<(), String> {
+        if self.transaction.hash() != other.transaction.hash() {
+            return Err(format!(
+                "Transaction hash mismatch: {:#x}, expected: {:#x}",
+                other.transaction.hash(),
+                self.transaction.hash()
+            ));
+        }
+        if self.omnilock_code_hash.is_none() {
+            self.omnilock_code_hash = other.omnilock_code_hash;
+        }
+        for (hash160, config) in other.multisig_configs {
+            self.multisig_configs.entry(hash160).or_insert(config);
+        }
+        for (hash160, multisig_script) in other.multisig_scripts {
+            self.multisig_scripts
+                .entry(hash160)
+                .or_insert(multisig_script);
+        }
+        for (out_point, cell) in other.input_cells {
+            match self.input_cells.get(&out_point) {
+                Some(existing) if existing != &cell => {
+                    return Err(format!("Conflicting input cell snapshot: {}", out_point));
+                }
+                Some(_) => {}
+                None => {
+                    self.input_cells.insert(out_point, cell);
+                }
+            }
+        }
+        for (lock_arg, signatures) in other.signatures {
+            for signature in signatures {
+                self.add_signature(lock_arg.clone(), signature)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify all signatures, every signer can only sign once for an input group and the
+    /// signatures can not exceed the threshold
+    pub fn check_signatures(&self) -> Result<(), String> {
+        let thresholds = self
+            .signing_groups()?
+            .into_iter()
+            .map(|group| {
+                (
+                    group.lock_script.args().raw_data(),
+                    group.threshold as usize,
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        for (lock_arg, signatures) in &self.signatures {
+            let mut signers: HashSet<H160> = HashSet::default();
+            for signature in signatures {
+                let signer = self.verify_signature(lock_arg, signature)?;
+                if !signers.insert(signer.clone()) {
+                    return Err(format!(
+                        "Conflicting signatures of signer {:#x} for lock_arg: 0x{}",
+                        signer,
+                        hex_string(lock_arg)
+                    ));
+                }
+            }
+            let threshold = thresholds.get(lock_arg).cloned().unwrap_or(0);
+            if signatures.len() > threshold {
+                return Err(format!(
+                    "Too many signatures for lock_arg: 0x{}, got: {}, threshold: {}",
+                    hex_string(lock_arg),
+                    signatures.len(),
+                    threshold
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify a signature of the input group with `lock_arg` against the signing message,
+    /// return the lock arg of the signer key. The input cell snapshots are required.
+    pub fn verify_signature(&self, lock_arg: &Bytes, signature: &Bytes) -> Result<H160, String> {
+        let group = self
+            .signing_groups()?
+            .into_iter()
+            .find(|group| group.lock_script.args().raw_data() == lock_arg)
+            .ok_or_else(|| {
+                format!(
+                    "No input group found for lock_arg: 0x{}",
+                    hex_string(lock_arg)
+                )
+            })?;
+        if group.threshold == 0 {
+            return Err(format!(
+                "Can not sign input group with lock_arg: 0x{} (unknown lock or missing multisig config)",
+                hex_string(lock_arg)
+            ));
+        }
+        if signature.len() != SECP_SIGNATURE_SIZE {
+            return Err(format!("Invalid signature length: {}", signature.len()));
+        }
+        let mut message_opt = None;
+        build_signature(
+            &self.transaction,
+            self.transaction.inputs().len(),
+            &group.input_indexes,
+            &self.init_witnesses(),
+            self.lock_placeholder(&group.lock_script.code_hash(), lock_arg)?,
+            |message: &H256, _tx: &rpc_types::Transaction| {
+                message_opt = Some(message.clone());
+                Ok([0u8; SECP_SIGNATURE_SIZE])
+            },
+        )?;
+        let message = message_opt.expect("signing message");
+        let recovery_id =
+            RecoveryId::from_i32(signature[64] as i32).map_err(|err| err.to_string())?;
+        let recoverable_signature =
+            RecoverableSignature::from_compact(&signature[0..64], recovery_id)
+                .map_err(|err| err.to_string())?;
+        let pubkey = SECP256K1
+            .recover_ecdsa(
+                &secp256k1::Message::from_slice(message.as_bytes()).unwrap(),
+                &recoverable_signature,
+            )
+            .map_err(|err| format!("Recover pubkey from signature failed: {}", err))?;
+        let signer = H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).unwrap();
+        if !group.signers.contains(&signer) {
+            return Err(format!(
+                "The signature(0x{}) is not signed by any required signer of lock_arg: 0x{}",
+                hex_string(signature),
+                hex_string(lock_arg)
+            ));
+        }
+        Ok(signer)
+    }
+
+    /// The witness lock (without signatures) used to compute the signing message
+    fn lock_placeholder(&self, code_hash: &Byte32, lock_arg: &Bytes) -> Result<Bytes, String> {
+        if MultisigScript::from_code_hash(code_hash).is_some() {
+            let hash160 = H160::from_slice(&lock_arg[..20]).unwrap();
+            let multisig_config = self.multisig_configs.get(&hash160).unwrap();
+            let sig_len = (multisig_config.threshold() as usize) * SECP_SIGNATURE_SIZE;
+            let mut data = BytesMut::from(&multisig_config.to_witness_data()[..]);
+            data.extend_from_slice(vec![0u8; sig_len].as_slice());
+            Ok(data.freeze())
+        } else if self.is_omnilock(&code_hash.unpack()) {
+            let args = OmnilockArgs::from_slice(lock_arg)?;
+            let multisig_config = self.multisig_configs.get(args.auth_content());
+            let config = args.to_config(multisig_config)?;
+            omnilock::zero_lock(&config)
+        } else {
+            Ok(Bytes::from(vec![0u8; SECP_SIGNATURE_SIZE]))
+        }
+    }
+
     pub fn check_tx<F: FnMut(OutPoint, bool) -> Result<CellOutput, String>>(
         &self,
         mut get_live_cell: F,
@@ -360,7 +986,7 @@ impl TxHelper {
             let capacity: u64 = output.capacity().unpack();
             input_total += capacity;
 
-            check_lock_script(&output.lock(), false)
+            self.check_lock(&output.lock(), false)
                 .map_err(|err| format!("Input(no.{}) {}", i + 1, err))?;
         }
 
@@ -370,7 +996,7 @@ impl TxHelper {
             let capacity: u64 = output.capacity().unpack();
             output_total += capacity;
 
-            check_lock_script(&output.lock(), false)
+            self.check_lock(&output.lock(), false)
                 .map_err(|err| format!("Output(no.{}) {}", i + 1, err))?;
         }
 
@@ -382,11 +1008,53 @@ pub type SignerFn = Box<
     dyn FnMut(&HashSet<H160>, &H256, &rpc_types::Transaction) -> Result<Option<[u8; 65]>, String>,
 >;
 
+fn secp_lock_field(lock_arg: &Bytes, signatures: &HashSet<Bytes>) -> Result<Bytes, String> {
+    if signatures.len() != 1 {
+        return Err(format!(
+            "Invalid secp signature length for lock_arg: 0x{}, got: {}, expected: 1",
+            hex_string(lock_arg),
+            signatures.len(),
+        ));
+    }
+    Ok(signatures.iter().last().unwrap().clone())
+}
+
+fn multisig_lock_field(
+    multisig_config: &MultisigConfig,
+    lock_arg: &Bytes,
+    signatures: &HashSet<Bytes>,
+) -> Result<Bytes, String> {
+    let threshold = multisig_config.threshold() as usize;
+    let mut data = BytesMut::from(&multisig_config.to_witness_data()[..]);
+    if signatures.len() != threshold {
+        return Err(format!(
+            "Invalid multisig signature length for lock_arg: 0x{}, got: {}, expected: {}",
+            hex_string(lock_arg),
+            signatures.len(),
+            threshold,
+        ));
+    }
+    for signature in signatures {
+        data.extend_from_slice(signature.as_ref());
+    }
+    Ok(data.freeze())
+}
+
 pub fn check_lock_script(lock: &Script, skip_check: bool) -> Result<(), String> {
+    check_lock_script_with(lock, skip_check, None)
+}
+
+/// Check the lock script, the Omnilock of other networks is recognized by `omnilock_code_hash`
+pub fn check_lock_script_with(
+    lock: &Script,
+    skip_check: bool,
+    omnilock_code_hash: Option<&H256>,
+) -> Result<(), String> {
     #[derive(Eq, PartialEq)]
     enum CodeHashCategory {
         Sighash,
-        Multisig,
+        Multisig(MultisigScript),
+        Omnilock,
         Other,
     }
 
@@ -396,8 +1064,10 @@ pub fn check_lock_script(lock: &Script, skip_check: bool) -> Result<(), String>
 
     let code_hash_category = if code_hash == SIGHASH_TYPE_HASH {
         CodeHashCategory::Sighash
-    } else if code_hash == MULTISIG_TYPE_HASH {
-        CodeHashCategory::Multisig
+    } else if let Some(variant) = MultisigScript::from_code_hash(&lock.code_hash()) {
+        CodeHashCategory::Multisig(variant)
+    } else if is_omnilock_type_hash(&code_hash) || omnilock_code_hash == Some(&code_hash) {
+        CodeHashCategory::Omnilock
     } else {
         CodeHashCategory::Other
     };
@@ -410,15 +1080,29 @@ pub fn check_lock_script(lock: &Script, skip_check: bool) -> Result<(), String>
 
     match (code_hash_category, hash_type, lock_args.len()) {
         (CodeHashCategory::Sighash, ScriptHashType::Type, 20) => Ok(()),
-        (CodeHashCategory::Multisig, ScriptHashType::Type, 20) => Ok(()),
-        (CodeHashCategory::Multisig, ScriptHashType::Type, 28) => Ok(()),
+        (CodeHashCategory::Multisig(variant), hash_type, 20 | 28)
+            if hash_type == variant.hash_type() =>
+        {
+            Ok(())
+        }
         (CodeHashCategory::Sighash, _, _) => Err(format!(
             "Invalid sighash lock script, hash_type: {}, args.length: {}",
             hash_type_str,
             lock_args.len()
         )),
-        (CodeHashCategory::Multisig, _, _) => Err(format!(
-            "Invalid multisig lock script, hash_type: {}, args.length: {}",
+        (CodeHashCategory::Multisig(variant), _, _) => Err(format!(
+            "Invalid multisig({}) lock script, hash_type: {}, args.length: {}",
+            variant,
+            hash_type_str,
+            lock_args.len()
+        )),
+        (CodeHashCategory::Omnilock, ScriptHashType::Type, _) => {
+            OmnilockArgs::from_slice(&lock_args)
+                .map(|_| ())
+                .map_err(|err| format!("Invalid omnilock lock script: {}", err))
+        }
+        (CodeHashCategory::Omnilock, _, _) => Err(format!(
+            "Invalid omnilock lock script, hash_type: {}, args.length: {}",
             hash_type_str,
             lock_args.len()
         )),
@@ -439,7 +1123,7 @@ pub fn build_signature<
     input_size: usize,
     input_group_idxs: &[usize],
     witnesses: &[packed::Bytes],
-    multisig_config_opt: Option<&MultisigConfig>,
+    lock_placeholder: Bytes,
     mut signer: S,
 ) -> Result<Bytes, String> {
     let init_witness_idx = input_group_idxs[0];
@@ -449,24 +1133,10 @@ pub fn build_signature<
         WitnessArgs::from_slice(witnesses[init_witness_idx].raw_data().as_ref())
             .map_err(|err| err.to_string())?
     };
-
-    let init_witness = if let Some(multisig_config) = multisig_config_opt {
-        let lock_without_sig = {
-            let sig_len = (multisig_config.threshold() as usize) * SECP_SIGNATURE_SIZE;
-            let mut data = BytesMut::from(&multisig_config.to_witness_data()[..]);
-            data.extend_from_slice(vec![0u8; sig_len].as_slice());
-            data.freeze()
-        };
-        init_witness
-            .as_builder()
-            .lock(Some(lock_without_sig).pack())
-            .build()
-    } else {
-        init_witness
-            .as_builder()
-            .lock(Some(Bytes::from(vec![0u8; SECP_SIGNATURE_SIZE])).pack())
-            .build()
-    };
+    let init_witness = init_witness
+        .as_builder()
+        .lock(Some(lock_placeholder).pack())
+        .build();
 
     let mut blake2b = new_blake2b();
     blake2b.update(tx.hash().as_slice());
@@ -496,6 +1166,9 @@ pub fn build_signature<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_utils::{genesis_info, sighash_lock};
+    use ckb_sdk::constants::ONE_CKB;
+    use ckb_sdk::traits::{CellCollectorError, LiveCell};
     use ckb_types::{h160, h256};
 
     #[test]
@@ -546,6 +1219,17 @@ mod tests {
             .as_builder()
             .args(Bytes::from(h256!("0x33").as_bytes()[0..12].to_vec()).pack())
             .build();
+        let lock_multisig_v2_ok = lock_multisig_ok_args_28
+            .clone()
+            .as_builder()
+            .code_hash(MULTISIG_V2_CODE_HASH.pack())
+            .hash_type(ScriptHashType::Data1.into())
+            .build();
+        let lock_multisig_v2_bad_hash_type = lock_multisig_v2_ok
+            .clone()
+            .as_builder()
+            .hash_type(ScriptHashType::Type.into())
+            .build();
 
         let lock_other_type = packed::Script::new_builder()
             .args(Bytes::from(h160!("0x33").as_bytes().to_vec()).pack())
@@ -558,6 +1242,21 @@ mod tests {
             .hash_type(ScriptHashType::Data.into())
             .build();
 
+        let lock_omnilock_ok = omnilock::omnilock_script(
+            &omnilock::OMNILOCK_TYPE_HASH_TESTNET,
+            &OmnilockArgs::new_pubkey_hash(h160!("0x33")),
+        );
+        let lock_omnilock_bad_hash_type = lock_omnilock_ok
+            .clone()
+            .as_builder()
+            .hash_type(ScriptHashType::Data1.into())
+            .build();
+        let lock_omnilock_bad_args = lock_omnilock_ok
+            .clone()
+            .as_builder()
+            .args(Bytes::from(h160!("0x33").as_bytes().to_vec()).pack())
+            .build();
+
         for (script, is_ok, skip_check) in &[
             (&lock_sighash_ok, true, false),
             (&lock_sighash_ok, true, true),
@@ -577,6 +1276,14 @@ mod tests {
             (&lock_multisig_bad_args_1, false, true),
             (&lock_multisig_bad_args_2, false, false),
             (&lock_multisig_bad_args_2, false, true),
+            (&lock_multisig_v2_ok, true, false),
+            (&lock_multisig_v2_bad_hash_type, false, true),
+            (&lock_omnilock_ok, true, false),
+            (&lock_omnilock_ok, true, true),
+            (&lock_omnilock_bad_hash_type, false, false),
+            (&lock_omnilock_bad_hash_type, false, true),
+            (&lock_omnilock_bad_args, false, false),
+            (&lock_omnilock_bad_args, false, true),
             (&lock_other_type, true, true),
             (&lock_other_type, false, false),
             (&lock_other_data, true, true),
@@ -585,4 +1292,481 @@ mod tests {
             assert_eq!(check_lock_script(script, *skip_check).is_ok(), *is_ok);
         }
     }
+
+    #[test]
+    fn test_signing_groups() {
+        let sighash_lock = packed::Script::new_builder()
+            .args(Bytes::from(h160!("0x33").as_bytes().to_vec()).pack())
+            .code_hash(SIGHASH_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let multisig_config =
+            MultisigConfig::new_with(vec![h160!("0x33"), h160!("0x44"), h160!("0x55")], 0, 2)
+                .unwrap();
+        let multisig_lock = packed::Script::new_builder()
+            .args(Bytes::from(multisig_config.hash160().as_bytes().to_vec()).pack())
+            .code_hash(MULTISIG_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let out_points = (0..3u32)
+            .map(|index| {
+                OutPoint::new_builder()
+                    .tx_hash(h256!("0x1").pack())
+                    .index(index.pack())
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let locks = [&sighash_lock, &multisig_lock, &sighash_lock];
+        let tx = TransactionBuilder::default()
+            .inputs(out_points.iter().map(|out_point| {
+                CellInput::new_builder()
+                    .previous_output(out_point.clone())
+                    .build()
+            }))
+            .build();
+        let mut helper = TxHelper::new(tx);
+        assert!(helper.signing_groups().is_err());
+        assert!(helper.check_input_cells().is_err());
+        helper
+            .snapshot_inputs(|out_point| {
+                let index: u32 = out_point.index().unpack();
+                let output = CellOutput::new_builder()
+                    .lock(locks[index as usize].clone())
+                    .build();
+                Ok((output, Bytes::from(vec![index as u8])))
+            })
+            .unwrap();
+        assert_eq!(helper.input_cells().len(), 3);
+        helper.check_input_cells().unwrap();
+        let (output, data) = helper.get_input_cell(&out_points[1]).unwrap();
+        assert_eq!(output.lock(), multisig_lock);
+        assert_eq!(data, Bytes::from(vec![1u8]));
+
+        let groups = helper.signing_groups().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].input_indexes, vec![0, 2]);
+        assert_eq!(groups[0].signers, vec![h160!("0x33")]);
+        assert_eq!((groups[0].signatures, groups[0].threshold), (0, 1));
+        // missing multisig config
+        assert_eq!(groups[1].input_indexes, vec![1]);
+        assert_eq!(groups[1].threshold, 0);
+        assert!(!groups[1].is_complete());
+
+        helper.add_multisig_config(multisig_config);
+        helper
+            .add_signature(
+                multisig_lock.args().raw_data(),
+                Bytes::from(vec![1u8; SECP_SIGNATURE_SIZE]),
+            )
+            .unwrap();
+        helper
+            .add_signature(
+                sighash_lock.args().raw_data(),
+                Bytes::from(vec![2u8; SECP_SIGNATURE_SIZE]),
+            )
+            .unwrap();
+        let groups = helper.signing_groups().unwrap();
+        assert!(groups[0].is_complete());
+        assert_eq!(groups[1].signers.len(), 3);
+        assert_eq!((groups[1].signatures, groups[1].threshold), (1, 2));
+        assert!(!groups[1].is_complete());
+
+        helper.clear_inputs();
+        assert!(helper.input_cells().is_empty());
+    }
+
+    #[test]
+    fn test_dev_chain_omnilock_input() {
+        let genesis_info = genesis_info();
+
+        let code_hash = h256!("0xaa");
+        let omnilock_item: CellDepItem = serde_json::from_value(serde_json::json!({
+            "script_id": {"code_hash": code_hash, "hash_type": "type"},
+            "cell_dep": {
+                "out_point": {"tx_hash": h256!("0xbb"), "index": "0x0"},
+                "dep_type": "code",
+            },
+        }))
+        .unwrap();
+        let lock =
+            omnilock::omnilock_script(&code_hash, &OmnilockArgs::new_pubkey_hash(h160!("0x33")));
+        let get_live_cell = |_out_point, _with_data| -> Result<CellOutput, String> {
+            Ok(CellOutput::new_builder().lock(lock.clone()).build())
+        };
+        let out_point = OutPoint::new(h256!("0x1").pack(), 0);
+
+        // Unknown code hash without the cell deps item
+        let mut helper = TxHelper::default();
+        assert!(helper
+            .add_input(
+                out_point.clone(),
+                None,
+                get_live_cell,
+                &genesis_info,
+                None,
+                false
+            )
+            .is_err());
+
+        helper
+            .add_input(
+                out_point,
+                None,
+                get_live_cell,
+                &genesis_info,
+                Some(&omnilock_item),
+                false,
+            )
+            .unwrap();
+        assert_eq!(helper.omnilock_code_hash(), Some(&code_hash));
+        let cell_deps = helper.transaction().cell_deps();
+        assert_eq!(cell_deps.len(), 1);
+        assert_eq!(cell_deps.get(0).unwrap(), omnilock_item.cell_dep.into());
+        helper
+            .snapshot_inputs(|out_point| Ok((get_live_cell(out_point, true)?, Bytes::new())))
+            .unwrap();
+        let groups = helper.signing_groups().unwrap();
+        assert_eq!(groups[0].signers, vec![h160!("0x33")]);
+    }
+
+    #[test]
+    fn test_balance() {
+        #[derive(Clone)]
+        struct MockCellCollector {
+            cells: Vec<LiveCell>,
+            used: HashSet<OutPoint>,
+        }
+        impl CellCollector for MockCellCollector {
+            fn collect_live_cells(
+                &mut self,
+                query: &CellQueryOptions,
+                apply_changes: bool,
+            ) -> Result<(Vec<LiveCell>, u64), CellCollectorError> {
+                let mut cells = Vec::new();
+                let mut total: u64 = 0;
+                for cell in &self.cells {
+                    if total >= query.min_total_capacity {
+                        break;
+                    }
+                    if !self.used.contains(&cell.out_point) {
+                        total += Unpack::<u64>::unpack(&cell.output.capacity());
+                        cells.push(cell.clone());
+                    }
+                }
+                if apply_changes {
+                    self.used
+                        .extend(cells.iter().map(|cell| cell.out_point.clone()));
+                }
+                Ok((cells, total))
+            }
+            fn lock_cell(&mut self, _: OutPoint, _: u64) -> Result<(), CellCollectorError> {
+                Ok(())
+            }
+            fn apply_tx(&mut self, _: Transaction, _: u64) -> Result<(), CellCollectorError> {
+                Ok(())
+            }
+            fn reset(&mut self) {}
+        }
+
+        let genesis_info = genesis_info();
+        let (from_lock, to_lock) = (sighash_lock(1), sighash_lock(2));
+        let live_cell = |capacity: u64, index: u32| LiveCell {
+            output: CellOutput::new_builder()
+                .lock(from_lock.clone())
+                .capacity((capacity * ONE_CKB).pack())
+                .build(),
+            output_data: Bytes::new(),
+            out_point: OutPoint::new(h256!("0xff").pack(), index),
+            block_number: 1,
+            tx_index: 1,
+        };
+        // Balance the transaction of one output and the existing inputs
+        let balance = |inputs: Vec<LiveCell>, cells: Vec<LiveCell>, output_capacity: u64| {
+            let mut collector = MockCellCollector {
+                cells: cells.clone(),
+                used: HashSet::default(),
+            };
+            let all_cells = inputs
+                .iter()
+                .chain(cells.iter())
+                .cloned()
+                .collect::<Vec<_>>();
+            let get_live_cell = |out_point: OutPoint, _with_data: bool| {
+                all_cells
+                    .iter()
+                    .find(|cell| cell.out_point == out_point)
+                    .map(|cell| cell.output.clone())
+                    .ok_or_else(|| String::from("cell not found"))
+            };
+            let mut helper = TxHelper::default();
+            for cell in inputs {
+                helper
+                    .add_input(
+                        cell.out_point.clone(),
+                        None,
+                        get_live_cell,
+                        &genesis_info,
+                        None,
+                        false,
+                    )
+                    .unwrap();
+                helper.add_input_cell(cell.out_point, cell.output, cell.output_data);
+            }
+            helper.add_output(
+                CellOutput::new_builder()
+                    .lock(to_lock.clone())
+                    .capacity((output_capacity * ONE_CKB).pack())
+                    .build(),
+                Bytes::new(),
+            );
+            helper
+                .balance(
+                    &from_lock,
+                    &from_lock,
+                    1000,
+                    &mut collector,
+                    get_live_cell,
+                    &genesis_info,
+                )
+                .map(|result| (result, helper))
+        };
+
+        // Enough inputs
+        let ((added_inputs, change_capacity, fee), helper) =
+            balance(vec![], vec![live_cell(200, 0), live_cell(200, 1)], 100).unwrap();
+        assert_eq!(added_inputs, 1);
+        assert_eq!(change_capacity, 100 * ONE_CKB - fee);
+        let tx = helper.placeholder_tx().unwrap();
+        assert_eq!(
+            fee,
+            FeeRate::from_u64(1000)
+                .fee(tx.data().as_reader().serialized_size_in_block() as u64)
+                .as_u64()
+        );
+        assert_eq!(tx.outputs().len(), 2);
+        assert_eq!(tx.output(1).unwrap().lock(), from_lock);
+        assert_eq!(tx.cell_deps().len(), 1);
+
+        // The dust leftover (less than the occupied capacity of change) forces extra inputs
+        let ((added_inputs, change_capacity, fee), helper) =
+            balance(vec![live_cell(130, 0)], vec![live_cell(100, 1)], 100).unwrap();
+        assert_eq!(added_inputs, 1);
+        assert_eq!(change_capacity, 130 * ONE_CKB - fee);
+        assert_eq!(helper.transaction().inputs().len(), 2);
+
+        // Insufficient capacity
+        let err = balance(vec![], vec![live_cell(130, 0)], 100)
+            .err()
+            .expect("capacity not enough");
+        assert!(err.starts_with("Capacity not enough"), "{}", err);
+    }
+
+    #[test]
+    fn test_type_id() {
+        // The genesis type id scripts are created from the cellbase input
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new_cellbase_input(0))
+            .build();
+        let helper = TxHelper::new(tx);
+        for output_index in 0..3 {
+            assert_eq!(
+                helper.type_id_args(output_index).unwrap(),
+                ckb_chain_spec::build_genesis_type_id_script(output_index)
+                    .args()
+                    .raw_data()
+            );
+        }
+        assert!(TxHelper::default().type_id_args(0).is_err());
+
+        // The type id output is invalid after the first input changed
+        let type_id_output = |helper: &TxHelper| {
+            let type_script = Script::new_builder()
+                .code_hash(TYPE_ID_CODE_HASH.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .args(helper.type_id_args(0).unwrap().pack())
+                .build();
+            CellOutput::new_builder()
+                .type_(Some(type_script).pack())
+                .build()
+        };
+        let input = |byte: u8| CellInput::new(OutPoint::new(Byte32::new([byte; 32]), 0), 0);
+        let get_live_cell = |_, _| Ok(CellOutput::default());
+        let mut helper = TxHelper::new(TransactionBuilder::default().input(input(1)).build());
+        helper.add_output(type_id_output(&helper), Bytes::new());
+        assert!(helper.check_type_id_outputs(get_live_cell).is_ok());
+        let changed = TxHelper::new(
+            helper
+                .transaction()
+                .as_advanced_builder()
+                .set_inputs(vec![input(2)])
+                .build(),
+        );
+        assert!(changed.check_type_id_outputs(get_live_cell).is_err());
+        // Not a new type id cell if an input has the same type script
+        let type_id_input = type_id_output(&helper);
+        assert!(changed
+            .check_type_id_outputs(|_, _| Ok(type_id_input.clone()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_add_deps() {
+        let mut helper = TxHelper::default();
+        let cell_dep = CellDep::new_builder()
+            .out_point(OutPoint::new(Byte32::new([1u8; 32]), 0))
+            .build();
+        assert!(helper.add_cell_dep(cell_dep.clone()));
+        assert!(!helper.add_cell_dep(cell_dep.clone()));
+        let other_dep = cell_dep
+            .clone()
+            .as_builder()
+            .dep_type(ckb_types::core::DepType::DepGroup.into())
+            .build();
+        assert!(helper.add_cell_dep(other_dep));
+        assert_eq!(helper.transaction().cell_deps().len(), 2);
+
+        let block_hash = Byte32::new([2u8; 32]);
+        assert!(helper.add_header_dep(block_hash.clone()));
+        assert!(!helper.add_header_dep(block_hash));
+        assert!(helper.add_header_dep(Byte32::new([3u8; 32])));
+        assert_eq!(helper.transaction().header_deps().len(), 2);
+    }
+
+    #[test]
+    fn test_multisig_script() {
+        let config = MultisigConfig::new_with(vec![h160!("0x1"), h160!("0x2")], 0, 1).unwrap();
+        let hash160 = config.hash160();
+        let tx = TransactionBuilder::default()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(OutPoint::new_builder().tx_hash(h256!("0x1").pack()).build())
+                    .build(),
+            )
+            .build();
+        let mut helper = TxHelper::new(tx);
+        helper.add_multisig_config(config);
+        assert_eq!(helper.multisig_script(&hash160), MultisigScript::Legacy);
+
+        // The chosen variant is used when no input is locked by the config
+        helper.set_multisig_script(hash160.clone(), MultisigScript::V2);
+        assert_eq!(helper.multisig_script(&hash160), MultisigScript::V2);
+
+        // The input lock takes precedence
+        let lock = packed::Script::new_builder()
+            .args(Bytes::from(hash160.as_bytes().to_vec()).pack())
+            .code_hash(MULTISIG_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        helper
+            .snapshot_inputs(|_| {
+                Ok((
+                    CellOutput::new_builder().lock(lock.clone()).build(),
+                    Bytes::new(),
+                ))
+            })
+            .unwrap();
+        assert_eq!(helper.multisig_script(&hash160), MultisigScript::Legacy);
+    }
+
+    #[test]
+    fn test_merge_signatures() {
+        let privkeys = [[1u8; 32], [2u8; 32], [3u8; 32]]
+            .iter()
+            .map(|bytes| secp256k1::SecretKey::from_slice(bytes).unwrap())
+            .collect::<Vec<_>>();
+        let lock_args = privkeys
+            .iter()
+            .map(|privkey| {
+                let pubkey = secp256k1::PublicKey::from_secret_key(&SECP256K1, privkey);
+                H160::from_slice(&blake2b_256(&pubkey.serialize()[..])[0..20]).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let multisig_config = MultisigConfig::new_with(lock_args.clone(), 0, 2).unwrap();
+        let lock = packed::Script::new_builder()
+            .args(Bytes::from(multisig_config.hash160().as_bytes().to_vec()).pack())
+            .code_hash(MULTISIG_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(OutPoint::new_builder().tx_hash(h256!("0x1").pack()).build())
+                    .build(),
+            )
+            .build();
+        let mut base = TxHelper::new(tx);
+        base.add_multisig_config(multisig_config);
+        base.snapshot_inputs(|_| {
+            Ok((
+                CellOutput::new_builder().lock(lock.clone()).build(),
+                Bytes::new(),
+            ))
+        })
+        .unwrap();
+
+        let sign = |helper: &TxHelper, idx: usize| -> TxHelper {
+            let privkey = privkeys[idx];
+            let lock_arg = lock_args[idx].clone();
+            let mut signer = move |signers: &HashSet<H160>,
+                                   message: &H256,
+                                   _tx: &rpc_types::Transaction|
+                  -> Result<Option<[u8; 65]>, String> {
+                if !signers.contains(&lock_arg) {
+                    return Ok(None);
+                }
+                let message = secp256k1::Message::from_slice(message.as_bytes()).unwrap();
+                let signature = SECP256K1.sign_ecdsa_recoverable(&message, &privkey);
+                Ok(Some(ckb_sdk::util::serialize_signature(&signature)))
+            };
+            let mut signed = helper.clone();
+            // sign with the snapshots only (offline mode)
+            let get_live_cell = |out_point: OutPoint, _| {
+                helper.get_input_cell(&out_point).map(|(output, _)| output)
+            };
+            for (lock_arg, signature) in helper
+                .sign_inputs(&mut signer, get_live_cell, false)
+                .unwrap()
+            {
+                signed.add_signature(lock_arg, signature).unwrap();
+            }
+            signed
+        };
+        let signed_a = sign(&base, 0);
+        let signed_b = sign(&base, 1);
+        let signed_c = sign(&base, 2);
+
+        let placeholder_witness = base.placeholder_tx().unwrap().witnesses().get(0).unwrap();
+        let placeholder_witness = WitnessArgs::from_slice(&placeholder_witness.raw_data()).unwrap();
+        assert_eq!(
+            placeholder_witness.lock().to_opt().unwrap().len(),
+            4 + 20 * 3 + SECP_SIGNATURE_SIZE * 2
+        );
+
+        let mut merged = base.clone();
+        merged.merge(signed_a.clone()).unwrap();
+        merged.merge(signed_b).unwrap();
+        merged.check_signatures().unwrap();
+        assert!(merged.signing_groups().unwrap()[0].is_complete());
+        // merge the same signature again is fine
+        merged.merge(signed_a).unwrap();
+        merged.check_signatures().unwrap();
+        // exceed threshold
+        let mut exceeded = merged.clone();
+        exceeded.merge(signed_c).unwrap();
+        assert!(exceeded.check_signatures().is_err());
+
+        // invalid signature
+        let mut invalid = base.clone();
+        invalid
+            .add_signature(
+                lock.args().raw_data(),
+                Bytes::from(vec![1u8; SECP_SIGNATURE_SIZE]),
+            )
+            .unwrap();
+        assert!(invalid.check_signatures().is_err());
+
+        // other transaction
+        let other = TxHelper::new(TransactionBuilder::default().build());
+        assert!(merged.merge(other).is_err());
+    }
 }
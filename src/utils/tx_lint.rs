@@ -0,0 +1,827 @@
+use std::collections::{HashMap, HashSet};
+
+use ckb_hash::blake2b_256;
+use ckb_sdk::{
+    constants::{
+        ACP_TYPE_HASH_AGGRON, ACP_TYPE_HASH_LINA, DAO_TYPE_HASH, MULTISIG_TYPE_HASH, ONE_CKB,
+        SECP_SIGNATURE_SIZE, SIGHASH_TYPE_HASH, TYPE_ID_CODE_HASH,
+    },
+    HumanCapacity, NetworkType, ScriptId, Since, SinceType,
+};
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, EpochNumberWithFraction, HeaderView, ScriptHashType, TransactionView},
+    packed::{CellDep, CellOutput, Script, WitnessArgs},
+    prelude::*,
+    H256,
+};
+use serde::Serialize;
+
+use super::cell_dep::CellDeps;
+use super::genesis_info::GenesisInfo;
+use super::multisig::MultisigScript;
+use super::omnilock::{OMNILOCK_TYPE_HASH_MAINNET, OMNILOCK_TYPE_HASH_TESTNET};
+use super::other::get_live_cell;
+use super::rpc::HttpRpcClient;
+
+/// The default maximum transaction fee when it is not given
+pub const DEFAULT_MAX_TX_FEE: u64 = ONE_CKB;
+/// A change output with less free capacity (capacity - occupied capacity) than this is dust
+pub const DUST_FREE_CAPACITY: u64 = ONE_CKB;
+/// Absolute since timestamps are in seconds, larger values are most likely in milliseconds
+const MAX_SINCE_TIMESTAMP: u64 = 253_402_300_799;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    /// The transaction will be rejected (or lose funds)
+    Error,
+    /// The transaction is valid but most likely not intended
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintItem {
+    pub level: LintLevel,
+    /// The name of the check, like: `occupied-capacity`
+    pub check: &'static str,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LintReport {
+    pub items: Vec<LintItem>,
+}
+
+impl LintReport {
+    pub fn error(&mut self, check: &'static str, message: String) {
+        self.items.push(LintItem {
+            level: LintLevel::Error,
+            check,
+            message,
+        });
+    }
+
+    pub fn warning(&mut self, check: &'static str, message: String) {
+        self.items.push(LintItem {
+            level: LintLevel::Warning,
+            check,
+            message,
+        });
+    }
+
+    pub fn count(&self, level: LintLevel) -> usize {
+        self.items.iter().filter(|item| item.level == level).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(LintLevel::Error) > 0
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "errors": self.count(LintLevel::Error),
+            "warnings": self.count(LintLevel::Warning),
+            "items": self.items,
+        })
+    }
+
+    /// Print the report to stderr, fail when there are errors (used before sending)
+    pub fn check_before_send(&self) -> Result<(), String> {
+        for item in &self.items {
+            let level = match item.level {
+                LintLevel::Error => "error",
+                LintLevel::Warning => "warning",
+            };
+            eprintln!("[lint] {}({}): {}", level, item.check, item.message);
+        }
+        if self.has_errors() {
+            return Err(format!(
+                "Transaction lint failed with {} error(s), fix them or add `--skip-lint` to send it anyway",
+                self.count(LintLevel::Error)
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub struct LintContext {
+    /// The network of the node the transaction is sent to
+    pub network: NetworkType,
+    /// Known scripts and their cell deps, see `known_cell_deps`
+    pub known_cell_deps: HashMap<ScriptId, (CellDep, String)>,
+    /// Used to check if the absolute since values are already satisfied
+    pub tip_header: Option<HeaderView>,
+    /// The maximum transaction fee (unit: shannons)
+    pub max_tx_fee: Option<u64>,
+}
+
+/// The cell deps of genesis scripts and the items of cell deps file
+pub fn known_cell_deps(
+    genesis_info: &GenesisInfo,
+    cell_deps: Option<&CellDeps>,
+) -> HashMap<ScriptId, (CellDep, String)> {
+    let mut known = HashMap::new();
+    known.insert(
+        ScriptId::new_type(SIGHASH_TYPE_HASH),
+        (genesis_info.sighash_dep(), String::from("sighash")),
+    );
+    known.insert(
+        ScriptId::new_type(MULTISIG_TYPE_HASH),
+        (genesis_info.multisig_dep(), String::from("multisig")),
+    );
+    known.insert(
+        ScriptId::new_type(DAO_TYPE_HASH),
+        (genesis_info.dao_dep(), String::from("Nervos DAO")),
+    );
+    if let Some(cell_deps) = cell_deps {
+        for (name, item) in &cell_deps.items {
+            known.insert(
+                item.script_id.clone().into(),
+                (item.cell_dep.clone().into(), name.to_string()),
+            );
+        }
+    }
+    known
+}
+
+/// The names of known scripts: the genesis scripts, the items of cell deps file and the
+/// well-known scripts which are not in genesis block
+pub fn known_script_names(
+    genesis_info: Option<&GenesisInfo>,
+    cell_deps: Option<&CellDeps>,
+) -> HashMap<ScriptId, String> {
+    let mut names = HashMap::new();
+    if let Some(genesis_info) = genesis_info {
+        for (script_id, (_, name)) in known_cell_deps(genesis_info, None) {
+            names.insert(script_id, name);
+        }
+    }
+    if let Some(cell_deps) = cell_deps {
+        for (name, item) in &cell_deps.items {
+            names.insert(item.script_id.clone().into(), name.to_string());
+        }
+    }
+    let well_known = [
+        (MultisigScript::V2.script_id(), "multisig_v2"),
+        (ScriptId::new_type(OMNILOCK_TYPE_HASH_MAINNET), "omnilock"),
+        (ScriptId::new_type(OMNILOCK_TYPE_HASH_TESTNET), "omnilock"),
+        (ScriptId::new_type(ACP_TYPE_HASH_LINA), "acp"),
+        (ScriptId::new_type(ACP_TYPE_HASH_AGGRON), "acp"),
+        (ScriptId::new_type(TYPE_ID_CODE_HASH), "type_id"),
+    ];
+    for (script_id, name) in well_known {
+        names.entry(script_id).or_insert_with(|| name.to_string());
+    }
+    names
+}
+
+/// Lint the transaction with the live input cells and the tip header from the node
+pub fn lint_tx_online(
+    rpc_client: &mut HttpRpcClient,
+    tx: &TransactionView,
+    network: NetworkType,
+    known_cell_deps: HashMap<ScriptId, (CellDep, String)>,
+    max_tx_fee: Option<u64>,
+) -> Result<LintReport, String> {
+    let ctx = LintContext {
+        network,
+        known_cell_deps,
+        tip_header: Some(rpc_client.get_tip_header()?.into()),
+        max_tx_fee,
+    };
+    // `None` means the cell is dead or unknown
+    let inputs = tx
+        .input_pts_iter()
+        .map(|out_point| get_live_cell(rpc_client, out_point, true).ok())
+        .collect::<Vec<_>>();
+    Ok(lint_tx(tx, &inputs, &ctx))
+}
+
+/// Check the transaction before sending, the `inputs` must be in the same order of the
+/// transaction inputs.
+pub fn lint_tx(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    ctx: &LintContext,
+) -> LintReport {
+    let mut report = LintReport::default();
+    check_inputs(tx, inputs, &mut report);
+    check_outputs(tx, inputs, &mut report);
+    check_fee(tx, inputs, ctx, &mut report);
+    check_deps(tx, inputs, ctx, &mut report);
+    check_since(tx, ctx, &mut report);
+    check_witnesses(tx, inputs, &mut report);
+    check_network(tx, ctx, &mut report);
+    report
+}
+
+fn check_inputs(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    report: &mut LintReport,
+) {
+    if tx.inputs().is_empty() {
+        report.error("inputs", String::from("The transaction has no inputs"));
+    }
+    let mut seen = HashMap::new();
+    for (idx, out_point) in tx.input_pts_iter().enumerate() {
+        if let Some(first) = seen.insert(out_point.clone(), idx) {
+            report.error(
+                "duplicate-inputs",
+                format!(
+                    "Input(no.{}) spends the same cell as input(no.{}): {}",
+                    idx + 1,
+                    first + 1,
+                    out_point
+                ),
+            );
+        }
+        if inputs.get(idx).map(Option::is_none).unwrap_or(true) {
+            report.error(
+                "inputs",
+                format!("Input(no.{}) is dead or unknown: {}", idx + 1, out_point),
+            );
+        }
+    }
+}
+
+fn check_outputs(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    report: &mut LintReport,
+) {
+    if tx.outputs().len() != tx.outputs_data().len() {
+        report.error(
+            "outputs",
+            format!(
+                "The number of outputs ({}) and outputs data ({}) not match",
+                tx.outputs().len(),
+                tx.outputs_data().len()
+            ),
+        );
+    }
+    let input_locks = inputs
+        .iter()
+        .flatten()
+        .map(|(output, _)| output.lock())
+        .collect::<HashSet<_>>();
+    for (idx, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+        let capacity: u64 = output.capacity().unpack();
+        let occupied =
+            match output.occupied_capacity(Capacity::bytes(data.len()).expect("data capacity")) {
+                Ok(occupied) => occupied.as_u64(),
+                Err(err) => {
+                    report.error(
+                        "occupied-capacity",
+                        format!("Output(no.{}) capacity overflow: {}", idx + 1, err),
+                    );
+                    continue;
+                }
+            };
+        if capacity < occupied {
+            report.error(
+                "occupied-capacity",
+                format!(
+                    "Output(no.{}) capacity {:#} is less than its occupied capacity {:#}",
+                    idx + 1,
+                    HumanCapacity(capacity),
+                    HumanCapacity(occupied)
+                ),
+            );
+            continue;
+        }
+        let is_change =
+            output.type_().is_none() && data.is_empty() && input_locks.contains(&output.lock());
+        if is_change && capacity - occupied < DUST_FREE_CAPACITY {
+            report.warning(
+                "dust-change",
+                format!(
+                    "Change output(no.{}) capacity {:#} is only {:#} above its occupied capacity, consider adding it to the fee",
+                    idx + 1,
+                    HumanCapacity(capacity),
+                    HumanCapacity(capacity - occupied)
+                ),
+            );
+        }
+    }
+}
+
+fn check_fee(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    ctx: &LintContext,
+    report: &mut LintReport,
+) {
+    if inputs.iter().any(Option::is_none) {
+        return;
+    }
+    // The outputs of NervosDAO withdraw include the interest, it can not be calculated
+    // without the headers
+    if inputs
+        .iter()
+        .flatten()
+        .any(|(output, _)| is_dao_cell(output))
+    {
+        return;
+    }
+    let input_total: u64 = inputs
+        .iter()
+        .flatten()
+        .map(|(output, _)| -> u64 { output.capacity().unpack() })
+        .sum();
+    let output_total: u64 = tx
+        .outputs()
+        .into_iter()
+        .map(|output| -> u64 { output.capacity().unpack() })
+        .sum();
+    if input_total < output_total {
+        report.error(
+            "fee",
+            format!(
+                "Outputs capacity {:#} is more than inputs capacity {:#}",
+                HumanCapacity(output_total),
+                HumanCapacity(input_total)
+            ),
+        );
+        return;
+    }
+    let tx_fee = input_total - output_total;
+    if let Some(max_tx_fee) = ctx.max_tx_fee {
+        if tx_fee > max_tx_fee {
+            report.error(
+                "fee",
+                format!(
+                    "Too much transaction fee: {:#}, max: {:#}",
+                    HumanCapacity(tx_fee),
+                    HumanCapacity(max_tx_fee)
+                ),
+            );
+        }
+    }
+}
+
+fn check_deps(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    ctx: &LintContext,
+    report: &mut LintReport,
+) {
+    let mut scripts = Vec::new();
+    for (idx, (output, _)) in inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, input)| input.as_ref().map(|input| (idx, input)))
+    {
+        scripts.push((format!("input(no.{}) lock", idx + 1), output.lock()));
+        if let Some(type_script) = output.type_().to_opt() {
+            scripts.push((format!("input(no.{}) type", idx + 1), type_script));
+        }
+    }
+    for (idx, output) in tx.outputs().into_iter().enumerate() {
+        if let Some(type_script) = output.type_().to_opt() {
+            scripts.push((format!("output(no.{}) type", idx + 1), type_script));
+        }
+    }
+
+    let cell_deps = tx.cell_deps().into_iter().collect::<HashSet<_>>();
+    let mut reported = HashSet::new();
+    for (name, script) in &scripts {
+        let script_id = ScriptId::from(script);
+        if let Some((cell_dep, dep_name)) = ctx.known_cell_deps.get(&script_id) {
+            if !cell_deps.contains(cell_dep) && reported.insert(script_id) {
+                report.error(
+                    "cell-deps",
+                    format!(
+                        "Missing cell dep of {} script (used by {}): {}",
+                        dep_name, name, cell_dep
+                    ),
+                );
+            }
+        }
+    }
+
+    // NervosDAO prepare requires the header of deposit block, withdraw requires the headers
+    // of deposit block and prepare block.
+    let header_deps = tx.header_deps().len();
+    for (idx, (output, data)) in inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, input)| input.as_ref().map(|input| (idx, input)))
+    {
+        if !is_dao_cell(output) {
+            continue;
+        }
+        let is_deposit = data.len() == 8 && data.iter().all(|byte| *byte == 0);
+        let required = if is_deposit { 1 } else { 2 };
+        if header_deps < required {
+            report.error(
+                "header-deps",
+                format!(
+                    "Input(no.{}) is a NervosDAO {} cell, at least {} header deps are required, found {}",
+                    idx + 1,
+                    if is_deposit { "deposit" } else { "prepared" },
+                    required,
+                    header_deps
+                ),
+            );
+        }
+    }
+}
+
+fn is_dao_cell(output: &CellOutput) -> bool {
+    output
+        .type_()
+        .to_opt()
+        .map(|script| ScriptId::from(&script) == ScriptId::new_type(DAO_TYPE_HASH))
+        .unwrap_or(false)
+}
+
+fn check_since(tx: &TransactionView, ctx: &LintContext, report: &mut LintReport) {
+    for (idx, input) in tx.inputs().into_iter().enumerate() {
+        let since = Since::from_raw_value(input.since().unpack());
+        if since.value() == 0 {
+            continue;
+        }
+        if !since.flags_is_valid() {
+            report.error(
+                "since",
+                format!(
+                    "Input(no.{}) has invalid since flags: {:#x}",
+                    idx + 1,
+                    since.value()
+                ),
+            );
+            continue;
+        }
+        let (since_type, value) = since.extract_metric().expect("valid since flags");
+        match since_type {
+            SinceType::EpochNumberWithFraction => {
+                let epoch = EpochNumberWithFraction::from_full_value(value);
+                if epoch.length() == 0 || epoch.index() >= epoch.length() {
+                    report.warning(
+                        "since",
+                        format!(
+                            "Input(no.{}) since epoch has invalid fraction: {}/{}",
+                            idx + 1,
+                            epoch.index(),
+                            epoch.length()
+                        ),
+                    );
+                    continue;
+                }
+            }
+            SinceType::Timestamp if since.is_absolute() && value > MAX_SINCE_TIMESTAMP => {
+                report.warning(
+                    "since",
+                    format!(
+                        "Input(no.{}) since timestamp {} is too large, the unit is seconds not milliseconds",
+                        idx + 1,
+                        value
+                    ),
+                );
+                continue;
+            }
+            _ => {}
+        }
+        if since.is_relative() {
+            continue;
+        }
+        if let Some(tip) = ctx.tip_header.as_ref() {
+            let not_ready = match since_type {
+                SinceType::BlockNumber => value > tip.number() + 1,
+                SinceType::EpochNumberWithFraction => {
+                    EpochNumberWithFraction::from_full_value(value).to_rational()
+                        > tip.epoch().to_rational()
+                }
+                SinceType::Timestamp => value > tip.timestamp() / 1000,
+            };
+            if not_ready {
+                report.warning(
+                    "since",
+                    format!(
+                        "Input(no.{}) is still locked by since {:?}: {}, tip block: {}, epoch: {}",
+                        idx + 1,
+                        since_type,
+                        since_display(since_type, value),
+                        tip.number(),
+                        tip.epoch()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn since_display(since_type: SinceType, value: u64) -> String {
+    match since_type {
+        SinceType::EpochNumberWithFraction => {
+            EpochNumberWithFraction::from_full_value(value).to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn check_witnesses(
+    tx: &TransactionView,
+    inputs: &[Option<(CellOutput, Bytes)>],
+    report: &mut LintReport,
+) {
+    // The witness of the first input of every lock script group
+    let mut groups: Vec<(Script, usize)> = Vec::new();
+    let mut seen = HashSet::new();
+    for (idx, input) in inputs.iter().enumerate() {
+        if let Some((output, _)) = input {
+            if seen.insert(output.lock()) {
+                groups.push((output.lock(), idx));
+            }
+        }
+    }
+    for (lock, idx) in groups {
+        let is_sighash = lock.code_hash() == SIGHASH_TYPE_HASH.pack()
+            && lock.hash_type() == ScriptHashType::Type.into();
+        let multisig = MultisigScript::from_lock(&lock);
+        if !is_sighash && multisig.is_none() {
+            continue;
+        }
+        let witness_lock = tx
+            .witnesses()
+            .get(idx)
+            .and_then(|witness| WitnessArgs::from_slice(&witness.raw_data()).ok())
+            .and_then(|witness_args| witness_args.lock().to_opt())
+            .map(|lock| lock.raw_data());
+        let witness_lock = match witness_lock {
+            Some(witness_lock) if !witness_lock.is_empty() => witness_lock,
+            _ => {
+                report.error(
+                    "witnesses",
+                    format!(
+                        "Input(no.{}) has no signature in the lock field of its witness",
+                        idx + 1
+                    ),
+                );
+                continue;
+            }
+        };
+        let result = if is_sighash {
+            check_sighash_witness(&witness_lock)
+        } else {
+            check_multisig_witness(&lock.args().raw_data(), &witness_lock)
+        };
+        if let Err(err) = result {
+            report.error(
+                "witnesses",
+                format!(
+                    "The witness of input(no.{}) does not match its lock script: {}",
+                    idx + 1,
+                    err
+                ),
+            );
+        }
+    }
+}
+
+fn check_sighash_witness(witness_lock: &[u8]) -> Result<(), String> {
+    if witness_lock.len() != SECP_SIGNATURE_SIZE {
+        return Err(format!(
+            "invalid signature length: {}, expected: {}",
+            witness_lock.len(),
+            SECP_SIGNATURE_SIZE
+        ));
+    }
+    check_signatures(witness_lock)
+}
+
+fn check_multisig_witness(lock_args: &[u8], witness_lock: &[u8]) -> Result<(), String> {
+    if witness_lock.len() < 4 || witness_lock[0] != 0 {
+        return Err(String::from("invalid multisig script header"));
+    }
+    let threshold = witness_lock[2] as usize;
+    let pubkeys_cnt = witness_lock[3] as usize;
+    let config_len = 4 + 20 * pubkeys_cnt;
+    let expected_len = config_len + SECP_SIGNATURE_SIZE * threshold;
+    if witness_lock.len() != expected_len {
+        return Err(format!(
+            "invalid multisig witness length: {}, expected: {} (threshold: {}, pubkeys: {})",
+            witness_lock.len(),
+            expected_len,
+            threshold,
+            pubkeys_cnt
+        ));
+    }
+    if lock_args.len() < 20 || blake2b_256(&witness_lock[..config_len])[..20] != lock_args[..20] {
+        return Err(String::from(
+            "the multisig config in witness does not match the lock args",
+        ));
+    }
+    check_signatures(&witness_lock[config_len..])
+}
+
+fn check_signatures(signatures: &[u8]) -> Result<(), String> {
+    if signatures
+        .chunks(SECP_SIGNATURE_SIZE)
+        .any(|signature| signature.iter().all(|byte| *byte == 0))
+    {
+        return Err(String::from("the signature is a placeholder (not signed)"));
+    }
+    Ok(())
+}
+
+fn check_network(tx: &TransactionView, ctx: &LintContext, report: &mut LintReport) {
+    let other_network = match ctx.network {
+        NetworkType::Mainnet => NetworkType::Testnet,
+        NetworkType::Testnet => NetworkType::Mainnet,
+        _ => return,
+    };
+    let other_scripts: Vec<(&str, H256)> = match other_network {
+        NetworkType::Mainnet => vec![
+            ("omnilock", OMNILOCK_TYPE_HASH_MAINNET),
+            ("anyone-can-pay", ACP_TYPE_HASH_LINA),
+        ],
+        _ => vec![
+            ("omnilock", OMNILOCK_TYPE_HASH_TESTNET),
+            ("anyone-can-pay", ACP_TYPE_HASH_AGGRON),
+        ],
+    };
+    for (idx, output) in tx.outputs().into_iter().enumerate() {
+        let code_hash: H256 = output.lock().code_hash().unpack();
+        if let Some((name, _)) = other_scripts.iter().find(|(_, hash)| hash == &code_hash) {
+            report.error(
+                "network",
+                format!(
+                    "Output(no.{}) is locked by the {} script of {}, but the transaction is sent to {}",
+                    idx + 1,
+                    name,
+                    other_network,
+                    ctx.network
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::sighash_lock;
+    use ckb_sdk::unlock::MultisigConfig;
+    use ckb_types::{
+        core::TransactionBuilder,
+        h160, h256,
+        packed::{CellInput, OutPoint},
+    };
+
+    fn cell(lock: Script, capacity: u64) -> CellOutput {
+        CellOutput::new_builder()
+            .lock(lock)
+            .capacity(capacity.pack())
+            .build()
+    }
+
+    fn checks(report: &LintReport, level: LintLevel) -> Vec<&'static str> {
+        report
+            .items
+            .iter()
+            .filter(|item| item.level == level)
+            .map(|item| item.check)
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_tx() {
+        let ctx = LintContext {
+            network: NetworkType::Testnet,
+            known_cell_deps: HashMap::new(),
+            tip_header: None,
+            max_tx_fee: Some(ONE_CKB),
+        };
+        let out_point = OutPoint::new(h256!("0x1").pack(), 0);
+        let input_cell = cell(sighash_lock(1), 1000 * ONE_CKB);
+        let signed_witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![1u8; SECP_SIGNATURE_SIZE])).pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(out_point.clone(), 0))
+            .output(cell(sighash_lock(2), 900 * ONE_CKB))
+            .output_data(Bytes::new().pack())
+            .output(cell(sighash_lock(1), 100 * ONE_CKB - 1000))
+            .output_data(Bytes::new().pack())
+            .witness(signed_witness.as_bytes().pack())
+            .build();
+        let inputs = vec![Some((input_cell.clone(), Bytes::new()))];
+        assert!(lint_tx(&tx, &inputs, &ctx).items.is_empty());
+
+        // dust change, unsigned, duplicate input, invalid since, omnilock of mainnet
+        let omnilock = Script::new_builder()
+            .code_hash(OMNILOCK_TYPE_HASH_MAINNET.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(vec![0u8; 22]).pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(out_point.clone(), 0))
+            .input(CellInput::new(out_point, 0x1f00_0000_0000_0001))
+            .output(cell(omnilock, 1900 * ONE_CKB))
+            .output_data(Bytes::new().pack())
+            .output(cell(sighash_lock(1), 61 * ONE_CKB + 1000))
+            .output_data(Bytes::new().pack())
+            .output(cell(sighash_lock(3), 60 * ONE_CKB))
+            .output_data(Bytes::new().pack())
+            .build();
+        let inputs = vec![
+            Some((input_cell.clone(), Bytes::new())),
+            Some((input_cell, Bytes::new())),
+        ];
+        let report = lint_tx(&tx, &inputs, &ctx);
+        assert_eq!(
+            checks(&report, LintLevel::Error),
+            vec![
+                "duplicate-inputs",
+                "occupied-capacity",
+                "fee",
+                "since",
+                "witnesses",
+                "network"
+            ]
+        );
+        assert_eq!(checks(&report, LintLevel::Warning), vec!["dust-change"]);
+        assert!(report.check_before_send().is_err());
+
+        // missing cell dep and dead input
+        let sighash_dep = CellDep::new_builder()
+            .out_point(OutPoint::new(h256!("0x2").pack(), 0))
+            .build();
+        let mut ctx = ctx;
+        ctx.known_cell_deps.insert(
+            ScriptId::new_type(SIGHASH_TYPE_HASH),
+            (sighash_dep, String::from("sighash")),
+        );
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(h256!("0x3").pack(), 0), 0))
+            .input(CellInput::new(OutPoint::new(h256!("0x3").pack(), 1), 0))
+            .witness(signed_witness.as_bytes().pack())
+            .build();
+        let inputs = vec![
+            Some((cell(sighash_lock(1), 100 * ONE_CKB), Bytes::new())),
+            None,
+        ];
+        let report = lint_tx(&tx, &inputs, &ctx);
+        assert_eq!(
+            checks(&report, LintLevel::Error),
+            vec!["inputs", "cell-deps"]
+        );
+    }
+
+    #[test]
+    fn test_lint_dao_withdraw() {
+        let ctx = LintContext {
+            network: NetworkType::Testnet,
+            known_cell_deps: HashMap::new(),
+            tip_header: None,
+            max_tx_fee: Some(ONE_CKB),
+        };
+        let dao_type = Script::new_builder()
+            .code_hash(DAO_TYPE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let prepared_cell = cell(sighash_lock(1), 1000 * ONE_CKB)
+            .as_builder()
+            .type_(Some(dao_type).pack())
+            .build();
+        let signed_witness = WitnessArgs::new_builder()
+            .lock(Some(Bytes::from(vec![1u8; SECP_SIGNATURE_SIZE])).pack())
+            .build();
+        // The output includes the interest
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(h256!("0x1").pack(), 0), 0))
+            .output(cell(sighash_lock(1), 1002 * ONE_CKB))
+            .output_data(Bytes::new().pack())
+            .header_dep(h256!("0x2").pack())
+            .header_dep(h256!("0x3").pack())
+            .witness(signed_witness.as_bytes().pack())
+            .build();
+        let inputs = vec![Some((
+            prepared_cell,
+            Bytes::from(100u64.to_le_bytes().to_vec()),
+        ))];
+        let report = lint_tx(&tx, &inputs, &ctx);
+        assert!(report.items.is_empty(), "{:?}", report.to_json());
+    }
+
+    #[test]
+    fn test_check_multisig_witness() {
+        let config = MultisigConfig::new_with(vec![h160!("0x1"), h160!("0x2")], 0, 2).unwrap();
+        let lock_args = config.hash160();
+        let mut witness_lock = config.to_witness_data();
+        witness_lock.extend_from_slice(&[1u8; SECP_SIGNATURE_SIZE * 2]);
+        assert!(check_multisig_witness(lock_args.as_bytes(), &witness_lock).is_ok());
+        assert!(check_multisig_witness(&[0u8; 20], &witness_lock).is_err());
+        assert!(check_multisig_witness(
+            lock_args.as_bytes(),
+            &witness_lock[..witness_lock.len() - 1]
+        )
+        .is_err());
+        let mut placeholder = config.to_witness_data();
+        placeholder.extend_from_slice(&[0u8; SECP_SIGNATURE_SIZE * 2]);
+        assert!(check_multisig_witness(lock_args.as_bytes(), &placeholder).is_err());
+    }
+}